@@ -1,4 +1,4 @@
-use super::db::stores::{BeaconBlockAtSlotError, BeaconBlockStore};
+use super::db::stores::{BeaconBlockStore, BeaconBlockStoreError};
 use super::db::ClientDB;
 use super::types::AttestationData;
 use super::types::Hash256;
@@ -58,10 +58,10 @@ where
     }
 }
 
-impl From<BeaconBlockAtSlotError> for Error {
-    fn from(e: BeaconBlockAtSlotError) -> Self {
+impl From<BeaconBlockStoreError> for Error {
+    fn from(e: BeaconBlockStoreError) -> Self {
         match e {
-            BeaconBlockAtSlotError::DBError(s) => Error::DBError(s),
+            BeaconBlockStoreError::DBError(s) => Error::DBError(s),
             _ => Error::UnableToLookupBlockAtSlot,
         }
     }
@@ -1,22 +1,27 @@
-use super::types::{AttestationData, BeaconState};
+use super::types::AttestationData;
 use super::{Error, Invalid, Outcome};
 
 /// Verify that an attestation's `data.justified_slot` matches the justified slot known to the
-/// `state`.
+/// state.
 ///
 /// In the case that an attestation references a slot _before_ the latest state transition, is
 /// acceptable for the attestation to reference the previous known `justified_slot`. If this were
 /// not the case, all attestations created _prior_ to the last state recalculation would be rejected
 /// if a block was justified in that state recalculation. It is both ideal and likely that blocks
 /// will be justified during a state recalcuation.
+///
+/// The slots are supplied individually (rather than as a state) so that this check may be used
+/// with any state representation.
 pub fn validate_attestation_justified_slot(
     data: &AttestationData,
-    state: &BeaconState,
+    latest_state_recalculation_slot: u64,
+    justified_slot: u64,
+    previous_justified_slot: u64,
 ) -> Result<Outcome, Error> {
-    let permissable_justified_slot = if data.slot >= state.latest_state_recalculation_slot {
-        state.justified_slot
+    let permissable_justified_slot = if data.slot >= latest_state_recalculation_slot {
+        justified_slot
     } else {
-        state.previous_justified_slot
+        previous_justified_slot
     };
     verify_or!(
         data.justified_slot == permissable_justified_slot,
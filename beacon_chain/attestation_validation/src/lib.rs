@@ -1,6 +1,7 @@
 extern crate bls;
 extern crate db;
 extern crate hashing;
+extern crate spec;
 extern crate ssz;
 extern crate ssz_helpers;
 extern crate types;
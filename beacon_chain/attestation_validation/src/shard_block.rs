@@ -1,22 +1,18 @@
-use super::db::ClientDB;
-use super::types::{AttestationData, BeaconState};
+use super::types::{AttestationData, CrosslinkRecord};
 use super::{Error, Invalid, Outcome};
 
-/// Check that an attestation is valid with reference to some state.
-pub fn validate_attestation_data_shard_block_hash<T>(
+/// Check that an attestation is valid with reference to the latest crosslinks known to some state.
+pub fn validate_attestation_data_shard_block_hash(
     data: &AttestationData,
-    state: &BeaconState,
-) -> Result<Outcome, Error>
-where
-    T: ClientDB + Sized,
-{
+    latest_crosslinks: &[CrosslinkRecord],
+) -> Result<Outcome, Error> {
     /*
      * The `shard_block_hash` in the state's `latest_crosslinks` must match either the
      * `latest_crosslink_hash` or the `shard_block_hash` on the attestation.
      *
      * TODO: figure out the reasoning behind this.
      */
-    match state.latest_crosslinks.get(data.shard as usize) {
+    match latest_crosslinks.get(data.shard as usize) {
         None => reject!(Invalid::UnknownShard),
         Some(crosslink) => {
             let local_shard_block_hash = crosslink.shard_block_hash;
@@ -4,13 +4,15 @@ use super::db::ClientDB;
 use super::types::{AttestationData, Bitfield, BitfieldError};
 use super::{Error, Invalid, Outcome};
 
-/// Validate that some signature is correct for some attestation data and known validator set.
+/// Validate that some signature is correct for some attestation data and known validator set, in
+/// some signature domain.
 pub fn validate_attestation_signature<T>(
     attestation_data: &AttestationData,
     participation_bitfield: &Bitfield,
     aggregate_signature: &AggregateSignature,
     attestation_indices: &[usize],
     validator_store: &ValidatorStore<T>,
+    domain: u64,
 ) -> Result<Outcome, Error>
 where
     T: ClientDB + Sized,
@@ -31,10 +33,8 @@ where
         }
     }
 
-    let signed_message = attestation_data_signing_message(attestation_data);
+    let signed_message = attestation_data.signing_message(domain);
     verify_or!(
-        // TODO: ensure "domain" for aggregate signatures is included.
-        // https://github.com/sigp/lighthouse/issues/91
         aggregate_signature.verify(&signed_message, &agg_pub_key),
         reject!(Invalid::SignatureInvalid)
     );
@@ -42,12 +42,6 @@ where
     accept!()
 }
 
-fn attestation_data_signing_message(attestation_data: &AttestationData) -> Vec<u8> {
-    let mut signed_message = attestation_data.canonical_root().to_vec();
-    signed_message.append(&mut vec![0]);
-    signed_message
-}
-
 impl From<ValidatorStoreError> for Error {
     fn from(error: ValidatorStoreError) -> Self {
         match error {
@@ -78,7 +72,8 @@ mod tests {
     #[test]
     fn test_signature_verification() {
         let attestation_data = AttestationData::zero();
-        let message = attestation_data_signing_message(&attestation_data);
+        let domain = 1;
+        let message = attestation_data.signing_message(domain);
         let signing_keypairs = vec![
             Keypair::random(),
             Keypair::random(),
@@ -129,9 +124,23 @@ mod tests {
             &agg_sig,
             &attestation_indices,
             &store,
+            domain,
         ).unwrap();
         assert_eq!(outcome, Outcome::Valid);
 
+        /*
+         * Run validation in another domain and assert that it fails.
+         */
+        let outcome = validate_attestation_signature(
+            &attestation_data,
+            &bitfield,
+            &agg_sig,
+            &attestation_indices,
+            &store,
+            domain + 1,
+        ).unwrap();
+        assert_eq!(outcome, Outcome::Invalid(Invalid::SignatureInvalid));
+
         /*
          * Add another validator to the bitfield, run validation will all other
          * parameters the same and assert that it fails.
@@ -143,6 +152,7 @@ mod tests {
             &agg_sig,
             &attestation_indices,
             &store,
+            domain,
         ).unwrap();
         assert_eq!(outcome, Outcome::Invalid(Invalid::SignatureInvalid));
     }
@@ -1,29 +1,1290 @@
 use super::BeaconChain;
-use db::ClientDB;
-use types::Hash256;
+use attestation_validation::{
+    validate_attestation_data_shard_block_hash, validate_attestation_for_block,
+    validate_attestation_justified_block_hash, validate_attestation_justified_slot,
+    validate_attestation_signature, Error as AttestationValidationError, Invalid, Outcome,
+};
+use db::stores::{
+    BeaconBlockStoreError, ChainMetadata, ChainMetadataStoreError, StateStoreError,
+    MAX_HEAD_BLOCK_HASHES,
+};
+use db::{ClientDB, DBError, WriteBatch};
+use fork_choice::{ForkChoice, ForkChoiceError};
+use maps::{generate_attester_and_proposer_maps_for_state, AttesterAndProposerMapError};
+use ssz::{Decodable, DecodeError};
+use ssz_helpers::ssz_beacon_block::{SszBeaconBlock, SszBeaconBlockError};
+use state_transition::StateTransitionError;
+use std::borrow::Cow;
+use std::sync::Arc;
+use types::{
+    ActiveState, Attestation, AttesterMap, BeaconBlock, CrystallizedState, Hash256, ProposerMap,
+};
 
+#[derive(Debug, PartialEq)]
 pub enum BlockProcessingOutcome {
+    /// The block was already present in the database, no processing was done.
     BlockAlreadyKnown,
+    /// The block was valid and extended the canonical chain.
     NewCanonicalBlock,
+    /// The block was valid and caused the canonical chain to switch to its fork.
     NewReorgBlock,
+    /// The block was valid but the fork choice did not select it as the head.
     NewForkBlock,
 }
 
+#[derive(Debug, PartialEq)]
 pub enum Error {
-    NotImplemented,
+    /// The SSZ could not be interpreted as a block.
+    InvalidBlockSsz(SszBeaconBlockError),
+    /// The block SSZ could not be fully decoded.
+    BlockDecodeError(DecodeError),
+    /// The block does not declare a parent in its `ancestor_hashes`.
+    NoParentHash,
+    /// The parent of the block is not known to the database.
+    UnknownParentBlock,
+    /// The parent of the block is stored in the database, but could not be decoded.
+    InvalidParentBlock,
+    /// The block slot is later than the present slot.
+    FutureSlot,
+    /// The block slot is not greater than the slot of its parent.
+    SlotNotAfterParent,
+    /// The block includes more attestations than the spec permits.
+    TooManyAttestations,
+    /// The active state referenced by the parent block is not known.
+    UnknownActiveState,
+    /// The crystallized state referenced by the parent block is not known.
+    UnknownCrystallizedState,
+    /// There are no attester and proposer maps for the parent crystallized state.
+    UnknownAttesterProposerMaps,
+    /// An attestation references a `(slot, shard)` without a known committee.
+    NoCommitteeForShard,
+    /// An attestation included in the block was found to be invalid.
+    InvalidAttestation(Invalid),
+    /// An attestation could not be validated due to some unexpected condition.
+    AttestationValidationError(AttestationValidationError),
+    /// The state transition function was unable to transition the parent states.
+    StateTransitionError(StateTransitionError),
+    /// The `active_state_root` in the block does not match the calculated active state.
+    ActiveStateRootMismatch,
+    /// The `crystallized_state_root` in the block does not match the calculated crystallized
+    /// state.
+    CrystallizedStateRootMismatch,
+    /// There is no proposer assigned to the block slot.
+    NoProposerForSlot,
+    /// The proposer assigned to the block slot is not in the validator registry.
+    UnknownProposer,
+    /// The block signature was not made by the proposer assigned to the block slot.
+    InvalidProposerSignature,
+    /// The fork version at the slot of the block or an attestation cannot be included in a
+    /// signature domain.
+    InvalidSignatureDomain,
+    UnableToGenerateMaps(AttesterAndProposerMapError),
+    /// The fork choice selected a head which is not one of the known head blocks.
+    UnknownHeadBlock,
+    /// The block creates a new fork while the chain already has `MAX_HEAD_BLOCK_HASHES` head
+    /// blocks.
+    TooManyHeadBlocks,
+    ForkChoiceError(ForkChoiceError),
+    BlockStoreError(BeaconBlockStoreError),
+    DBError(String),
 }
 
-impl<T> BeaconChain<T>
+impl<T, F> BeaconChain<T, F>
 where
     T: ClientDB + Sized,
+    F: ForkChoice,
 {
+    /// Process some serialized block, returning the outcome of processing and the hash of the
+    /// block.
+    ///
+    /// The block is fully validated (including all attestations and the resulting state roots)
+    /// before it is written to the database and added to the set of head blocks. An `Err` is
+    /// returned if the block is invalid; invalid blocks are never stored.
+    ///
+    /// Before it is stored, the block and its attestations are supplied to the fork choice which
+    /// then determines the new canonical head.
     pub fn process_block(
         &mut self,
-        _ssz: &[u8],
-        _present_slot: u64,
+        ssz: &[u8],
+        present_slot: u64,
     ) -> Result<(BlockProcessingOutcome, Hash256), Error> {
-        // TODO: block processing has been removed.
-        // https://github.com/sigp/lighthouse/issues/98
-        Err(Error::NotImplemented)
+        /*
+         * Generate a SszBlock to read directly from the serialized SSZ.
+         */
+        let ssz_block = SszBeaconBlock::from_slice(ssz)?;
+        let block_hash = Hash256::from(&ssz_block.block_hash()[..]);
+
+        /*
+         * If this block is already known, return immediately and indicate the block is known. Don't
+         * attempt to deserialize the block.
+         */
+        if self.store.block.block_exists(&block_hash)? {
+            return Ok((BlockProcessingOutcome::BlockAlreadyKnown, block_hash));
+        }
+
+        /*
+         * Blocks from the future are rejected before any further work is done.
+         */
+        if ssz_block.slot() > present_slot {
+            return Err(Error::FutureSlot);
+        }
+
+        /*
+         * Load the parent block from the database.
+         *
+         * A block must build upon a block which is already known locally.
+         */
+        let parent_hash = Hash256::from(ssz_block.parent_hash().ok_or(Error::NoParentHash)?);
+        let parent_block = {
+            let parent_ssz = self
+                .store
+                .block
+                .get_serialized_block(&parent_hash)?
+                .ok_or(Error::UnknownParentBlock)?;
+            let (parent_block, _) =
+                BeaconBlock::ssz_decode(&parent_ssz, 0).map_err(|_| Error::InvalidParentBlock)?;
+            parent_block
+        };
+
+        if ssz_block.slot() <= parent_block.slot {
+            return Err(Error::SlotNotAfterParent);
+        }
+
+        /*
+         * Fully deserialize the block.
+         */
+        let (block, _) = BeaconBlock::ssz_decode(ssz_block.block_ssz(), 0)?;
+
+        if block.attestations.len() as u64 > self.spec.max_attestations {
+            return Err(Error::TooManyAttestations);
+        }
+
+        /*
+         * Validate the block against the states referenced by the parent and produce the new
+         * states which result from applying the block.
+         */
+        let (new_act_state, new_cry_state, new_maps, attestation_targets) = {
+            let parent_act_state = self
+                .active_states
+                .get(&parent_block.active_state_root)
+                .ok_or(Error::UnknownActiveState)?;
+            let parent_cry_state = self
+                .crystallized_states
+                .get(&parent_block.crystallized_state_root)
+                .ok_or(Error::UnknownCrystallizedState)?;
+            let parent_maps = self
+                .attester_proposer_maps
+                .get(&parent_block.crystallized_state_root)
+                .cloned()
+                .ok_or(Error::UnknownAttesterProposerMaps)?;
+
+            /*
+             * If the block is at least one cycle beyond the last state recalculation of its
+             * parent, the states are recalculated before the block is applied. The proposer of
+             * the block is then assigned by the recalculated crystallized state, rather than that
+             * of the parent, so maps are generated for it.
+             *
+             * Otherwise the committees are unchanged and the maps of the parent are used.
+             */
+            let (pre_act_state, pre_cry_state) =
+                self.pre_block_states(parent_act_state, parent_cry_state, block.slot)?;
+            let maps = match pre_cry_state {
+                Cow::Owned(ref cry_state) => {
+                    let (attester_map, proposer_map) =
+                        generate_attester_and_proposer_maps_for_state(
+                            cry_state,
+                            self.spec.epoch_length,
+                        )?;
+                    (Arc::new(attester_map), Arc::new(proposer_map))
+                }
+                Cow::Borrowed(_) => parent_maps.clone(),
+            };
+
+            /*
+             * The block must be signed by the proposer assigned to its slot. The signature is
+             * verified before the attestations and the state transition.
+             */
+            self.validate_block_signature(&block, &pre_cry_state, &maps.1)?;
+
+            /*
+             * Validate each attestation included in the block, collecting the block targeted by
+             * each participating validator.
+             */
+            let mut attestation_targets: Vec<(usize, Hash256)> = vec![];
+            for attestation in &block.attestations {
+                let participants = self.validate_block_attestation(
+                    attestation,
+                    &block,
+                    &parent_block,
+                    &parent_hash,
+                    parent_cry_state,
+                    &parent_maps.0,
+                )?;
+                for validator_index in participants {
+                    attestation_targets.push((validator_index, attestation.data.beacon_block_hash));
+                }
+            }
+
+            /*
+             * A block commits to the roots of its post-states, so those states cannot contain
+             * the hash of the block itself. The `recent_block_hashes` are instead extended with
+             * the hash of the parent.
+             */
+            let (new_act_state, new_cry_state) =
+                self.apply_block(&pre_act_state, &pre_cry_state, &block, &parent_hash)?;
+            (new_act_state, new_cry_state, maps, attestation_targets)
+        };
+
+        /*
+         * The state roots declared in the block must match the states which result from
+         * processing the block.
+         */
+        let new_act_state_root = new_act_state.canonical_root();
+        if block.active_state_root != new_act_state_root {
+            return Err(Error::ActiveStateRootMismatch);
+        }
+        let new_cry_state_root = new_cry_state.canonical_root();
+        if block.crystallized_state_root != new_cry_state_root {
+            return Err(Error::CrystallizedStateRootMismatch);
+        }
+
+        /*
+         * The block is valid, inform the fork choice of the block and the attestations it
+         * contains and find the new head of the chain.
+         *
+         * This is done on a clone of the fork choice, which only replaces it once the block has
+         * been stored. A block which is rejected from here on therefore leaves the fork choice
+         * (including the latest attestation of each validator) unchanged.
+         *
+         * Attestations are weighted by the validator balances of the crystallized state
+         * referenced by the new block. Only descendants of the finalized block are searched.
+         */
+        let mut fork_choice = self.fork_choice.clone();
+        fork_choice.add_block(&block, &block_hash)?;
+        for (validator_index, target) in &attestation_targets {
+            fork_choice.add_attestation(*validator_index, target)?;
+        }
+        let head = fork_choice.find_head(&self.finalized_block_hash, &new_cry_state.validators)?;
+        /*
+         * The head must be the new block or one of the existing heads, other than the parent
+         * (which is no longer a head).
+         */
+        if head != block_hash && (head == parent_hash || !self.head_block_hashes.contains(&head)) {
+            return Err(Error::UnknownHeadBlock);
+        }
+
+        /*
+         * Determine the new head blocks and the index of the canonical head amongst them.
+         */
+        let parent_was_canonical = parent_hash == self.canonical_block_hash();
+        let mut head_block_hashes = self.head_block_hashes.clone();
+        update_head_block_hashes(&mut head_block_hashes, &parent_hash, block_hash);
+        if head_block_hashes.len() > MAX_HEAD_BLOCK_HASHES {
+            return Err(Error::TooManyHeadBlocks);
+        }
+        let canonical_head_block_hash = head_block_hashes
+            .iter()
+            .position(|hash| *hash == head)
+            .ok_or(Error::UnknownHeadBlock)?;
+        let metadata = ChainMetadata {
+            head_block_hashes: head_block_hashes.clone(),
+            canonical_head_block_hash,
+            ..self.metadata()
+        };
+
+        /*
+         * The states, the block, the canonical head and the chain metadata are written in a
+         * single batch so that the database always holds a chain which may be resumed from.
+         */
+        self.store_block(
+            &block_hash,
+            ssz_block.block_ssz(),
+            (&new_act_state_root, &new_act_state),
+            (&new_cry_state_root, &new_cry_state),
+            &head,
+            &metadata,
+        )?;
+
+        self.fork_choice = fork_choice;
+        self.active_states.insert(new_act_state_root, new_act_state);
+        self.attester_proposer_maps
+            .insert(new_cry_state_root, new_maps);
+        self.crystallized_states
+            .insert(new_cry_state_root, new_cry_state);
+        self.head_block_hashes = head_block_hashes;
+        self.canonical_head_block_hash = canonical_head_block_hash;
+
+        /*
+         * Anything included in the block need not be included again by block production. If the
+         * block is the new head, attestations which are too old for its children are removed.
+         */
+        self.pending_attestations
+            .retain(|attestation| !block.attestations.contains(attestation));
+        self.pending_specials
+            .retain(|(_, special)| !block.specials.contains(special));
+        if head == block_hash {
+            self.expire_pending_attestations(block.slot);
+        }
+
+        /*
+         * If the new head has advanced finality, prune the forks which do not descend from the
+         * finalized block.
+         *
+         * The block is already stored, so a pruning failure does not reject it. The failure is
+         * instead recorded in `self.pruning_error`.
+         */
+        self.pruning_error = self.update_finalization().err();
+
+        let outcome = if head != block_hash {
+            BlockProcessingOutcome::NewForkBlock
+        } else if parent_was_canonical {
+            BlockProcessingOutcome::NewCanonicalBlock
+        } else {
+            BlockProcessingOutcome::NewReorgBlock
+        };
+
+        Ok((outcome, block_hash))
+    }
+
+    /// Validate some attestation for inclusion in `block`, with reference to the parent of the
+    /// block and the crystallized state of that parent.
+    ///
+    /// Checks are ordered from least to most expensive, the signature is verified last.
+    ///
+    /// Returns the indices of the validators which participated in the attestation.
+    pub(crate) fn validate_block_attestation(
+        &self,
+        attestation: &Attestation,
+        block: &BeaconBlock,
+        parent_block: &BeaconBlock,
+        parent_hash: &Hash256,
+        cry_state: &CrystallizedState,
+        attester_map: &AttesterMap,
+    ) -> Result<Vec<usize>, Error> {
+        let data = &attestation.data;
+
+        ensure_valid(validate_attestation_for_block(
+            data.slot,
+            block.slot,
+            parent_block.slot,
+            &self.spec,
+        )?)?;
+
+        ensure_valid(validate_attestation_justified_slot(
+            data,
+            cry_state.last_state_recalculation_slot,
+            cry_state.last_justified_slot,
+            cry_state.previous_justified_slot,
+        )?)?;
+
+        ensure_valid(validate_attestation_data_shard_block_hash(
+            data,
+            &cry_state.crosslinks,
+        )?)?;
+
+        ensure_valid(validate_attestation_justified_block_hash(
+            data,
+            parent_hash,
+            &self.store.block,
+        )?)?;
+
+        /*
+         * Find the committee which is expected to have produced the attestation.
+         */
+        let shard = data.shard;
+        if shard > u64::from(u16::max_value()) {
+            return Err(Error::NoCommitteeForShard);
+        }
+        let attestation_indices = attester_map
+            .get(&(data.slot, shard as u16))
+            .ok_or(Error::NoCommitteeForShard)?;
+
+        let domain = cry_state
+            .fork_data()
+            .domain(data.slot, self.spec.domain_attestation)
+            .ok_or(Error::InvalidSignatureDomain)?;
+        ensure_valid(validate_attestation_signature(
+            data,
+            &attestation.participation_bitfield,
+            &attestation.aggregate_sig,
+            attestation_indices,
+            &self.store.validator,
+            domain,
+        )?)?;
+
+        Ok(attestation_participants(attestation, attestation_indices))
+    }
+
+    /// Verify that `block` is signed by the proposer assigned to the block slot in `proposer_map`,
+    /// where `cry_state` is the crystallized state from which `proposer_map` was generated.
+    ///
+    /// The signature is in the proposal domain of the fork version at the block slot.
+    fn validate_block_signature(
+        &self,
+        block: &BeaconBlock,
+        cry_state: &CrystallizedState,
+        proposer_map: &ProposerMap,
+    ) -> Result<(), Error> {
+        let proposer_index = proposer_map
+            .get(&block.slot)
+            .ok_or(Error::NoProposerForSlot)?;
+        let proposer = cry_state
+            .validators
+            .get(*proposer_index)
+            .ok_or(Error::UnknownProposer)?;
+
+        let domain = cry_state
+            .fork_data()
+            .domain(block.slot, self.spec.domain_proposal)
+            .ok_or(Error::InvalidSignatureDomain)?;
+        if block
+            .signature
+            .verify(&block.proposal_signing_message(domain), &proposer.pubkey)
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidProposerSignature)
+        }
+    }
+
+    /// Atomically store a valid block along with its post-states, the new canonical head and the
+    /// chain metadata.
+    fn store_block(
+        &self,
+        block_hash: &Hash256,
+        block_ssz: &[u8],
+        act_state: (&Hash256, &ActiveState),
+        cry_state: (&Hash256, &CrystallizedState),
+        head: &Hash256,
+        metadata: &ChainMetadata,
+    ) -> Result<(), Error> {
+        let mut batch = WriteBatch::new();
+        self.store
+            .state
+            .put_active_state_in_batch(&mut batch, act_state.0, act_state.1);
+        self.store
+            .state
+            .put_crystallized_state_in_batch(&mut batch, cry_state.0, cry_state.1);
+        self.store
+            .block
+            .put_block_in_batch(&mut batch, block_hash, block_ssz)?;
+        self.store
+            .block
+            .set_canonical_head_in_batch(&mut batch, head)?;
+        self.store
+            .metadata
+            .put_metadata_in_batch(&mut batch, metadata);
+        self.store.write_batch(batch)?;
+        Ok(())
+    }
+}
+
+/// Replace `parent_hash` in `head_block_hashes` with `block_hash`.
+///
+/// If the parent is not a head block (i.e., the new block creates a fork) the new block is simply
+/// added as a new head.
+fn update_head_block_hashes(
+    head_block_hashes: &mut Vec<Hash256>,
+    parent_hash: &Hash256,
+    block_hash: Hash256,
+) {
+    head_block_hashes.retain(|hash| hash != parent_hash);
+    head_block_hashes.push(block_hash);
+}
+
+/// Returns the indices of the validators in `attestation_indices` (the committee of the
+/// attestation) which are marked as participating by the attestation bitfield.
+pub(crate) fn attestation_participants(
+    attestation: &Attestation,
+    attestation_indices: &[usize],
+) -> Vec<usize> {
+    attestation_indices
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| attestation.participation_bitfield.get(*i).unwrap_or(false))
+        .map(|(_, validator_index)| *validator_index)
+        .collect()
+}
+
+/// Convert an attestation validation `Outcome` into a `Result`.
+fn ensure_valid(outcome: Outcome) -> Result<(), Error> {
+    match outcome {
+        Outcome::Valid => Ok(()),
+        Outcome::Invalid(reason) => Err(Error::InvalidAttestation(reason)),
+    }
+}
+
+impl From<SszBeaconBlockError> for Error {
+    fn from(e: SszBeaconBlockError) -> Error {
+        Error::InvalidBlockSsz(e)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Error {
+        Error::BlockDecodeError(e)
+    }
+}
+
+impl From<AttestationValidationError> for Error {
+    fn from(e: AttestationValidationError) -> Error {
+        Error::AttestationValidationError(e)
+    }
+}
+
+impl From<StateTransitionError> for Error {
+    fn from(e: StateTransitionError) -> Error {
+        Error::StateTransitionError(e)
+    }
+}
+
+impl From<AttesterAndProposerMapError> for Error {
+    fn from(e: AttesterAndProposerMapError) -> Error {
+        Error::UnableToGenerateMaps(e)
+    }
+}
+
+impl From<ForkChoiceError> for Error {
+    fn from(e: ForkChoiceError) -> Error {
+        Error::ForkChoiceError(e)
+    }
+}
+
+impl From<BeaconBlockStoreError> for Error {
+    fn from(e: BeaconBlockStoreError) -> Error {
+        Error::BlockStoreError(e)
+    }
+}
+
+impl From<DBError> for Error {
+    fn from(e: DBError) -> Error {
+        Error::DBError(e.message)
+    }
+}
+
+impl From<StateStoreError> for Error {
+    fn from(e: StateStoreError) -> Error {
+        match e {
+            StateStoreError::DBError(s) => Error::DBError(s),
+            StateStoreError::DecodeError => Error::DBError("Unable to decode state".to_string()),
+        }
+    }
+}
+
+impl From<ChainMetadataStoreError> for Error {
+    fn from(e: ChainMetadataStoreError) -> Error {
+        match e {
+            ChainMetadataStoreError::DBError(s) => Error::DBError(s),
+            ChainMetadataStoreError::DecodeError => {
+                Error::DBError("Unable to decode chain metadata".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate bls;
+
+    use self::bls::{Keypair, Signature};
+    use super::*;
+    use db::{DiskDB, DiskDBConfig, MemoryDB};
+    use fork_choice::{LmdGhostForkChoice, NaiveForkChoice};
+    use hashing::canonical_hash;
+    use ssz::ssz_encode;
+    use state_transition::SpecialProcessingError;
+    use std::collections::HashSet;
+    use std::{env, fs, process};
+    use test_utils::*;
+    use types::{ValidatorRecord, ValidatorStatus};
+    use {BeaconChain, BeaconChainError, BeaconChainStore};
+
+    #[test]
+    fn test_process_block_canonical() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let (outcome, hash) = process(&mut chain, &block, 1).unwrap();
+
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+        assert_eq!(chain.canonical_block_hash(), hash);
+        assert_eq!(chain.head_block_hashes, vec![hash]);
+        assert!(chain.store.block.block_exists(&hash).unwrap());
+
+        let (outcome, known_hash) = process(&mut chain, &block, 1).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::BlockAlreadyKnown);
+        assert_eq!(known_hash, hash);
+    }
+
+    #[test]
+    fn test_process_block_across_cycle_boundary() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let block = child_block(&chain, &genesis_hash, 9, vec![], &keypairs);
+        let (outcome, _) = process(&mut chain, &block, 9).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+
+        let cry_state = &chain.crystallized_states[&block.crystallized_state_root];
+        assert_eq!(cry_state.last_state_recalculation_slot, 8);
+        assert!(chain
+            .attester_proposer_maps
+            .contains_key(&block.crystallized_state_root));
+    }
+
+    #[test]
+    fn test_process_block_fork_and_reorg() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let a = child_block(&chain, &genesis_hash, 2, vec![], &keypairs);
+        let (outcome, a_hash) = process(&mut chain, &a, 3).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+
+        let b = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let (outcome, b_hash) = process(&mut chain, &b, 3).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewForkBlock);
+        assert_eq!(chain.canonical_block_hash(), a_hash);
+        assert_eq!(chain.head_block_hashes.len(), 2);
+
+        let c = child_block(&chain, &b_hash, 3, vec![], &keypairs);
+        let (outcome, c_hash) = process(&mut chain, &c, 3).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewReorgBlock);
+        assert_eq!(chain.canonical_block_hash(), c_hash);
+        assert_eq!(chain.head_block_hashes, vec![a_hash, c_hash]);
+
+        /*
+         * The canonical slot index follows the re-org.
+         */
+        let canonical_at = |slot| {
+            chain
+                .store
+                .block
+                .canonical_block_hash_at_slot(slot)
+                .unwrap()
+        };
+        assert_eq!(canonical_at(1), Some(b_hash.to_vec()));
+        assert_eq!(canonical_at(2), None);
+        assert_eq!(canonical_at(3), Some(c_hash.to_vec()));
+    }
+
+    #[test]
+    fn test_process_block_invalid() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+        let genesis_cry_state = chain.crystallized_states.values().next().unwrap().clone();
+
+        let block = child_block(&chain, &genesis_hash, 2, vec![], &keypairs);
+        assert_eq!(process(&mut chain, &block, 1), Err(Error::FutureSlot));
+
+        let block = child_block(&chain, &genesis_hash, 0, vec![], &keypairs);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::SlotNotAfterParent)
+        );
+
+        let mut block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        block.ancestor_hashes = vec![Hash256::from("unknown".as_bytes())];
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::UnknownParentBlock)
+        );
+
+        let mut block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        block.active_state_root = Hash256::from("bad_root".as_bytes());
+        sign_block(&chain, &mut block, &genesis_cry_state, &keypairs);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::ActiveStateRootMismatch)
+        );
+
+        let mut block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        block.crystallized_state_root = Hash256::from("bad_root".as_bytes());
+        sign_block(&chain, &mut block, &genesis_cry_state, &keypairs);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::CrystallizedStateRootMismatch)
+        );
+
+        assert_eq!(
+            chain.process_block(&[42; 8], 1),
+            Err(Error::InvalidBlockSsz(SszBeaconBlockError::TooShort))
+        );
+
+        /*
+         * None of the invalid blocks should have changed the chain.
+         */
+        assert_eq!(chain.head_block_hashes, vec![genesis_hash]);
+    }
+
+    #[test]
+    fn test_process_block_too_many_attestations() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+        chain.spec.max_attestations = 1;
+
+        let attestation = signed_attestation(&chain, 0, Hash256::zero(), &keypairs);
+        let block = child_block(
+            &chain,
+            &genesis_hash,
+            1,
+            vec![attestation.clone(), attestation.clone()],
+            &keypairs,
+        );
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::TooManyAttestations)
+        );
+        assert_eq!(chain.head_block_hashes, vec![genesis_hash]);
+
+        let block = child_block(&chain, &genesis_hash, 1, vec![attestation], &keypairs);
+        assert!(process(&mut chain, &block, 1).is_ok());
+    }
+
+    /// A fork choice which fails to find the head when `fail` is set, and otherwise defers to
+    /// `inner`.
+    #[derive(Clone)]
+    struct FailingForkChoice<F: ForkChoice> {
+        inner: F,
+        fail: bool,
+    }
+
+    impl<F: ForkChoice> ForkChoice for FailingForkChoice<F> {
+        fn add_block(
+            &mut self,
+            block: &BeaconBlock,
+            block_hash: &Hash256,
+        ) -> Result<(), ForkChoiceError> {
+            self.inner.add_block(block, block_hash)
+        }
+
+        fn add_attestation(
+            &mut self,
+            validator_index: usize,
+            target_block_hash: &Hash256,
+        ) -> Result<(), ForkChoiceError> {
+            self.inner
+                .add_attestation(validator_index, target_block_hash)
+        }
+
+        fn remove_blocks(
+            &mut self,
+            block_hashes: &HashSet<Hash256>,
+        ) -> Result<(), ForkChoiceError> {
+            self.inner.remove_blocks(block_hashes)
+        }
+
+        fn find_head(
+            &mut self,
+            justified_block_hash: &Hash256,
+            validators: &[ValidatorRecord],
+        ) -> Result<Hash256, ForkChoiceError> {
+            if self.fail {
+                return Err(ForkChoiceError::MissingBlock);
+            }
+            self.inner.find_head(justified_block_hash, validators)
+        }
+    }
+
+    #[test]
+    fn test_process_block_fork_choice_error() {
+        let (store, spec, keypairs) = test_store_and_spec(8);
+        let fork_choice = FailingForkChoice {
+            inner: NaiveForkChoice::new(),
+            fail: true,
+        };
+        let mut chain = BeaconChain::new(store, spec, fork_choice).unwrap();
+        let genesis_hash = chain.canonical_block_hash();
+
+        let block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::ForkChoiceError(ForkChoiceError::MissingBlock))
+        );
+
+        /*
+         * Nothing was stored, and the block was not added to the fork choice.
+         */
+        let block_hash = Hash256::from(&canonical_hash(&ssz_encode(&block))[..]);
+        assert!(!chain.store.block.block_exists(&block_hash).unwrap());
+        assert_eq!(chain.head_block_hashes, vec![genesis_hash]);
+        assert_eq!(chain.active_states.len(), 1);
+
+        chain.fork_choice.fail = false;
+        assert_eq!(
+            chain.fork_choice.find_head(&genesis_hash, &[]),
+            Ok(genesis_hash)
+        );
+        let (outcome, hash) = process(&mut chain, &block, 1).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+        assert_eq!(hash, block_hash);
+    }
+
+    #[test]
+    fn test_process_block_fork_choice_error_keeps_latest_attestations() {
+        let (store, spec, keypairs) = test_store_and_spec(8);
+        let fork_choice = FailingForkChoice {
+            inner: LmdGhostForkChoice::new(store.block.clone()),
+            fail: false,
+        };
+        let mut chain = BeaconChain::new(store, spec, fork_choice).unwrap();
+        let genesis_hash = chain.canonical_block_hash();
+
+        let a = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let (_, a_hash) = process(&mut chain, &a, 2).unwrap();
+        let b = child_block(&chain, &genesis_hash, 2, vec![], &keypairs);
+        let (_, b_hash) = process(&mut chain, &b, 2).unwrap();
+
+        let attestation = signed_attestation(&chain, 0, a_hash, &keypairs);
+        let c = child_block(&chain, &a_hash, 3, vec![attestation], &keypairs);
+        let (_, c_hash) = process(&mut chain, &c, 3).unwrap();
+        assert_eq!(chain.canonical_block_hash(), c_hash);
+
+        /*
+         * A block which moves the same attestations to `b` is rejected by the fork choice.
+         */
+        chain.fork_choice.fail = true;
+        let attestation = signed_attestation(&chain, 0, b_hash, &keypairs);
+        let d = child_block(&chain, &b_hash, 3, vec![attestation], &keypairs);
+        assert_eq!(
+            process(&mut chain, &d, 3),
+            Err(Error::ForkChoiceError(ForkChoiceError::MissingBlock))
+        );
+
+        /*
+         * The attestations of the rejected block did not replace those to `a`.
+         */
+        chain.fork_choice.fail = false;
+        let validators = &chain.crystallized_states[&c.crystallized_state_root].validators;
+        assert_eq!(
+            chain.fork_choice.find_head(&genesis_hash, validators),
+            Ok(c_hash)
+        );
+    }
+
+    #[test]
+    fn test_process_block_invalid_signature() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let genesis_cry_state_root = *chain.crystallized_states.keys().next().unwrap();
+
+        let mut block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let proposer = chain.attester_proposer_maps[&genesis_cry_state_root].1[&1];
+        let fork_data = chain.crystallized_states[&genesis_cry_state_root].fork_data();
+
+        /*
+         * A block signed by some validator other than the proposer.
+         */
+        let proposal_domain = fork_data.domain(1, chain.spec.domain_proposal).unwrap();
+        let proposal_message = block.proposal_signing_message(proposal_domain);
+        block.signature = Signature::new(&proposal_message, &keypairs[(proposer + 1) % 8].sk);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::InvalidProposerSignature)
+        );
+
+        /*
+         * A block signed by the proposer, but in the attestation domain.
+         */
+        let attestation_domain = fork_data.domain(1, chain.spec.domain_attestation).unwrap();
+        let attestation_message = block.proposal_signing_message(attestation_domain);
+        block.signature = Signature::new(&attestation_message, &keypairs[proposer].sk);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::InvalidProposerSignature)
+        );
+
+        block.signature = Signature::empty_signature();
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::InvalidProposerSignature)
+        );
+
+        block.signature = Signature::new(&proposal_message, &keypairs[proposer].sk);
+        assert!(process(&mut chain, &block, 1).is_ok());
+    }
+
+    #[test]
+    fn test_process_block_signature_checked_before_transition() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        /*
+         * A block which is invalid in several ways is rejected for its signature, as that is
+         * verified before the state transition.
+         */
+        let mut block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        block.randao_reveal = Hash256::zero();
+        block.signature = Signature::empty_signature();
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::InvalidProposerSignature)
+        );
+    }
+
+    #[test]
+    fn test_process_block_signature_across_cycle_boundary() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+        let genesis_cry_state = chain.crystallized_states.values().next().unwrap().clone();
+
+        /*
+         * The crystallized state of the parent assigns no proposer beyond the cycle boundary, the
+         * proposer is assigned by the recalculated crystallized state.
+         */
+        let genesis_maps = &chain.attester_proposer_maps[&genesis_cry_state.canonical_root()];
+        assert!(!genesis_maps.1.contains_key(&9));
+
+        let mut block = child_block(&chain, &genesis_hash, 9, vec![], &keypairs);
+        let signature = block.signature.clone();
+        let cry_state = chain
+            .pre_block_states(
+                chain.active_states.values().next().unwrap(),
+                &genesis_cry_state,
+                9,
+            )
+            .unwrap()
+            .1
+            .into_owned();
+        let (_, proposer_map) =
+            generate_attester_and_proposer_maps_for_state(&cry_state, chain.spec.epoch_length)
+                .unwrap();
+        let proposer = proposer_map[&9];
+
+        /*
+         * A block signed by any other validator is rejected.
+         */
+        let domain = cry_state
+            .fork_data()
+            .domain(9, chain.spec.domain_proposal)
+            .unwrap();
+        block.signature = Signature::new(
+            &block.proposal_signing_message(domain),
+            &keypairs[(proposer + 1) % 8].sk,
+        );
+        assert_eq!(
+            process(&mut chain, &block, 9),
+            Err(Error::InvalidProposerSignature)
+        );
+
+        block.signature = signature;
+        assert!(process(&mut chain, &block, 9).is_ok());
+    }
+
+    #[test]
+    fn test_process_block_invalid_randao_reveal() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+        let genesis_cry_state = chain.crystallized_states.values().next().unwrap().clone();
+        let proposer = chain.attester_proposer_maps[&genesis_cry_state.canonical_root()].1[&1];
+
+        let mut block = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let reveal = block.randao_reveal;
+
+        /*
+         * The reveal must hash to the commitment of the proposer. At slot 1 only a single layer
+         * may be revealed, so neither the commitment itself nor a layer two beneath it is valid.
+         */
+        let onion = test_hash_onion(proposer);
+        for wrong_reveal in &[
+            Hash256::zero(),
+            onion.commitment(),
+            onion.reveal(&onion.commitment(), 2).unwrap(),
+        ] {
+            block.randao_reveal = *wrong_reveal;
+            sign_block(&chain, &mut block, &genesis_cry_state, &keypairs);
+            assert_eq!(
+                process(&mut chain, &block, 1),
+                Err(Error::StateTransitionError(
+                    StateTransitionError::InvalidRandaoReveal
+                ))
+            );
+        }
+        assert_eq!(chain.head_block_hashes, vec![genesis_hash]);
+
+        /*
+         * A valid reveal becomes the commitment of the proposer.
+         */
+        block.randao_reveal = reveal;
+        sign_block(&chain, &mut block, &genesis_cry_state, &keypairs);
+        process(&mut chain, &block, 1).unwrap();
+        let validators = &chain.crystallized_states[&block.crystallized_state_root].validators;
+        assert_eq!(validators[proposer].randao_commitment, reveal);
+        assert_eq!(validators[proposer].randao_last_change, 1);
+    }
+
+    #[test]
+    fn test_process_block_with_attestation() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let attestation = signed_attestation(&chain, 0, Hash256::zero(), &keypairs);
+        let block = child_block(&chain, &genesis_hash, 1, vec![attestation], &keypairs);
+        let (outcome, _) = process(&mut chain, &block, 1).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+    }
+
+    #[test]
+    fn test_process_block_with_invalid_attestation() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let wrong_keypairs: Vec<Keypair> = (0..8).map(|_| Keypair::random()).collect();
+        let attestation = signed_attestation(&chain, 0, Hash256::zero(), &wrong_keypairs);
+        let block = child_block(&chain, &genesis_hash, 1, vec![attestation], &keypairs);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::InvalidAttestation(Invalid::SignatureInvalid))
+        );
+
+        let mut attestation = signed_attestation(&chain, 0, Hash256::zero(), &wrong_keypairs);
+        attestation.data.justified_slot = 1;
+        let block = child_block(&chain, &genesis_hash, 1, vec![attestation], &keypairs);
+        assert_eq!(
+            process(&mut chain, &block, 1),
+            Err(Error::InvalidAttestation(
+                Invalid::JustifiedSlotImpermissable
+            ))
+        );
+    }
+
+    #[test]
+    fn test_process_block_with_specials() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+        let genesis_cry_state = chain.crystallized_states.values().next().unwrap().clone();
+        let commitment = Hash256::from("commitment".as_bytes());
+
+        let specials = vec![
+            signed_logout(&chain, &genesis_cry_state, 1, 2, &keypairs),
+            signed_randao_change(&chain, &genesis_cry_state, 1, commitment, &keypairs),
+        ];
+        let block =
+            child_block_with_specials(&chain, &genesis_hash, 1, vec![], specials, &keypairs);
+        process(&mut chain, &block, 1).unwrap();
+
+        /*
+         * The specials are applied to the validators of the crystallized state of the block.
+         */
+        let proposer = chain.attester_proposer_maps[&block.crystallized_state_root].1[&1];
+        let validators = &chain.crystallized_states[&block.crystallized_state_root].validators;
+        assert_eq!(validators[2].status, ValidatorStatus::PendingExit);
+        assert_eq!(validators[proposer].randao_commitment, commitment);
+        assert_eq!(validators[proposer].randao_last_change, 1);
+    }
+
+    #[test]
+    fn test_process_block_with_invalid_specials() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+        let genesis_cry_state = chain.crystallized_states.values().next().unwrap().clone();
+        let wrong_keypairs: Vec<Keypair> = (0..8).map(|_| Keypair::random()).collect();
+
+        let check = |chain: &mut BeaconChain<_, _>, special, error| {
+            let mut block = child_block(chain, &genesis_hash, 1, vec![], &keypairs);
+            block.specials = vec![special];
+            sign_block(chain, &mut block, &genesis_cry_state, &keypairs);
+            assert_eq!(
+                process(chain, &block, 1),
+                Err(Error::StateTransitionError(
+                    StateTransitionError::SpecialProcessingError(error)
+                ))
+            );
+        };
+
+        let forged_logout = signed_logout(&chain, &genesis_cry_state, 1, 2, &wrong_keypairs);
+        check(
+            &mut chain,
+            forged_logout,
+            SpecialProcessingError::InvalidLogoutSignature,
+        );
+        let forged_change = signed_randao_change(
+            &chain,
+            &genesis_cry_state,
+            1,
+            Hash256::zero(),
+            &wrong_keypairs,
+        );
+        check(
+            &mut chain,
+            forged_change,
+            SpecialProcessingError::InvalidRandaoChangeSignature,
+        );
+        assert_eq!(chain.head_block_hashes, vec![genesis_hash]);
+    }
+
+    #[test]
+    fn test_process_block_lmd_ghost() {
+        let (mut chain, keypairs) = lmd_ghost_test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let a = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let (_, a_hash) = process(&mut chain, &a, 2).unwrap();
+        let b = child_block(&chain, &genesis_hash, 2, vec![], &keypairs);
+        let (_, b_hash) = process(&mut chain, &b, 2).unwrap();
+
+        /*
+         * An attestation to `a` makes its fork the heaviest.
+         */
+        let attestation = signed_attestation(&chain, 0, a_hash, &keypairs);
+        let c = child_block(&chain, &a_hash, 3, vec![attestation], &keypairs);
+        let (_, c_hash) = process(&mut chain, &c, 3).unwrap();
+        assert_eq!(chain.canonical_block_hash(), c_hash);
+
+        /*
+         * Unlike the naive fork choice, a higher slot alone does not make a block the head.
+         */
+        let d = child_block(&chain, &b_hash, 4, vec![], &keypairs);
+        let (outcome, _) = process(&mut chain, &d, 4).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewForkBlock);
+        assert_eq!(chain.canonical_block_hash(), c_hash);
+    }
+
+    #[test]
+    fn test_new_chain_already_exists() {
+        let db = Arc::new(MemoryDB::open());
+        let (spec, _) = test_spec(8);
+
+        let chain = BeaconChain::new(
+            BeaconChainStore::new(db.clone()),
+            spec.clone(),
+            NaiveForkChoice::new(),
+        )
+        .unwrap();
+        let genesis_hash = chain.canonical_block_hash();
+
+        /*
+         * The stored chain is not replaced, it may only be resumed.
+         */
+        assert_eq!(
+            BeaconChain::new(
+                BeaconChainStore::new(db.clone()),
+                spec.clone(),
+                NaiveForkChoice::new()
+            )
+            .err(),
+            Some(BeaconChainError::ChainAlreadyExists)
+        );
+        let chain =
+            BeaconChain::from_store(BeaconChainStore::new(db), spec, NaiveForkChoice::new())
+                .unwrap();
+        assert_eq!(chain.canonical_block_hash(), genesis_hash);
+    }
+
+    #[test]
+    fn test_new_chain_invalid_spec() {
+        /*
+         * A zero shard count or epoch length would leave slots without committees.
+         */
+        for &(shard_count, epoch_length) in &[(0, 8), (8, 0), (0, 0)] {
+            let (store, mut spec, _) = test_store_and_spec(8);
+            spec.shard_count = shard_count;
+            spec.epoch_length = epoch_length;
+            assert_eq!(
+                BeaconChain::new(store, spec.clone(), NaiveForkChoice::new()).err(),
+                Some(BeaconChainError::InvalidSpec)
+            );
+
+            let (store, _, _) = test_store_and_spec(8);
+            assert_eq!(
+                BeaconChain::from_store(store, spec, NaiveForkChoice::new()).err(),
+                Some(BeaconChainError::InvalidSpec)
+            );
+        }
+    }
+
+    #[test]
+    fn test_restart_from_disk_db() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_chain_restart_from_disk_db_{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+
+        let (spec, keypairs) = test_spec(8);
+        let open_store = || {
+            let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+            BeaconChainStore::new(Arc::new(db))
+        };
+
+        /*
+         * There is no chain to resume from an empty database.
+         */
+        let store = open_store();
+        let fork_choice = LmdGhostForkChoice::new(store.block.clone());
+        assert_eq!(
+            BeaconChain::from_store(store, spec.clone(), fork_choice).err(),
+            Some(BeaconChainError::MissingChainMetadata)
+        );
+
+        /*
+         * Build two forks from genesis. The fork with the lower hash receives an attestation, so
+         * without that attestation the fork choice would select the other fork.
+         */
+        let (heads, head_hash, head_cry_state_root, other_hash) = {
+            let store = open_store();
+            let fork_choice = LmdGhostForkChoice::new(store.block.clone());
+            let mut chain = BeaconChain::new(store, spec.clone(), fork_choice).unwrap();
+            let genesis_hash = chain.canonical_block_hash();
+
+            let a = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+            let (_, a_hash) = process(&mut chain, &a, 2).unwrap();
+            let b = child_block(&chain, &genesis_hash, 2, vec![], &keypairs);
+            let (_, b_hash) = process(&mut chain, &b, 2).unwrap();
+            let (voted_hash, other_hash) = if a_hash < b_hash {
+                (a_hash, b_hash)
+            } else {
+                (b_hash, a_hash)
+            };
+
+            let attestation = signed_attestation(&chain, 0, voted_hash, &keypairs);
+            let c = child_block(&chain, &voted_hash, 3, vec![attestation], &keypairs);
+            let (_, c_hash) = process(&mut chain, &c, 3).unwrap();
+            assert_eq!(chain.canonical_block_hash(), c_hash);
+
+            (
+                chain.head_block_hashes.clone(),
+                c_hash,
+                c.crystallized_state_root,
+                other_hash,
+            )
+        };
+
+        /*
+         * Resume the chain from the database alone.
+         */
+        let store = open_store();
+        let fork_choice = LmdGhostForkChoice::new(store.block.clone());
+        let mut chain = BeaconChain::from_store(store, spec, fork_choice).unwrap();
+
+        assert_eq!(chain.head_block_hashes, heads);
+        assert_eq!(chain.canonical_block_hash(), head_hash);
+        assert_eq!(chain.active_states.len(), 4);
+        assert!(chain.crystallized_states.contains_key(&head_cry_state_root));
+        assert!(chain
+            .attester_proposer_maps
+            .contains_key(&head_cry_state_root));
+
+        /*
+         * The stored attestation was given to the fork choice, so extending the other fork does
+         * not cause a re-org.
+         */
+        let d = child_block(&chain, &other_hash, 4, vec![], &keypairs);
+        let (outcome, _) = process(&mut chain, &d, 4).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewForkBlock);
+        assert_eq!(chain.canonical_block_hash(), head_hash);
+
+        let e = child_block(&chain, &head_hash, 5, vec![], &keypairs);
+        let (outcome, e_hash) = process(&mut chain, &e, 5).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+        assert_eq!(
+            chain.store.block.canonical_head().unwrap(),
+            Some(e_hash.to_vec())
+        );
+
+        drop(chain);
+        fs::remove_dir_all(&path).unwrap();
     }
 }
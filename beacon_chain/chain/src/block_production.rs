@@ -0,0 +1,362 @@
+use super::BeaconChain;
+use attestation_validation::Error as AttestationValidationError;
+use block_processing::Error as BlockProcessingError;
+use db::{ClientDB, DBError};
+use fork_choice::ForkChoice;
+use maps::{generate_attester_and_proposer_maps_for_state, AttesterAndProposerMapError};
+use ssz::Decodable;
+use state_transition::{process_specials, update_crystallized_state, StateTransitionError};
+use std::borrow::Cow;
+use std::slice;
+use types::beacon_block::{
+    MAX_ANCESTOR_HASHES, MAX_ATTESTATIONS_PER_BLOCK, MAX_SPECIALS_PER_BLOCK,
+};
+use types::{BeaconBlock, Hash256, SpecialRecordPayload};
+
+/// The number of hashes in the `ancestor_hashes` of a produced block, which is as many as a block
+/// may hold.
+pub const ANCESTOR_HASHES_LENGTH: usize = MAX_ANCESTOR_HASHES;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The canonical head block is not known to the database.
+    UnknownHeadBlock,
+    /// The canonical head block is stored in the database, but could not be decoded.
+    InvalidHeadBlock,
+    /// The requested slot is not greater than the slot of the canonical head block.
+    SlotNotAfterHead,
+    /// The active state referenced by the canonical head block is not known.
+    UnknownActiveState,
+    /// The crystallized state referenced by the canonical head block is not known.
+    UnknownCrystallizedState,
+    /// There are no attester and proposer maps for the crystallized state of the canonical head.
+    UnknownAttesterProposerMaps,
+    /// There is no proposer assigned to the requested slot.
+    NoProposerForSlot,
+    /// The attester and proposer maps of the recalculated crystallized state could not be generated.
+    UnableToGenerateMaps(AttesterAndProposerMapError),
+    /// A pending attestation could not be validated due to some unexpected condition.
+    AttestationValidationError(AttestationValidationError),
+    /// The state transition function was unable to transition the states of the canonical head.
+    StateTransitionError(StateTransitionError),
+    DBError(String),
+}
+
+impl<T, F> BeaconChain<T, F>
+where
+    T: ClientDB + Sized,
+    F: ForkChoice,
+{
+    /// Produce a block at `slot` which builds upon the canonical head.
+    ///
+    /// The block includes each of the pending attestations and specials which are valid for
+    /// inclusion (up to the limits of the spec) and declares the state roots which result from
+    /// applying the block to the states of the canonical head.
+    ///
+    /// The `randao_reveal` must hash to the `randao_commitment` of the proposer of `slot`. The
+    /// returned block is unsigned, it must be signed by the proposer before it is valid.
+    pub fn produce_block(&self, slot: u64, randao_reveal: Hash256) -> Result<BeaconBlock, Error> {
+        let parent_hash = self.canonical_block_hash();
+        let parent_block = {
+            let parent_ssz = self
+                .store
+                .block
+                .get_serialized_block(&parent_hash)?
+                .ok_or(Error::UnknownHeadBlock)?;
+            let (parent_block, _) =
+                BeaconBlock::ssz_decode(&parent_ssz, 0).map_err(|_| Error::InvalidHeadBlock)?;
+            parent_block
+        };
+
+        if slot <= parent_block.slot {
+            return Err(Error::SlotNotAfterHead);
+        }
+
+        let parent_act_state = self
+            .active_states
+            .get(&parent_block.active_state_root)
+            .ok_or(Error::UnknownActiveState)?;
+        let parent_cry_state = self
+            .crystallized_states
+            .get(&parent_block.crystallized_state_root)
+            .ok_or(Error::UnknownCrystallizedState)?;
+        let (attester_map, parent_proposer_map) = self
+            .attester_proposer_maps
+            .get(&parent_block.crystallized_state_root)
+            .cloned()
+            .ok_or(Error::UnknownAttesterProposerMaps)?;
+
+        // TODO: the PoW chain is not yet followed, so the reference of the parent is reused.
+        let mut block = BeaconBlock {
+            slot,
+            randao_reveal,
+            pow_chain_reference: parent_block.pow_chain_reference,
+            ancestor_hashes: child_ancestor_hashes(&parent_block, &parent_hash),
+            ..BeaconBlock::zero()
+        };
+
+        /*
+         * Include the pending attestations which would be accepted by block processing, leaving
+         * out any which are invalid for this block.
+         */
+        for attestation in &self.pending_attestations {
+            if block.attestations.len() as u64 >= self.spec.max_attestations
+                || block.attestations.len() >= MAX_ATTESTATIONS_PER_BLOCK
+            {
+                break;
+            }
+            match self.validate_block_attestation(
+                attestation,
+                &block,
+                &parent_block,
+                &parent_hash,
+                parent_cry_state,
+                &attester_map,
+            ) {
+                Ok(_) => block.attestations.push(attestation.clone()),
+                Err(BlockProcessingError::AttestationValidationError(e)) => {
+                    return Err(Error::AttestationValidationError(e))
+                }
+                Err(_) => continue,
+            }
+        }
+
+        /*
+         * As in block processing, the states are recalculated up to the slot of the block before
+         * it is applied, in which case the recalculated crystallized state assigns the proposer.
+         */
+        let (pre_act_state, pre_cry_state) =
+            self.pre_block_states(parent_act_state, parent_cry_state, slot)?;
+        let proposer = match pre_cry_state {
+            Cow::Owned(ref cry_state) => {
+                let (_, proposer_map) = generate_attester_and_proposer_maps_for_state(
+                    cry_state,
+                    self.spec.epoch_length,
+                )?;
+                proposer_map.get(&slot).cloned()
+            }
+            Cow::Borrowed(_) => parent_proposer_map.get(&slot).cloned(),
+        };
+        let proposer = proposer.ok_or(Error::NoProposerForSlot)?;
+
+        /*
+         * Include the pending specials which would be accepted by block processing, up to the
+         * number of each kind (and in total) which is permitted in a block.
+         *
+         * Each special is applied to the validators which result from the randao reveal and the
+         * specials included before it, leaving out any which are invalid.
+         */
+        let fork_data = pre_cry_state.fork_data();
+        let mut validators =
+            update_crystallized_state(&pre_cry_state, &block, &self.spec)?.validators;
+        let mut logouts = 0;
+        let mut casper_slashings = 0;
+        for (_, special) in &self.pending_specials {
+            if block.specials.len() >= MAX_SPECIALS_PER_BLOCK {
+                break;
+            }
+            let count = match special.decode_payload() {
+                Ok(SpecialRecordPayload::Logout(_)) => Some((&mut logouts, self.spec.max_exits)),
+                Ok(SpecialRecordPayload::CasperSlashing(_)) => {
+                    Some((&mut casper_slashings, self.spec.max_casper_slashings))
+                }
+                Ok(SpecialRecordPayload::RandaoChange(_)) => None,
+                Err(_) => continue,
+            };
+            if let Some((count, max)) = &count {
+                if **count >= *max {
+                    continue;
+                }
+            }
+            if let Ok(new_validators) = process_specials(
+                validators.clone(),
+                slice::from_ref(special),
+                slot,
+                proposer,
+                &fork_data,
+                &self.spec,
+            ) {
+                validators = new_validators;
+                if let Some((count, _)) = count {
+                    *count += 1;
+                }
+                block.specials.push(special.clone());
+            }
+        }
+
+        /*
+         * As in block processing, the states are transitioned using the hash of the parent.
+         */
+        let (act_state, cry_state) =
+            self.apply_block(&pre_act_state, &pre_cry_state, &block, &parent_hash)?;
+        block.active_state_root = act_state.canonical_root();
+        block.crystallized_state_root = cry_state.canonical_root();
+
+        Ok(block)
+    }
+}
+
+/// Returns the `ancestor_hashes` of a child of `parent`, where `parent_hash` is the hash of
+/// `parent`.
+///
+/// The hash at index `i` is the most recent ancestor with a slot which is a multiple of `2^i`,
+/// so the first hash is always that of the parent.
+pub fn child_ancestor_hashes(parent: &BeaconBlock, parent_hash: &Hash256) -> Vec<Hash256> {
+    let mut ancestor_hashes = parent.ancestor_hashes.clone();
+    ancestor_hashes.resize(ANCESTOR_HASHES_LENGTH, Hash256::zero());
+    for (i, ancestor_hash) in ancestor_hashes.iter_mut().enumerate() {
+        /*
+         * The slot is a multiple of `2^i` if its lowest `i` bits are zero.
+         */
+        if parent.slot.trailing_zeros() as usize >= i {
+            *ancestor_hash = *parent_hash;
+        }
+    }
+    ancestor_hashes
+}
+
+impl From<StateTransitionError> for Error {
+    fn from(e: StateTransitionError) -> Error {
+        Error::StateTransitionError(e)
+    }
+}
+
+impl From<AttesterAndProposerMapError> for Error {
+    fn from(e: AttesterAndProposerMapError) -> Error {
+        Error::UnableToGenerateMaps(e)
+    }
+}
+
+impl From<DBError> for Error {
+    fn from(e: DBError) -> Error {
+        Error::DBError(e.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate bls;
+
+    use self::bls::{Keypair, Signature};
+    use super::*;
+    use block_processing::BlockProcessingOutcome;
+    use test_utils::*;
+    use types::SpecialRecord;
+
+    #[test]
+    fn test_produce_block() {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let attestation = signed_attestation(&chain, 0, Hash256::zero(), &keypairs);
+        let wrong_keypairs: Vec<Keypair> = (0..8).map(|_| Keypair::random()).collect();
+        let invalid_attestation = signed_attestation(&chain, 0, Hash256::zero(), &wrong_keypairs);
+        chain
+            .insert_pending_attestation(invalid_attestation.clone())
+            .unwrap();
+        chain
+            .insert_pending_attestation(attestation.clone())
+            .unwrap();
+
+        let genesis_cry_state = chain.crystallized_states.values().next().unwrap().clone();
+        let randao_change = signed_randao_change(
+            &chain,
+            &genesis_cry_state,
+            1,
+            Hash256::from("commitment".as_bytes()),
+            &keypairs,
+        );
+        let logout = signed_logout(&chain, &genesis_cry_state, 1, 2, &keypairs);
+        let forged_logout = signed_logout(&chain, &genesis_cry_state, 1, 3, &wrong_keypairs);
+        let malformed_logout = SpecialRecord::logout(&[]);
+        for special in &[
+            malformed_logout.clone(),
+            forged_logout.clone(),
+            logout.clone(),
+            randao_change.clone(),
+        ] {
+            chain.insert_pending_special(special.clone(), 1).unwrap();
+        }
+
+        let randao_reveal = test_randao_reveal(&chain, &genesis_hash, 1);
+        let mut block = chain.produce_block(1, randao_reveal).unwrap();
+        assert_eq!(block.slot, 1);
+        assert_eq!(block.randao_reveal, randao_reveal);
+        assert_eq!(
+            block.ancestor_hashes,
+            vec![genesis_hash; ANCESTOR_HASHES_LENGTH]
+        );
+        assert_eq!(block.attestations, vec![attestation]);
+        assert_eq!(block.specials, vec![logout, randao_change]);
+        assert_eq!(block.signature, Signature::empty_signature());
+
+        /*
+         * Once signed by the proposer the block is valid, and its contents are no longer
+         * pending.
+         */
+        assert_ne!(
+            block.crystallized_state_root,
+            genesis_cry_state.canonical_root()
+        );
+        sign_block(&chain, &mut block, &genesis_cry_state, &keypairs);
+        let (outcome, hash) = process(&mut chain, &block, 1).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+        assert_eq!(chain.pending_attestations, vec![invalid_attestation]);
+        assert_eq!(
+            chain.pending_specials,
+            vec![(1, malformed_logout), (1, forged_logout)]
+        );
+
+        let randao_reveal = test_randao_reveal(&chain, &hash, 2);
+        let block = chain.produce_block(2, randao_reveal).unwrap();
+        assert_eq!(block.parent_hash(), Some(&hash));
+    }
+
+    #[test]
+    fn test_produce_block_across_cycle_boundary() {
+        let (chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        let randao_reveal = test_randao_reveal(&chain, &genesis_hash, 9);
+        let block = chain.produce_block(9, randao_reveal).unwrap();
+        let expected = child_block(&chain, &genesis_hash, 9, vec![], &keypairs);
+
+        assert_eq!(block.active_state_root, expected.active_state_root);
+        assert_eq!(
+            block.crystallized_state_root,
+            expected.crystallized_state_root
+        );
+    }
+
+    #[test]
+    fn test_produce_block_slot_not_after_head() {
+        let (chain, _) = test_chain(8);
+
+        assert_eq!(
+            chain.produce_block(0, Hash256::zero()),
+            Err(Error::SlotNotAfterHead)
+        );
+    }
+
+    #[test]
+    fn test_child_ancestor_hashes() {
+        let mut parent = BeaconBlock::zero();
+        parent.slot = 4;
+        parent.ancestor_hashes = vec![Hash256::from("ancestor".as_bytes()); ANCESTOR_HASHES_LENGTH];
+        let parent_hash = Hash256::from("parent".as_bytes());
+
+        let ancestor_hashes = child_ancestor_hashes(&parent, &parent_hash);
+        assert_eq!(ancestor_hashes.len(), ANCESTOR_HASHES_LENGTH);
+        assert_eq!(&ancestor_hashes[..3], &[parent_hash; 3]);
+        assert_eq!(&ancestor_hashes[3..], &parent.ancestor_hashes[3..]);
+
+        /*
+         * The ancestors of the genesis block are padded with zero hashes.
+         */
+        let genesis = BeaconBlock::zero();
+        assert_eq!(
+            child_ancestor_hashes(&genesis, &parent_hash),
+            vec![parent_hash; ANCESTOR_HASHES_LENGTH]
+        );
+    }
+}
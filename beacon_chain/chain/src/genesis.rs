@@ -1,12 +1,18 @@
-use super::{ActiveState, ChainConfig, CrystallizedState};
-use types::ValidatorStatus;
-use validator_induction::ValidatorInductor;
+use super::{ActiveState, ChainSpec, CrystallizedState};
+use types::{
+    BeaconBlock, BeaconState, CrosslinkRecord, ForkData, Hash256, ShardAndCommittee,
+    ValidatorRecord, ValidatorStatus,
+};
+use validator_induction::{ValidatorInductionError, ValidatorInductor};
 use validator_shuffling::{shard_and_committees_for_cycle, ValidatorAssignmentError};
 
+/// The fork version used for the genesis states.
+pub const INITIAL_FORK_VERSION: u32 = 0;
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
     ValidationAssignmentError(ValidatorAssignmentError),
-    NotImplemented,
+    ValidatorInductionError(ValidatorInductionError),
 }
 
 impl From<ValidatorAssignmentError> for Error {
@@ -15,39 +21,126 @@ impl From<ValidatorAssignmentError> for Error {
     }
 }
 
+impl From<ValidatorInductionError> for Error {
+    fn from(e: ValidatorInductionError) -> Error {
+        Error::ValidatorInductionError(e)
+    }
+}
+
 /// Initialize a new ChainHead with genesis parameters.
 ///
 /// Used when syncing a chain from scratch.
-pub fn genesis_states(config: &ChainConfig) -> Result<(ActiveState, CrystallizedState), Error> {
+pub fn genesis_states(spec: &ChainSpec) -> Result<(ActiveState, CrystallizedState), Error> {
+    let validators = genesis_validators(spec)?;
+    let shard_and_committee_for_slots = genesis_shard_and_committees(&validators, spec)?;
+
     /*
-     * Parse the ValidatorRegistrations into ValidatorRecords and induct them.
-     *
-     * Ignore any records which fail proof-of-possession or are invalid.
+     * Set all the crosslink records to reference zero hashes.
      */
-    let validators = {
-        let mut inductor = ValidatorInductor::new(0, config.shard_count, vec![]);
-        for registration in &config.initial_validators {
-            let _ = inductor.induct(&registration, ValidatorStatus::Active);
-        }
-        inductor.to_vec()
-    };
+    let crosslinks = vec![CrosslinkRecord::zero(); spec.shard_count as usize];
 
     /*
-     * Assign the validators to shards, using all zeros as the seed.
-     *
-     * Crystallizedstate stores two cycles, so we simply repeat the same assignment twice.
+     * Create a active state with zero hashes for all block hashes in the previous cycle.
      */
-    let _shard_and_committee_for_slots = {
-        let mut a = shard_and_committees_for_cycle(&vec![0; 32], &validators, 0, &config)?;
-        let mut b = a.clone();
-        a.append(&mut b);
-        a
+    let active_state = ActiveState {
+        pending_attestations: vec![],
+        pending_specials: vec![],
+        recent_block_hashes: vec![Hash256::zero(); spec.epoch_length as usize],
+        randao_mix: Hash256::zero(),
     };
 
-    // TODO: implement genesis for `BeaconState`
-    // https://github.com/sigp/lighthouse/issues/99
+    let crystallized_state = CrystallizedState {
+        validator_set_change_slot: 0,
+        validators,
+        crosslinks,
+        last_state_recalculation_slot: spec.initial_slot_number,
+        last_finalized_slot: 0,
+        last_justified_slot: 0,
+        previous_justified_slot: 0,
+        justified_streak: 0,
+        shard_and_committee_for_slots,
+        deposits_penalized_in_period: vec![],
+        validator_set_delta_hash_chain: Hash256::zero(),
+        pre_fork_version: INITIAL_FORK_VERSION,
+        post_fork_version: INITIAL_FORK_VERSION,
+        fork_slot_number: 0,
+    };
+
+    Ok((active_state, crystallized_state))
+}
+
+/// Initialize a `BeaconState` with genesis parameters.
+///
+/// Equivalent to the states produced by `genesis_states`, expressed as a single `BeaconState`,
+/// except that `latest_block_hashes` holds `latest_block_roots_length` hashes.
+pub fn genesis_beacon_state(spec: &ChainSpec) -> Result<BeaconState, Error> {
+    let validators = genesis_validators(spec)?;
+    let shard_and_committee_for_slots = genesis_shard_and_committees(&validators, spec)?;
+
+    Ok(BeaconState {
+        slot: spec.initial_slot_number,
+        validator_registry: validators,
+        validator_registry_latest_change_slot: 0,
+        validator_registry_exit_count: 0,
+        validator_registry_delta_chain_tip: Hash256::zero(),
+        randao_mix: Hash256::zero(),
+        next_seed: Hash256::zero(),
+        shard_and_committee_for_slots,
+        persistent_committees: vec![],
+        persistent_committee_reassignments: vec![],
+        previous_justified_slot: 0,
+        justified_slot: 0,
+        justified_slot_bitfield: 0,
+        finalized_slot: 0,
+        latest_crosslinks: vec![CrosslinkRecord::zero(); spec.shard_count as usize],
+        latest_state_recalculation_slot: spec.initial_slot_number,
+        latest_block_hashes: vec![Hash256::zero(); spec.latest_block_roots_length as usize],
+        latest_penalized_exit_balances: vec![],
+        latest_attestations: vec![],
+        processed_pow_receipt_root: Hash256::zero(),
+        candidate_pow_receipt_roots: vec![],
+        genesis_time: spec.genesis_time,
+        fork_data: ForkData {
+            pre_fork_version: u64::from(INITIAL_FORK_VERSION),
+            post_fork_version: u64::from(INITIAL_FORK_VERSION),
+            fork_slot: 0,
+        },
+    })
+}
+
+/// Returns the genesis block for the given state roots.
+///
+/// The genesis block has no ancestors; all blocks descend from it.
+pub fn genesis_block(active_state_root: Hash256, crystallized_state_root: Hash256) -> BeaconBlock {
+    BeaconBlock {
+        active_state_root,
+        crystallized_state_root,
+        ..BeaconBlock::zero()
+    }
+}
+
+/// Parse the ValidatorRegistrations into ValidatorRecords and induct them.
+///
+/// Ignore any records which fail proof-of-possession or are invalid.
+fn genesis_validators(spec: &ChainSpec) -> Result<Vec<ValidatorRecord>, Error> {
+    let mut inductor = ValidatorInductor::new(0, spec, vec![])?;
+    for registration in &spec.initial_validators {
+        let _ = inductor.induct(&registration, ValidatorStatus::Active);
+    }
+    Ok(inductor.to_vec())
+}
 
-    Err(Error::NotImplemented)
+/// Assign the validators to shards, using all zeros as the seed.
+///
+/// Crystallizedstate stores two cycles, so we simply repeat the same assignment twice.
+fn genesis_shard_and_committees(
+    validators: &[ValidatorRecord],
+    spec: &ChainSpec,
+) -> Result<Vec<Vec<ShardAndCommittee>>, Error> {
+    let mut a = shard_and_committees_for_cycle(&[0; 32], validators, 0, spec)?;
+    let mut b = a.clone();
+    a.append(&mut b);
+    Ok(a)
 }
 
 #[cfg(test)]
@@ -55,26 +148,22 @@ mod tests {
     extern crate bls;
     extern crate validator_induction;
 
-    // TODO: implement genesis for `BeaconState`
-    // https://github.com/sigp/lighthouse/issues/99
-    //
-    /*
     use self::bls::{create_proof_of_possession, Keypair};
     use super::*;
+    use state_transition::transition_state;
     use types::{Address, Hash256, ValidatorRegistration};
 
     #[test]
     fn test_genesis_no_validators() {
-        let config = ChainConfig::standard();
-        let (act, cry) = genesis_states(&config).unwrap();
+        let spec = ChainSpec::foundation();
+        let (act, cry) = genesis_states(&spec).unwrap();
 
         assert_eq!(cry.validator_set_change_slot, 0);
         assert_eq!(cry.validators.len(), 0);
-        assert_eq!(cry.crosslinks.len(), config.shard_count as usize);
+        assert_eq!(cry.crosslinks.len(), spec.shard_count as usize);
         for cl in cry.crosslinks {
-            assert_eq!(cl.recently_changed, false);
             assert_eq!(cl.slot, 0);
-            assert_eq!(cl.hash, Hash256::zero());
+            assert_eq!(cl.shard_block_hash, Hash256::zero());
         }
         assert_eq!(cry.last_state_recalculation_slot, 0);
         assert_eq!(cry.last_finalized_slot, 0);
@@ -82,7 +171,7 @@ mod tests {
         assert_eq!(cry.justified_streak, 0);
         assert_eq!(
             cry.shard_and_committee_for_slots.len(),
-            (config.cycle_length as usize) * 2
+            (spec.epoch_length as usize) * 2
         );
         assert_eq!(cry.deposits_penalized_in_period.len(), 0);
         assert_eq!(cry.validator_set_delta_hash_chain, Hash256::zero());
@@ -94,7 +183,7 @@ mod tests {
         assert_eq!(act.pending_specials.len(), 0);
         assert_eq!(
             act.recent_block_hashes,
-            vec![Hash256::zero(); config.cycle_length as usize]
+            vec![Hash256::zero(); spec.epoch_length as usize]
         );
         assert_eq!(act.randao_mix, Hash256::zero());
     }
@@ -112,43 +201,122 @@ mod tests {
 
     #[test]
     fn test_genesis_valid_validators() {
-        let mut config = ChainConfig::standard();
+        let mut spec = ChainSpec::foundation();
         let validator_count = 5;
 
         for _ in 0..validator_count {
-            config.initial_validators.push(random_registration());
+            spec.initial_validators.push(random_registration());
         }
 
-        let (_, cry) = genesis_states(&config).unwrap();
+        let (_, cry) = genesis_states(&spec).unwrap();
 
         assert_eq!(cry.validators.len(), validator_count);
     }
 
     #[test]
     fn test_genesis_invalid_validators() {
-        let mut config = ChainConfig::standard();
+        let mut spec = ChainSpec::foundation();
         let good_validator_count = 5;
 
         for _ in 0..good_validator_count {
-            config.initial_validators.push(random_registration());
+            spec.initial_validators.push(random_registration());
         }
 
         let mut bad_v = random_registration();
         let bad_kp = Keypair::random();
         bad_v.proof_of_possession = create_proof_of_possession(&bad_kp);
-        config.initial_validators.push(bad_v);
+        spec.initial_validators.push(bad_v);
 
         let mut bad_v = random_registration();
-        bad_v.withdrawal_shard = config.shard_count + 1;
-        config.initial_validators.push(bad_v);
+        bad_v.withdrawal_shard = spec.shard_count as u16 + 1;
+        spec.initial_validators.push(bad_v);
 
-        let (_, cry) = genesis_states(&config).unwrap();
+        let (_, cry) = genesis_states(&spec).unwrap();
 
         assert!(
-            config.initial_validators.len() != good_validator_count,
+            spec.initial_validators.len() != good_validator_count,
             "test is invalid"
         );
         assert_eq!(cry.validators.len(), good_validator_count);
     }
-    */
+
+    #[test]
+    fn test_genesis_beacon_state() {
+        let mut spec = ChainSpec::foundation();
+        let validator_count = 5;
+
+        for _ in 0..validator_count {
+            spec.initial_validators.push(random_registration());
+        }
+
+        let (act, cry) = genesis_states(&spec).unwrap();
+        let state = genesis_beacon_state(&spec).unwrap();
+
+        assert_eq!(state.validator_registry, cry.validators);
+        assert_eq!(
+            state.shard_and_committee_for_slots,
+            cry.shard_and_committee_for_slots
+        );
+        assert_eq!(state.latest_crosslinks, cry.crosslinks);
+        assert_eq!(
+            state.latest_block_hashes,
+            vec![Hash256::zero(); spec.latest_block_roots_length as usize]
+        );
+        assert_eq!(state.randao_mix, act.randao_mix);
+        assert_eq!(state.slot, 0);
+        assert_eq!(state.latest_state_recalculation_slot, 0);
+        assert_eq!(state.justified_slot, 0);
+        assert_eq!(state.finalized_slot, 0);
+        assert_eq!(state.genesis_time, spec.genesis_time);
+        assert_eq!(
+            state.fork_data.pre_fork_version,
+            u64::from(INITIAL_FORK_VERSION)
+        );
+        assert_eq!(
+            state.fork_data.post_fork_version,
+            u64::from(INITIAL_FORK_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_genesis_initial_slot_number() {
+        let mut spec = ChainSpec::minimal();
+        spec.initial_slot_number = 64;
+        for _ in 0..8 {
+            spec.initial_validators.push(random_registration());
+        }
+
+        let (_, cry) = genesis_states(&spec).unwrap();
+        assert_eq!(cry.last_state_recalculation_slot, 64);
+
+        let mut state = genesis_beacon_state(&spec).unwrap();
+        assert_eq!(state.slot, 64);
+        assert_eq!(state.latest_state_recalculation_slot, 64);
+
+        /*
+         * The first epoch begins at the initial slot, so it is processed at the last slot before
+         * `initial_slot_number + epoch_length`.
+         */
+        for _ in 0..spec.epoch_length {
+            transition_state(&mut state, &Hash256::zero(), None, &spec).unwrap();
+        }
+        assert_eq!(state.slot, 64 + spec.epoch_length);
+        assert_eq!(
+            state.latest_state_recalculation_slot,
+            64 + spec.epoch_length
+        );
+    }
+
+    #[test]
+    fn test_genesis_block() {
+        let active_state_root = Hash256::from("active".as_bytes());
+        let crystallized_state_root = Hash256::from("crystallized".as_bytes());
+
+        let block = genesis_block(active_state_root, crystallized_state_root);
+
+        assert_eq!(block.slot, 0);
+        assert!(block.ancestor_hashes.is_empty());
+        assert_eq!(block.active_state_root, active_state_root);
+        assert_eq!(block.crystallized_state_root, crystallized_state_root);
+    }
 }
@@ -1,5 +1,8 @@
+extern crate attestation_validation;
 extern crate db;
-extern crate naive_fork_choice;
+extern crate fork_choice;
+extern crate hashing;
+extern crate spec;
 extern crate ssz;
 extern crate ssz_helpers;
 extern crate state_transition;
@@ -8,31 +11,69 @@ extern crate validator_induction;
 extern crate validator_shuffling;
 
 mod block_processing;
+mod block_production;
 mod genesis;
 mod maps;
+mod pending;
+mod pruning;
 mod stores;
+#[cfg(test)]
+mod test_utils;
 mod transition;
 
-use db::ClientDB;
-use genesis::{genesis_states, Error as GenesisError};
-use maps::{generate_attester_and_proposer_maps, AttesterAndProposerMapError};
+pub use block_processing::{BlockProcessingOutcome, Error as BlockProcessingError};
+pub use block_production::{child_ancestor_hashes, Error as BlockProductionError};
+pub use genesis::genesis_beacon_state;
+pub use pending::{Error as PendingError, MAX_PENDING_ATTESTATIONS, MAX_PENDING_SPECIALS};
+pub use pruning::{PruningError, PruningMetrics};
+
+pub use stores::BeaconChainStore;
+
+use block_processing::attestation_participants;
+use db::stores::{
+    BeaconBlockStoreError, ChainMetadata, ChainMetadataStoreError, StateStoreError,
+    ValidatorStoreError,
+};
+use db::{ClientDB, DBError, WriteBatch};
+use fork_choice::{ForkChoice, ForkChoiceError};
+use genesis::{genesis_block, genesis_states, Error as GenesisError};
+use hashing::canonical_hash;
+use maps::{generate_attester_and_proposer_maps_for_state, AttesterAndProposerMapError};
+use spec::ChainSpec;
+use ssz::{ssz_encode, Decodable};
 use std::collections::HashMap;
 use std::sync::Arc;
-use stores::BeaconChainStore;
-use types::{ActiveState, AttesterMap, ChainConfig, CrystallizedState, Hash256, ProposerMap};
+use types::{
+    ActiveState, Attestation, AttesterMap, BeaconBlock, CrystallizedState, Hash256, ProposerMap,
+    SpecialRecord,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum BeaconChainError {
     InvalidGenesis,
     InsufficientValidators,
+    /// The spec cannot be used to run a chain (e.g., it has a zero `shard_count` or
+    /// `epoch_length`).
+    InvalidSpec,
     UnableToGenerateMaps(AttesterAndProposerMapError),
     GenesisError(GenesisError),
+    ForkChoiceError(ForkChoiceError),
+    BlockStoreError(BeaconBlockStoreError),
+    /// The database does not contain a chain to resume.
+    MissingChainMetadata,
+    /// The stored chain metadata is inconsistent (e.g., the canonical head is not a head block).
+    InvalidChainMetadata,
+    /// The database already contains a chain, which should be resumed with
+    /// `BeaconChain::from_store` rather than replaced.
+    ChainAlreadyExists,
     DBError(String),
 }
 
-pub struct BeaconChain<T: ClientDB + Sized> {
+pub struct BeaconChain<T: ClientDB + Sized, F: ForkChoice> {
     /// The last slot which has been finalized, this is common to all forks.
     pub last_finalized_slot: u64,
+    /// The hash of the finalized block, the fork choice only searches its descendants.
+    pub finalized_block_hash: Hash256,
     /// A vec of all block heads (tips of chains).
     pub head_block_hashes: Vec<Hash256>,
     /// The index of the canonical block in `head_block_hashes`.
@@ -45,55 +86,262 @@ pub struct BeaconChain<T: ClientDB + Sized> {
     pub attester_proposer_maps: HashMap<Hash256, (Arc<AttesterMap>, Arc<ProposerMap>)>,
     /// A collection of database stores used by the chain.
     pub store: BeaconChainStore<T>,
-    /// The chain configuration.
-    pub config: ChainConfig,
+    /// The constants and initial values of the chain.
+    pub spec: ChainSpec,
+    /// The hash of the genesis block, the root of the block tree.
+    pub genesis_block_hash: Hash256,
+    /// The rule used to select the canonical head from the block tree.
+    pub fork_choice: F,
+    /// The total resources reclaimed by pruning since the chain was created (or resumed).
+    pub pruning_metrics: PruningMetrics,
+    /// The error from pruning after the most recently processed block, if pruning failed. The
+    /// block itself is still accepted, pruning is attempted again after the next block.
+    pub pruning_error: Option<PruningError>,
+    /// Attestations which are yet to be included in a block, for use in block production.
+    pub(crate) pending_attestations: Vec<Attestation>,
+    /// Specials which are yet to be included in a block, for use in block production, with the
+    /// slot at which each was inserted.
+    pub(crate) pending_specials: Vec<(u64, SpecialRecord)>,
 }
 
-impl<T> BeaconChain<T>
+impl<T, F> BeaconChain<T, F>
 where
     T: ClientDB + Sized,
+    F: ForkChoice,
 {
-    pub fn new(store: BeaconChainStore<T>, config: ChainConfig) -> Result<Self, BeaconChainError> {
-        if config.initial_validators.is_empty() {
+    /// Start a new chain from the genesis described by `spec`, storing it in an empty database.
+    ///
+    /// Returns `ChainAlreadyExists` if the database already holds a chain, use
+    /// `BeaconChain::from_store` to resume it.
+    pub fn new(
+        store: BeaconChainStore<T>,
+        spec: ChainSpec,
+        mut fork_choice: F,
+    ) -> Result<Self, BeaconChainError> {
+        if !spec.validate() {
+            return Err(BeaconChainError::InvalidSpec);
+        }
+        if spec.initial_validators.is_empty() {
             return Err(BeaconChainError::InsufficientValidators);
         }
+        if store.metadata.get_metadata()?.is_some() {
+            return Err(BeaconChainError::ChainAlreadyExists);
+        }
+
+        let (active_state, crystallized_state) = genesis_states(&spec)?;
+
+        /*
+         * All of genesis is written in a single batch so that a failure cannot leave a partially
+         * stored chain in the database.
+         */
+        let mut batch = WriteBatch::new();
+
+        /*
+         * States are keyed by their root so that they may be found using the state roots
+         * declared in a block.
+         */
+        let active_state_root = active_state.canonical_root();
+        let crystallized_state_root = crystallized_state.canonical_root();
+        store
+            .state
+            .put_active_state_in_batch(&mut batch, &active_state_root, &active_state);
+        store.state.put_crystallized_state_in_batch(
+            &mut batch,
+            &crystallized_state_root,
+            &crystallized_state,
+        );
+
+        /*
+         * Store the genesis block, it is the ancestor of all blocks in the chain.
+         */
+        let genesis_block = genesis_block(active_state_root, crystallized_state_root);
+        let genesis_ssz = ssz_encode(&genesis_block);
+        let genesis_block_hash = Hash256::from(&canonical_hash(&genesis_ssz)[..]);
+        store
+            .block
+            .put_block_in_batch(&mut batch, &genesis_block_hash, &genesis_ssz)?;
+        store
+            .block
+            .set_canonical_head_in_batch(&mut batch, &genesis_block_hash)?;
+        fork_choice.add_block(&genesis_block, &genesis_block_hash)?;
 
-        let (active_state, crystallized_state) = genesis_states(&config)?;
+        /*
+         * Store the public keys of the genesis validators so attestation signatures may be
+         * verified.
+         */
+        for (i, validator) in crystallized_state.validators.iter().enumerate() {
+            store
+                .validator
+                .put_public_key_by_index_in_batch(&mut batch, i, &validator.pubkey);
+        }
 
-        let canonical_latest_block_hash = Hash256::zero();
-        let head_block_hashes = vec![canonical_latest_block_hash];
+        let head_block_hashes = vec![genesis_block_hash];
         let canonical_head_block_hash = 0;
         let mut active_states = HashMap::new();
         let mut crystallized_states = HashMap::new();
         let mut attester_proposer_maps = HashMap::new();
 
-        let (attester_map, proposer_map) = generate_attester_and_proposer_maps(
-            &crystallized_state.shard_and_committee_for_slots,
-            0,
-        )?;
+        let (attester_map, proposer_map) =
+            generate_attester_and_proposer_maps_for_state(&crystallized_state, spec.epoch_length)?;
 
-        active_states.insert(canonical_latest_block_hash, active_state);
-        crystallized_states.insert(canonical_latest_block_hash, crystallized_state);
+        active_states.insert(active_state_root, active_state);
+        crystallized_states.insert(crystallized_state_root, crystallized_state);
         attester_proposer_maps.insert(
-            canonical_latest_block_hash,
+            crystallized_state_root,
             (Arc::new(attester_map), Arc::new(proposer_map)),
         );
 
-        Ok(Self {
+        let chain = Self {
             last_finalized_slot: 0,
+            finalized_block_hash: genesis_block_hash,
             head_block_hashes,
             canonical_head_block_hash,
             active_states,
             crystallized_states,
             attester_proposer_maps,
             store,
-            config,
+            spec,
+            genesis_block_hash,
+            fork_choice,
+            pruning_metrics: PruningMetrics::default(),
+            pruning_error: None,
+            pending_attestations: vec![],
+            pending_specials: vec![],
+        };
+        chain
+            .store
+            .metadata
+            .put_metadata_in_batch(&mut batch, &chain.metadata());
+        chain.store.write_batch(batch)?;
+
+        Ok(chain)
+    }
+
+    /// Resume a chain which was previously stored in the database (e.g., by `BeaconChain::new`
+    /// before a restart).
+    ///
+    /// The states and head blocks are loaded from the database, the attester and proposer maps
+    /// are regenerated and the fork choice is informed of every stored block and the
+    /// attestations they contain.
+    ///
+    /// The `spec` must be the same as that used to create the chain.
+    pub fn from_store(
+        store: BeaconChainStore<T>,
+        spec: ChainSpec,
+        mut fork_choice: F,
+    ) -> Result<Self, BeaconChainError> {
+        if !spec.validate() {
+            return Err(BeaconChainError::InvalidSpec);
+        }
+        let metadata = store
+            .metadata
+            .get_metadata()?
+            .ok_or(BeaconChainError::MissingChainMetadata)?;
+        if metadata.canonical_head_block_hash >= metadata.head_block_hashes.len() {
+            return Err(BeaconChainError::InvalidChainMetadata);
+        }
+
+        let active_states: HashMap<Hash256, ActiveState> =
+            store.state.active_states()?.into_iter().collect();
+        let crystallized_states: HashMap<Hash256, CrystallizedState> =
+            store.state.crystallized_states()?.into_iter().collect();
+
+        let mut attester_proposer_maps = HashMap::new();
+        for (root, cry_state) in &crystallized_states {
+            let (attester_map, proposer_map) =
+                generate_attester_and_proposer_maps_for_state(cry_state, spec.epoch_length)?;
+            attester_proposer_maps.insert(*root, (Arc::new(attester_map), Arc::new(proposer_map)));
+        }
+
+        /*
+         * Load every stored block, ordered by slot so that the parent of each block is known to
+         * the fork choice before the block itself.
+         */
+        let mut blocks: Vec<(Hash256, BeaconBlock)> = vec![];
+        for hash in store.block.block_hashes()? {
+            let ssz = store
+                .block
+                .get_serialized_block(&hash)?
+                .ok_or(BeaconBlockStoreError::UnknownBeaconBlock)?;
+            let (block, _) = BeaconBlock::ssz_decode(&ssz, 0)
+                .map_err(|_| BeaconBlockStoreError::InvalidBeaconBlock)?;
+            blocks.push((Hash256::from(&hash[..]), block));
+        }
+        blocks.sort_by_key(|(hash, block)| (block.slot, *hash));
+
+        let crystallized_state_roots: HashMap<Hash256, Hash256> = blocks
+            .iter()
+            .map(|(hash, block)| (*hash, block.crystallized_state_root))
+            .collect();
+
+        for (hash, block) in &blocks {
+            fork_choice.add_block(block, hash)?;
+
+            /*
+             * Stored blocks have already been validated, so the committee of each attestation
+             * is found in the maps of the crystallized state of the parent block.
+             */
+            let attester_map = block
+                .ancestor_hashes
+                .first()
+                .and_then(|parent_hash| crystallized_state_roots.get(parent_hash))
+                .and_then(|root| attester_proposer_maps.get(root))
+                .map(|(attester_map, _)| attester_map.clone());
+            if let Some(attester_map) = attester_map {
+                for attestation in &block.attestations {
+                    let shard = attestation.data.shard;
+                    if shard > u64::from(u16::max_value()) {
+                        continue;
+                    }
+                    let key = (attestation.data.slot, shard as u16);
+                    if let Some(attestation_indices) = attester_map.get(&key) {
+                        for validator_index in
+                            attestation_participants(attestation, attestation_indices)
+                        {
+                            fork_choice.add_attestation(
+                                validator_index,
+                                &attestation.data.beacon_block_hash,
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            last_finalized_slot: metadata.last_finalized_slot,
+            finalized_block_hash: metadata.finalized_block_hash,
+            head_block_hashes: metadata.head_block_hashes,
+            canonical_head_block_hash: metadata.canonical_head_block_hash,
+            active_states,
+            crystallized_states,
+            attester_proposer_maps,
+            store,
+            spec,
+            genesis_block_hash: metadata.genesis_block_hash,
+            fork_choice,
+            pruning_metrics: PruningMetrics::default(),
+            pruning_error: None,
+            pending_attestations: vec![],
+            pending_specials: vec![],
         })
     }
 
     pub fn canonical_block_hash(&self) -> Hash256 {
         self.head_block_hashes[self.canonical_head_block_hash]
     }
+
+    /// Returns the metadata which must be stored in order to resume the chain with
+    /// `BeaconChain::from_store`.
+    fn metadata(&self) -> ChainMetadata {
+        ChainMetadata {
+            genesis_block_hash: self.genesis_block_hash,
+            last_finalized_slot: self.last_finalized_slot,
+            finalized_block_hash: self.finalized_block_hash,
+            head_block_hashes: self.head_block_hashes.clone(),
+            canonical_head_block_hash: self.canonical_head_block_hash,
+        }
+    }
 }
 
 impl From<AttesterAndProposerMapError> for BeaconChainError {
@@ -102,6 +350,57 @@ impl From<AttesterAndProposerMapError> for BeaconChainError {
     }
 }
 
+impl From<BeaconBlockStoreError> for BeaconChainError {
+    fn from(e: BeaconBlockStoreError) -> BeaconChainError {
+        BeaconChainError::BlockStoreError(e)
+    }
+}
+
+impl From<DBError> for BeaconChainError {
+    fn from(e: DBError) -> BeaconChainError {
+        BeaconChainError::DBError(e.message)
+    }
+}
+
+impl From<ValidatorStoreError> for BeaconChainError {
+    fn from(e: ValidatorStoreError) -> BeaconChainError {
+        match e {
+            ValidatorStoreError::DBError(s) => BeaconChainError::DBError(s),
+            ValidatorStoreError::DecodeError => {
+                BeaconChainError::DBError("Unable to decode public key".to_string())
+            }
+        }
+    }
+}
+
+impl From<StateStoreError> for BeaconChainError {
+    fn from(e: StateStoreError) -> BeaconChainError {
+        match e {
+            StateStoreError::DBError(s) => BeaconChainError::DBError(s),
+            StateStoreError::DecodeError => {
+                BeaconChainError::DBError("Unable to decode state".to_string())
+            }
+        }
+    }
+}
+
+impl From<ChainMetadataStoreError> for BeaconChainError {
+    fn from(e: ChainMetadataStoreError) -> BeaconChainError {
+        match e {
+            ChainMetadataStoreError::DBError(s) => BeaconChainError::DBError(s),
+            ChainMetadataStoreError::DecodeError => {
+                BeaconChainError::DBError("Unable to decode chain metadata".to_string())
+            }
+        }
+    }
+}
+
+impl From<ForkChoiceError> for BeaconChainError {
+    fn from(e: ForkChoiceError) -> BeaconChainError {
+        BeaconChainError::ForkChoiceError(e)
+    }
+}
+
 impl From<GenesisError> for BeaconChainError {
     fn from(e: GenesisError) -> BeaconChainError {
         BeaconChainError::GenesisError(e)
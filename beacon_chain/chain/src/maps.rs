@@ -1,4 +1,4 @@
-use types::{AttesterMap, ProposerMap, ShardAndCommittee};
+use types::{AttesterMap, CrystallizedState, ProposerMap, ShardAndCommittee};
 
 #[derive(Debug, PartialEq)]
 pub enum AttesterAndProposerMapError {
@@ -40,6 +40,22 @@ pub fn generate_attester_and_proposer_maps(
     Ok((attester_map, proposer_map))
 }
 
+/// Generate the attester and proposer maps for the committees of some crystallized state.
+///
+/// The shard and committees of a crystallized state begin one cycle prior to its last state
+/// recalculation (or at slot zero, for the genesis state).
+pub fn generate_attester_and_proposer_maps_for_state(
+    cry_state: &CrystallizedState,
+    cycle_length: u64,
+) -> Result<(AttesterMap, ProposerMap), AttesterAndProposerMapError> {
+    generate_attester_and_proposer_maps(
+        &cry_state.shard_and_committee_for_slots,
+        cry_state
+            .last_state_recalculation_slot
+            .saturating_sub(cycle_length),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
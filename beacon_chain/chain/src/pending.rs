@@ -0,0 +1,184 @@
+use super::BeaconChain;
+use db::ClientDB;
+use fork_choice::ForkChoice;
+use types::beacon_block::{MAX_ATTESTATIONS_PER_BLOCK, MAX_SPECIALS_PER_BLOCK};
+use types::{Attestation, SpecialRecord};
+
+/// The greatest number of attestations which may be pending inclusion in a block.
+pub const MAX_PENDING_ATTESTATIONS: usize = 4 * MAX_ATTESTATIONS_PER_BLOCK;
+
+/// The greatest number of specials which may be pending inclusion in a block.
+pub const MAX_PENDING_SPECIALS: usize = 4 * MAX_SPECIALS_PER_BLOCK;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The pool already holds as many items as it may, the item was not inserted.
+    PoolFull,
+}
+
+impl<T, F> BeaconChain<T, F>
+where
+    T: ClientDB + Sized,
+    F: ForkChoice,
+{
+    /// Insert an attestation into those pending inclusion in a block by block production.
+    ///
+    /// An attestation which is already pending is ignored. The attestation is not validated until
+    /// a block is produced.
+    pub fn insert_pending_attestation(&mut self, attestation: Attestation) -> Result<(), Error> {
+        if self.pending_attestations.contains(&attestation) {
+            return Ok(());
+        }
+        if self.pending_attestations.len() >= MAX_PENDING_ATTESTATIONS {
+            return Err(Error::PoolFull);
+        }
+        self.pending_attestations.push(attestation);
+        Ok(())
+    }
+
+    /// Insert a special, received at `slot`, into those pending inclusion in a block by block
+    /// production.
+    ///
+    /// A special which is already pending is ignored. The special is not validated until a block
+    /// is produced.
+    pub fn insert_pending_special(
+        &mut self,
+        special: SpecialRecord,
+        slot: u64,
+    ) -> Result<(), Error> {
+        if self
+            .pending_specials
+            .iter()
+            .any(|(_, pending)| *pending == special)
+        {
+            return Ok(());
+        }
+        if self.pending_specials.len() >= MAX_PENDING_SPECIALS {
+            return Err(Error::PoolFull);
+        }
+        self.pending_specials.push((slot, special));
+        Ok(())
+    }
+
+    /// Remove the pending attestations which are too old to be included in a child of a block at
+    /// `slot`, returning the number removed.
+    pub fn expire_pending_attestations(&mut self, slot: u64) -> usize {
+        let earliest_slot = slot.saturating_sub(self.spec.epoch_length + 1);
+        let count = self.pending_attestations.len();
+        self.pending_attestations
+            .retain(|attestation| attestation.data.slot >= earliest_slot);
+        count - self.pending_attestations.len()
+    }
+
+    /// Remove the pending specials which were inserted prior to `slot`, returning the number
+    /// removed.
+    pub fn expire_pending_specials(&mut self, slot: u64) -> usize {
+        let count = self.pending_specials.len();
+        self.pending_specials
+            .retain(|(inserted_slot, _)| *inserted_slot >= slot);
+        count - self.pending_specials.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::test_chain;
+
+    fn attestation(slot: u64) -> Attestation {
+        let mut attestation = Attestation::zero();
+        attestation.data.slot = slot;
+        attestation
+    }
+
+    fn special(i: usize) -> SpecialRecord {
+        SpecialRecord {
+            kind: 0,
+            data: vec![(i >> 8) as u8, i as u8],
+        }
+    }
+
+    #[test]
+    fn test_insert_pending_attestation() {
+        let (mut chain, _) = test_chain(8);
+
+        chain.insert_pending_attestation(attestation(1)).unwrap();
+        chain.insert_pending_attestation(attestation(1)).unwrap();
+        assert_eq!(chain.pending_attestations, vec![attestation(1)]);
+
+        for i in 1..MAX_PENDING_ATTESTATIONS as u64 {
+            chain
+                .insert_pending_attestation(attestation(i + 1))
+                .unwrap();
+        }
+        assert_eq!(
+            chain.insert_pending_attestation(attestation(0)),
+            Err(Error::PoolFull)
+        );
+        assert_eq!(chain.pending_attestations.len(), MAX_PENDING_ATTESTATIONS);
+    }
+
+    #[test]
+    fn test_insert_pending_special() {
+        let (mut chain, _) = test_chain(8);
+
+        chain.insert_pending_special(special(0), 1).unwrap();
+        chain.insert_pending_special(special(0), 2).unwrap();
+        assert_eq!(chain.pending_specials, vec![(1, special(0))]);
+
+        for i in 1..MAX_PENDING_SPECIALS {
+            chain.insert_pending_special(special(i), 1).unwrap();
+        }
+        assert_eq!(
+            chain.insert_pending_special(special(MAX_PENDING_SPECIALS), 1),
+            Err(Error::PoolFull)
+        );
+        assert_eq!(chain.pending_specials.len(), MAX_PENDING_SPECIALS);
+    }
+
+    #[test]
+    fn test_expire_pending_attestations() {
+        let (mut chain, _) = test_chain(8);
+        let epoch_length = chain.spec.epoch_length;
+        for slot in 0..3 * epoch_length {
+            chain.insert_pending_attestation(attestation(slot)).unwrap();
+        }
+
+        /*
+         * A child of a block at the last slot may include attestations from the preceding
+         * `epoch_length + 1` slots onwards.
+         */
+        let slot = 3 * epoch_length - 1;
+        let earliest_slot = slot - epoch_length - 1;
+        assert_eq!(
+            chain.expire_pending_attestations(slot),
+            earliest_slot as usize
+        );
+        assert_eq!(chain.pending_attestations[0], attestation(earliest_slot));
+        assert_eq!(chain.expire_pending_attestations(slot), 0);
+    }
+
+    #[test]
+    fn test_expire_pending_specials() {
+        let (mut chain, _) = test_chain(8);
+        for slot in 0..4 {
+            chain
+                .insert_pending_special(special(slot as usize), slot)
+                .unwrap();
+        }
+
+        assert_eq!(chain.expire_pending_specials(2), 2);
+        assert_eq!(
+            chain.pending_specials,
+            vec![(2, special(2)), (3, special(3))]
+        );
+
+        /*
+         * Pending attestations are not affected.
+         */
+        chain.insert_pending_attestation(attestation(0)).unwrap();
+        assert_eq!(chain.expire_pending_specials(4), 2);
+        assert!(chain.pending_specials.is_empty());
+        assert_eq!(chain.pending_attestations, vec![attestation(0)]);
+    }
+}
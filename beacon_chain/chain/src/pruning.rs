@@ -0,0 +1,575 @@
+use super::BeaconChain;
+use db::stores::{BeaconBlockStoreError, ChainMetadata, ChainMetadataStoreError, StateStoreError};
+use db::{ClientDB, DBError, WriteBatch};
+use fork_choice::{ForkChoice, ForkChoiceError};
+use ssz_helpers::ssz_beacon_block::SszBeaconBlock;
+use std::collections::HashSet;
+use std::ops::AddAssign;
+use types::Hash256;
+
+#[derive(Debug, PartialEq)]
+pub enum PruningError {
+    /// The finalized block (or one of its descendants) is not known to the database.
+    UnknownBlock,
+    /// A stored block could not be interpreted as a block.
+    InvalidBlock,
+    /// The crystallized state of the canonical head is not known.
+    UnknownCrystallizedState,
+    /// The canonical head does not descend from the finalized block.
+    CanonicalHeadNotFinalized,
+    ForkChoiceError(ForkChoiceError),
+    BlockStoreError(BeaconBlockStoreError),
+    DBError(String),
+}
+
+/// The resources reclaimed by pruning the chain.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PruningMetrics {
+    /// The number of blocks deleted from the database.
+    pub blocks: usize,
+    /// The total size of the deleted blocks, in bytes.
+    pub block_bytes: usize,
+    /// The number of head blocks removed (i.e., the number of forks pruned).
+    pub head_blocks: usize,
+    /// The number of active states evicted.
+    pub active_states: usize,
+    /// The number of crystallized states (and their attester and proposer maps) evicted.
+    pub crystallized_states: usize,
+}
+
+impl AddAssign for PruningMetrics {
+    fn add_assign(&mut self, other: PruningMetrics) {
+        self.blocks += other.blocks;
+        self.block_bytes += other.block_bytes;
+        self.head_blocks += other.head_blocks;
+        self.active_states += other.active_states;
+        self.crystallized_states += other.crystallized_states;
+    }
+}
+
+impl<T, F> BeaconChain<T, F>
+where
+    T: ClientDB + Sized,
+    F: ForkChoice,
+{
+    /// Advance `last_finalized_slot` to the finalized slot of the crystallized state of the
+    /// canonical head, and `finalized_block_hash` to the canonical block at that slot.
+    ///
+    /// If the finalized slot has advanced, the chain is pruned to the newly finalized block and
+    /// the resources reclaimed are returned.
+    pub(crate) fn update_finalization(&mut self) -> Result<Option<PruningMetrics>, PruningError> {
+        let head_hash = self.canonical_block_hash();
+        let head_ssz = self
+            .store
+            .block
+            .get_serialized_block(&head_hash)?
+            .ok_or(PruningError::UnknownBlock)?;
+        let head_block =
+            SszBeaconBlock::from_slice(&head_ssz).map_err(|_| PruningError::InvalidBlock)?;
+        let finalized_slot = self
+            .crystallized_states
+            .get(&Hash256::from(head_block.cry_state_root()))
+            .ok_or(PruningError::UnknownCrystallizedState)?
+            .last_finalized_slot;
+
+        if finalized_slot <= self.last_finalized_slot {
+            return Ok(None);
+        }
+
+        /*
+         * The finalized block is the latest block in the canonical chain with a slot no greater
+         * than the finalized slot (the finalized slot itself may have been skipped).
+         */
+        let mut slot = finalized_slot;
+        let finalized_block_hash = loop {
+            if let Some(hash) = self.store.block.canonical_block_hash_at_slot(slot)? {
+                break Hash256::from(&hash[..]);
+            }
+            if slot == 0 {
+                return Err(PruningError::UnknownBlock);
+            }
+            slot -= 1;
+        };
+
+        /*
+         * The finalization is only advanced once the chain has been pruned to the finalized block.
+         */
+        self.prune_to(&finalized_block_hash, finalized_slot, finalized_block_hash)
+            .map(Some)
+    }
+
+    /// Prune the chain so that only the block at `finalized_block_hash`, its ancestors and its
+    /// descendants remain.
+    ///
+    /// All other blocks are deleted from the database and removed from the fork choice and the
+    /// head blocks. States which are not referenced by the finalized block or one of its
+    /// descendants are evicted from memory and the database, along with their attester and
+    /// proposer maps.
+    ///
+    /// The resources reclaimed are returned and added to `self.pruning_metrics`.
+    pub fn prune(
+        &mut self,
+        finalized_block_hash: &Hash256,
+    ) -> Result<PruningMetrics, PruningError> {
+        let last_finalized_slot = self.last_finalized_slot;
+        let fork_choice_root = self.finalized_block_hash;
+        self.prune_to(finalized_block_hash, last_finalized_slot, fork_choice_root)
+    }
+
+    /// As per `prune`, but `last_finalized_slot` and `finalized_block_hash` are also set to
+    /// the given values.
+    ///
+    /// All deletions and the new chain metadata are written in a single batch. The chain is only
+    /// modified in memory once that write has succeeded.
+    fn prune_to(
+        &mut self,
+        finalized_block_hash: &Hash256,
+        last_finalized_slot: u64,
+        fork_choice_root: Hash256,
+    ) -> Result<PruningMetrics, PruningError> {
+        let mut metrics = PruningMetrics::default();
+
+        /*
+         * Find the finalized block and its descendants, noting the states each of them reference.
+         */
+        let mut retained_blocks: HashSet<Hash256> = HashSet::new();
+        let mut retained_active_states: HashSet<Hash256> = HashSet::new();
+        let mut retained_crystallized_states: HashSet<Hash256> = HashSet::new();
+        let mut queue = vec![*finalized_block_hash];
+        while let Some(hash) = queue.pop() {
+            let ssz = self
+                .store
+                .block
+                .get_serialized_block(&hash)?
+                .ok_or(PruningError::UnknownBlock)?;
+            let block = SszBeaconBlock::from_slice(&ssz).map_err(|_| PruningError::InvalidBlock)?;
+            retained_active_states.insert(Hash256::from(block.act_state_root()));
+            retained_crystallized_states.insert(Hash256::from(block.cry_state_root()));
+            for child in self.store.block.block_children(&hash)? {
+                queue.push(Hash256::from(&child[..]));
+            }
+            retained_blocks.insert(hash);
+        }
+
+        let canonical_block_hash = self.canonical_block_hash();
+        if !retained_blocks.contains(&canonical_block_hash) {
+            return Err(PruningError::CanonicalHeadNotFinalized);
+        }
+
+        /*
+         * The ancestors of the finalized block are retained, but not their states.
+         */
+        let mut parent_hash = {
+            let ssz = self
+                .store
+                .block
+                .get_serialized_block(finalized_block_hash)?
+                .ok_or(PruningError::UnknownBlock)?;
+            let block = SszBeaconBlock::from_slice(&ssz).map_err(|_| PruningError::InvalidBlock)?;
+            block.parent_hash().map(Hash256::from)
+        };
+        while let Some(hash) = parent_hash {
+            parent_hash = match self.store.block.get_serialized_block(&hash)? {
+                None => None,
+                Some(ssz) => {
+                    let block =
+                        SszBeaconBlock::from_slice(&ssz).map_err(|_| PruningError::InvalidBlock)?;
+                    retained_blocks.insert(hash);
+                    block.parent_hash().map(Hash256::from)
+                }
+            };
+        }
+
+        /*
+         * Every other block is deleted, along with the states which are no longer referenced.
+         */
+        let mut batch = WriteBatch::new();
+
+        let mut pruned_blocks: HashSet<Hash256> = HashSet::new();
+        for hash in self.store.block.block_hashes()? {
+            let hash = Hash256::from(&hash[..]);
+            if retained_blocks.contains(&hash) {
+                continue;
+            }
+            if let Some(ssz) = self.store.block.get_serialized_block(&hash)? {
+                metrics.block_bytes += ssz.len();
+            }
+            self.store.block.delete_block_in_batch(&mut batch, &hash)?;
+            metrics.blocks += 1;
+            pruned_blocks.insert(hash);
+        }
+
+        let head_block_hashes: Vec<Hash256> = self
+            .head_block_hashes
+            .iter()
+            .filter(|hash| !pruned_blocks.contains(hash))
+            .cloned()
+            .collect();
+        metrics.head_blocks = self.head_block_hashes.len() - head_block_hashes.len();
+        let canonical_head_block_hash = head_block_hashes
+            .iter()
+            .position(|hash| *hash == canonical_block_hash)
+            .ok_or(PruningError::CanonicalHeadNotFinalized)?;
+
+        let stale_active_states: Vec<Hash256> = self
+            .active_states
+            .keys()
+            .filter(|root| !retained_active_states.contains(root))
+            .cloned()
+            .collect();
+        for root in &stale_active_states {
+            self.store
+                .state
+                .delete_active_state_in_batch(&mut batch, root);
+            metrics.active_states += 1;
+        }
+
+        let stale_crystallized_states: Vec<Hash256> = self
+            .crystallized_states
+            .keys()
+            .filter(|root| !retained_crystallized_states.contains(root))
+            .cloned()
+            .collect();
+        for root in &stale_crystallized_states {
+            self.store
+                .state
+                .delete_crystallized_state_in_batch(&mut batch, root);
+            metrics.crystallized_states += 1;
+        }
+
+        let metadata = ChainMetadata {
+            last_finalized_slot,
+            finalized_block_hash: fork_choice_root,
+            head_block_hashes: head_block_hashes.clone(),
+            canonical_head_block_hash,
+            ..self.metadata()
+        };
+        self.store
+            .metadata
+            .put_metadata_in_batch(&mut batch, &metadata);
+        self.store.write_batch(batch)?;
+
+        /*
+         * The database has been pruned, bring the chain in memory into line with it.
+         */
+        self.fork_choice.remove_blocks(&pruned_blocks)?;
+        self.head_block_hashes = head_block_hashes;
+        self.canonical_head_block_hash = canonical_head_block_hash;
+        for root in &stale_active_states {
+            self.active_states.remove(root);
+        }
+        for root in &stale_crystallized_states {
+            self.crystallized_states.remove(root);
+            self.attester_proposer_maps.remove(root);
+        }
+        self.last_finalized_slot = last_finalized_slot;
+        self.finalized_block_hash = fork_choice_root;
+
+        self.pruning_metrics += metrics.clone();
+        Ok(metrics)
+    }
+}
+
+impl From<ForkChoiceError> for PruningError {
+    fn from(e: ForkChoiceError) -> PruningError {
+        PruningError::ForkChoiceError(e)
+    }
+}
+
+impl From<BeaconBlockStoreError> for PruningError {
+    fn from(e: BeaconBlockStoreError) -> PruningError {
+        PruningError::BlockStoreError(e)
+    }
+}
+
+impl From<DBError> for PruningError {
+    fn from(e: DBError) -> PruningError {
+        PruningError::DBError(e.message)
+    }
+}
+
+impl From<StateStoreError> for PruningError {
+    fn from(e: StateStoreError) -> PruningError {
+        match e {
+            StateStoreError::DBError(s) => PruningError::DBError(s),
+            StateStoreError::DecodeError => {
+                PruningError::DBError("Unable to decode state".to_string())
+            }
+        }
+    }
+}
+
+impl From<ChainMetadataStoreError> for PruningError {
+    fn from(e: ChainMetadataStoreError) -> PruningError {
+        match e {
+            ChainMetadataStoreError::DBError(s) => PruningError::DBError(s),
+            ChainMetadataStoreError::DecodeError => {
+                PruningError::DBError("Unable to decode chain metadata".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate bls;
+
+    use self::bls::Keypair;
+    use super::*;
+    use block_processing::{BlockProcessingOutcome, Error as BlockProcessingError};
+    use db::MemoryDB;
+    use fork_choice::NaiveForkChoice;
+    use ssz::ssz_encode;
+    use test_utils::*;
+    use types::BeaconBlock;
+
+    type TestChain = BeaconChain<MemoryDB, NaiveForkChoice>;
+
+    /// Returns a chain with forks from `genesis` and `a` (where `b` is the canonical head), the
+    /// blocks `[a, b, c, x]` with their hashes and the keypairs of the validators.
+    fn forked_chain() -> (TestChain, Vec<(BeaconBlock, Hash256)>, Vec<Keypair>) {
+        let (mut chain, keypairs) = test_chain(8);
+        let genesis_hash = chain.canonical_block_hash();
+
+        /*
+         * genesis -> a -> b
+         *         |    \
+         *         |     -> c
+         *          \
+         *           -> x
+         */
+        let a = child_block(&chain, &genesis_hash, 1, vec![], &keypairs);
+        let (_, a_hash) = process(&mut chain, &a, 6).unwrap();
+        let b = child_block(&chain, &a_hash, 6, vec![], &keypairs);
+        let (_, b_hash) = process(&mut chain, &b, 6).unwrap();
+        let c = child_block(&chain, &a_hash, 2, vec![], &keypairs);
+        let (_, c_hash) = process(&mut chain, &c, 6).unwrap();
+        let x = child_block(&chain, &genesis_hash, 2, vec![], &keypairs);
+        let (_, x_hash) = process(&mut chain, &x, 6).unwrap();
+
+        assert_eq!(chain.canonical_block_hash(), b_hash);
+        assert_eq!(chain.head_block_hashes.len(), 3);
+
+        (
+            chain,
+            vec![(a, a_hash), (b, b_hash), (c, c_hash), (x, x_hash)],
+            keypairs,
+        )
+    }
+
+    #[test]
+    fn test_prune() {
+        let (mut chain, blocks, keypairs) = forked_chain();
+        let genesis_hash = chain.genesis_block_hash;
+        let (b, b_hash) = blocks[1].clone();
+        let (c, c_hash) = blocks[2].clone();
+        let (x, x_hash) = blocks[3].clone();
+
+        let c_cry_state_root = c.crystallized_state_root;
+        assert!(b.crystallized_state_root != c_cry_state_root);
+
+        let metrics = chain.prune(&b_hash).unwrap();
+        assert_eq!(
+            metrics,
+            PruningMetrics {
+                blocks: 2,
+                block_bytes: ssz_encode(&c).len() + ssz_encode(&x).len(),
+                head_blocks: 2,
+                active_states: 4,
+                crystallized_states: 4,
+            }
+        );
+        assert_eq!(chain.pruning_metrics, metrics);
+
+        /*
+         * The forks are removed, the finalized block and its ancestors remain.
+         */
+        assert_eq!(chain.head_block_hashes, vec![b_hash]);
+        assert_eq!(chain.canonical_block_hash(), b_hash);
+        for (hash, exists) in &[
+            (genesis_hash, true),
+            (blocks[0].1, true),
+            (b_hash, true),
+            (c_hash, false),
+            (x_hash, false),
+        ] {
+            assert_eq!(chain.store.block.block_exists(hash).unwrap(), *exists);
+        }
+
+        /*
+         * Only the states of the finalized block remain.
+         */
+        assert_eq!(chain.active_states.len(), 1);
+        assert!(chain.active_states.contains_key(&b.active_state_root));
+        assert_eq!(chain.crystallized_states.len(), 1);
+        assert!(chain
+            .crystallized_states
+            .contains_key(&b.crystallized_state_root));
+        assert!(!chain.attester_proposer_maps.contains_key(&c_cry_state_root));
+        assert_eq!(
+            chain.store.state.active_states().unwrap().len(),
+            chain.active_states.len()
+        );
+        assert_eq!(
+            chain.store.state.crystallized_states().unwrap().len(),
+            chain.crystallized_states.len()
+        );
+        assert_eq!(
+            chain
+                .store
+                .metadata
+                .get_metadata()
+                .unwrap()
+                .unwrap()
+                .head_block_hashes,
+            vec![b_hash]
+        );
+
+        /*
+         * The chain may still be extended from the finalized block, but not from a pruned fork.
+         */
+        let d = child_block(&chain, &b_hash, 7, vec![], &keypairs);
+        let (outcome, _) = process(&mut chain, &d, 7).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+
+        let mut y = BeaconBlock::zero();
+        y.slot = 7;
+        y.ancestor_hashes = vec![x_hash];
+        assert_eq!(
+            process(&mut chain, &y, 7),
+            Err(BlockProcessingError::UnknownParentBlock)
+        );
+    }
+
+    #[test]
+    fn test_prune_canonical_head_not_finalized() {
+        let (mut chain, blocks, _) = forked_chain();
+        let (_, x_hash) = blocks[3];
+
+        assert_eq!(
+            chain.prune(&x_hash),
+            Err(PruningError::CanonicalHeadNotFinalized)
+        );
+        assert_eq!(chain.head_block_hashes.len(), 3);
+        assert!(chain.store.block.block_exists(&x_hash).unwrap());
+        assert_eq!(chain.pruning_metrics, PruningMetrics::default());
+    }
+
+    #[test]
+    fn test_update_finalization() {
+        let (mut chain, blocks, _) = forked_chain();
+        let (ref a, a_hash) = blocks[0];
+        let (ref b, b_hash) = blocks[1];
+
+        /*
+         * Nothing is pruned until the finalized slot advances.
+         */
+        assert_eq!(chain.update_finalization(), Ok(None));
+        assert_eq!(chain.head_block_hashes.len(), 3);
+
+        /*
+         * Finalizing a skipped slot finalizes the latest canonical block prior to that slot.
+         */
+        chain
+            .crystallized_states
+            .get_mut(&b.crystallized_state_root)
+            .unwrap()
+            .last_finalized_slot = 3;
+        let metrics = chain.update_finalization().unwrap().unwrap();
+        assert_eq!(chain.last_finalized_slot, 3);
+        assert_eq!(chain.finalized_block_hash, a_hash);
+        let metadata = chain.store.metadata.get_metadata().unwrap().unwrap();
+        assert_eq!(metadata.last_finalized_slot, 3);
+        assert_eq!(metadata.finalized_block_hash, a_hash);
+
+        /*
+         * Only `x` does not descend from `a`. The states of `genesis` and `x` are evicted.
+         */
+        assert_eq!(metrics.blocks, 1);
+        assert_eq!(metrics.head_blocks, 1);
+        assert_eq!(metrics.active_states, 2);
+        assert_eq!(metrics.crystallized_states, 2);
+        assert!(chain.active_states.contains_key(&a.active_state_root));
+        assert_eq!(chain.canonical_block_hash(), b_hash);
+        assert!(chain.store.block.block_exists(&a_hash).unwrap());
+
+        assert_eq!(chain.update_finalization(), Ok(None));
+    }
+
+    #[test]
+    fn test_update_finalization_prune_error() {
+        let (mut chain, blocks, _) = forked_chain();
+        let (_, a_hash) = blocks[0];
+        let (ref b, _) = blocks[1];
+        let (_, x_hash) = blocks[3];
+        let genesis_hash = chain.genesis_block_hash;
+
+        chain
+            .crystallized_states
+            .get_mut(&b.crystallized_state_root)
+            .unwrap()
+            .last_finalized_slot = 3;
+        chain
+            .store
+            .block
+            .put_serialized_block(&a_hash, "not a block".as_bytes())
+            .unwrap();
+
+        /*
+         * Neither the finalization nor the database is modified if pruning fails.
+         */
+        assert_eq!(chain.update_finalization(), Err(PruningError::InvalidBlock));
+        assert_eq!(chain.last_finalized_slot, 0);
+        assert_eq!(chain.finalized_block_hash, genesis_hash);
+        assert_eq!(chain.head_block_hashes.len(), 3);
+        assert!(chain.store.block.block_exists(&x_hash).unwrap());
+        let metadata = chain.store.metadata.get_metadata().unwrap().unwrap();
+        assert_eq!(metadata.last_finalized_slot, 0);
+        assert_eq!(metadata.head_block_hashes.len(), 3);
+    }
+
+    #[test]
+    fn test_process_block_prune_error() {
+        let (mut chain, blocks, keypairs) = forked_chain();
+        let (ref a, a_hash) = blocks[0];
+        let (ref b, b_hash) = blocks[1];
+        let (_, x_hash) = blocks[3];
+
+        chain
+            .crystallized_states
+            .get_mut(&b.crystallized_state_root)
+            .unwrap()
+            .last_finalized_slot = 3;
+        let d = child_block(&chain, &b_hash, 7, vec![], &keypairs);
+        chain
+            .store
+            .block
+            .put_serialized_block(&a_hash, "not a block".as_bytes())
+            .unwrap();
+
+        /*
+         * The block is accepted even though the pruning which follows it fails.
+         */
+        let (outcome, d_hash) = process(&mut chain, &d, 7).unwrap();
+        assert_eq!(outcome, BlockProcessingOutcome::NewCanonicalBlock);
+        assert_eq!(chain.canonical_block_hash(), d_hash);
+        assert!(chain.store.block.block_exists(&d_hash).unwrap());
+        assert_eq!(chain.pruning_error, Some(PruningError::InvalidBlock));
+        assert_eq!(chain.last_finalized_slot, 0);
+        assert!(chain.store.block.block_exists(&x_hash).unwrap());
+
+        /*
+         * Pruning is attempted again after the next block.
+         */
+        chain
+            .store
+            .block
+            .put_serialized_block(&a_hash, &ssz_encode(a))
+            .unwrap();
+        let e = child_block(&chain, &d_hash, 8, vec![], &keypairs);
+        process(&mut chain, &e, 8).unwrap();
+        assert_eq!(chain.pruning_error, None);
+        assert!(chain.last_finalized_slot >= 3);
+        assert!(!chain.store.block.block_exists(&x_hash).unwrap());
+    }
+}
@@ -1,9 +1,33 @@
-use db::stores::{BeaconBlockStore, PoWChainStore, ValidatorStore};
-use db::ClientDB;
+use db::stores::{BeaconBlockStore, ChainMetadataStore, PoWChainStore, StateStore, ValidatorStore};
+use db::{ClientDB, DBError, WriteBatch};
 use std::sync::Arc;
 
 pub struct BeaconChainStore<T: ClientDB + Sized> {
     pub block: Arc<BeaconBlockStore<T>>,
     pub pow_chain: Arc<PoWChainStore<T>>,
     pub validator: Arc<ValidatorStore<T>>,
+    pub state: Arc<StateStore<T>>,
+    pub metadata: Arc<ChainMetadataStore<T>>,
+    /// The database upon which the stores are built.
+    db: Arc<T>,
+}
+
+impl<T: ClientDB + Sized> BeaconChainStore<T> {
+    /// Create each of the stores upon the given database.
+    pub fn new(db: Arc<T>) -> Self {
+        Self {
+            block: Arc::new(BeaconBlockStore::new(db.clone())),
+            pow_chain: Arc::new(PoWChainStore::new(db.clone())),
+            validator: Arc::new(ValidatorStore::new(db.clone())),
+            state: Arc::new(StateStore::new(db.clone())),
+            metadata: Arc::new(ChainMetadataStore::new(db.clone())),
+            db,
+        }
+    }
+
+    /// Atomically apply a batch of writes made by any of the stores (e.g., via
+    /// `BeaconBlockStore::put_block_in_batch`).
+    pub fn write_batch(&self, batch: WriteBatch) -> Result<(), DBError> {
+        self.db.write_batch(batch)
+    }
 }
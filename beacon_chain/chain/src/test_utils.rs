@@ -0,0 +1,250 @@
+extern crate bls;
+extern crate validator_client;
+
+use self::bls::{create_proof_of_possession, AggregateSignature, Keypair, Signature};
+use db::{ClientDB, MemoryDB};
+use fork_choice::{ForkChoice, LmdGhostForkChoice, NaiveForkChoice};
+use maps::generate_attester_and_proposer_maps_for_state;
+use spec::ChainSpec;
+use ssz::{ssz_encode, Decodable};
+use std::sync::Arc;
+use types::{
+    Address, Attestation, BeaconBlock, Bitfield, CrystallizedState, Hash256, LogoutSpecial,
+    RandaoChangeSpecial, SpecialRecord, ValidatorRegistration,
+};
+use {BeaconChain, BeaconChainStore, BlockProcessingError, BlockProcessingOutcome};
+
+pub use self::validator_client::test_utils::test_hash_onion;
+
+pub fn test_chain(
+    validator_count: usize,
+) -> (BeaconChain<MemoryDB, NaiveForkChoice>, Vec<Keypair>) {
+    let (store, spec, keypairs) = test_store_and_spec(validator_count);
+    let chain = BeaconChain::new(store, spec, NaiveForkChoice::new()).unwrap();
+    (chain, keypairs)
+}
+
+pub fn lmd_ghost_test_chain(
+    validator_count: usize,
+) -> (
+    BeaconChain<MemoryDB, LmdGhostForkChoice<MemoryDB>>,
+    Vec<Keypair>,
+) {
+    let (store, spec, keypairs) = test_store_and_spec(validator_count);
+    let fork_choice = LmdGhostForkChoice::new(store.block.clone());
+    let chain = BeaconChain::new(store, spec, fork_choice).unwrap();
+    (chain, keypairs)
+}
+
+pub fn test_store_and_spec(
+    validator_count: usize,
+) -> (BeaconChainStore<MemoryDB>, ChainSpec, Vec<Keypair>) {
+    let store = BeaconChainStore::new(Arc::new(MemoryDB::open()));
+    let (spec, keypairs) = test_spec(validator_count);
+    (store, spec, keypairs)
+}
+
+/// Returns a spec with `validator_count` initial validators, along with the keypair of each
+/// validator. The `randao_commitment` of each validator is the commitment of its
+/// `test_hash_onion`.
+pub fn test_spec(validator_count: usize) -> (ChainSpec, Vec<Keypair>) {
+    let mut spec = ChainSpec::minimal();
+
+    let keypairs: Vec<Keypair> = (0..validator_count).map(|_| Keypair::random()).collect();
+    for (i, keypair) in keypairs.iter().enumerate() {
+        spec.initial_validators.push(ValidatorRegistration {
+            pubkey: keypair.pk.clone(),
+            withdrawal_shard: 0,
+            withdrawal_address: Address::zero(),
+            randao_commitment: test_hash_onion(i).commitment(),
+            proof_of_possession: create_proof_of_possession(&keypair),
+        });
+    }
+
+    (spec, keypairs)
+}
+
+/// Returns a block at `slot` which is a valid child of the block at `parent_hash`, signed by its
+/// proposer using `keypairs`.
+pub fn child_block<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    parent_hash: &Hash256,
+    slot: u64,
+    attestations: Vec<Attestation>,
+    keypairs: &[Keypair],
+) -> BeaconBlock {
+    child_block_with_specials(chain, parent_hash, slot, attestations, vec![], keypairs)
+}
+
+/// Returns a block as per `child_block`, which also includes `specials`.
+pub fn child_block_with_specials<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    parent_hash: &Hash256,
+    slot: u64,
+    attestations: Vec<Attestation>,
+    specials: Vec<SpecialRecord>,
+    keypairs: &[Keypair],
+) -> BeaconBlock {
+    let parent_ssz = chain
+        .store
+        .block
+        .get_serialized_block(parent_hash)
+        .unwrap()
+        .unwrap();
+    let (parent, _) = BeaconBlock::ssz_decode(&parent_ssz, 0).unwrap();
+
+    let mut block = BeaconBlock::zero();
+    block.slot = slot;
+    block.randao_reveal = test_randao_reveal(chain, parent_hash, slot);
+    block.ancestor_hashes = vec![*parent_hash];
+    block.attestations = attestations;
+    block.specials = specials;
+
+    /*
+     * The states are recalculated up to the slot of the block before it is applied, as per
+     * block processing.
+     */
+    let act_state = &chain.active_states[&parent.active_state_root];
+    let cry_state = &chain.crystallized_states[&parent.crystallized_state_root];
+    let (act_state, cry_state) = chain.pre_block_states(act_state, cry_state, slot).unwrap();
+    let (act_state, cry_state) = chain
+        .apply_block(&act_state, &cry_state, &block, parent_hash)
+        .unwrap();
+    block.active_state_root = act_state.canonical_root();
+    block.crystallized_state_root = cry_state.canonical_root();
+    sign_block(chain, &mut block, &cry_state, keypairs);
+    block
+}
+
+/// Returns the randao reveal of the proposer of a block at `slot` which builds upon the block at
+/// `parent_hash`, being the layer of their `test_hash_onion` beneath their current commitment.
+pub fn test_randao_reveal<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    parent_hash: &Hash256,
+    slot: u64,
+) -> Hash256 {
+    let parent_ssz = chain
+        .store
+        .block
+        .get_serialized_block(parent_hash)
+        .unwrap()
+        .unwrap();
+    let (parent, _) = BeaconBlock::ssz_decode(&parent_ssz, 0).unwrap();
+    let act_state = &chain.active_states[&parent.active_state_root];
+    let cry_state = &chain.crystallized_states[&parent.crystallized_state_root];
+    let (_, cry_state) = chain.pre_block_states(act_state, cry_state, slot).unwrap();
+
+    let (_, proposer_map) =
+        generate_attester_and_proposer_maps_for_state(&cry_state, chain.spec.epoch_length).unwrap();
+    let proposer = proposer_map[&slot];
+    test_hash_onion(proposer)
+        .reveal(&cry_state.validators[proposer].randao_commitment, 1)
+        .unwrap()
+}
+
+/// Sign `block` as its proposer using `keypairs`, where `cry_state` assigns the proposer of the
+/// block (i.e., it is the crystallized state of the parent, or one resulting from the block).
+pub fn sign_block<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    block: &mut BeaconBlock,
+    cry_state: &CrystallizedState,
+    keypairs: &[Keypair],
+) {
+    let (_, proposer_map) =
+        generate_attester_and_proposer_maps_for_state(cry_state, chain.spec.epoch_length).unwrap();
+    let domain = cry_state
+        .fork_data()
+        .domain(block.slot, chain.spec.domain_proposal)
+        .unwrap();
+    block.signature = Signature::new(
+        &block.proposal_signing_message(domain),
+        &keypairs[proposer_map[&block.slot]].sk,
+    );
+}
+
+/// Returns a logout for the validator at `validator_index`, signed using `keypairs` in the logout
+/// domain of `cry_state` at `slot`.
+pub fn signed_logout<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    cry_state: &CrystallizedState,
+    slot: u64,
+    validator_index: usize,
+    keypairs: &[Keypair],
+) -> SpecialRecord {
+    let domain = cry_state
+        .fork_data()
+        .domain(slot, chain.spec.domain_logout)
+        .unwrap();
+    let message = LogoutSpecial::signing_message(domain);
+    let logout = LogoutSpecial {
+        validator_index: validator_index as u64,
+        signature: Signature::new(&message, &keypairs[validator_index].sk),
+    };
+    SpecialRecord::logout(&ssz_encode(&logout))
+}
+
+/// Returns a randao change to `randao_commitment` by the proposer of the block at `slot`, as
+/// assigned by `cry_state`, signed using `keypairs`.
+pub fn signed_randao_change<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    cry_state: &CrystallizedState,
+    slot: u64,
+    randao_commitment: Hash256,
+    keypairs: &[Keypair],
+) -> SpecialRecord {
+    let (_, proposer_map) =
+        generate_attester_and_proposer_maps_for_state(cry_state, chain.spec.epoch_length).unwrap();
+    let proposer = proposer_map[&slot];
+    let mut change = RandaoChangeSpecial {
+        proposer_index: proposer as u64,
+        randao_commitment,
+        signature: Signature::empty_signature(),
+    };
+    let domain = cry_state
+        .fork_data()
+        .domain(slot, chain.spec.domain_proposal)
+        .unwrap();
+    change.signature = Signature::new(&change.signing_message(domain), &keypairs[proposer].sk);
+    SpecialRecord::randao_change(&ssz_encode(&change))
+}
+
+/// Returns an attestation to `beacon_block_hash` for the first shard at `slot`, signed by the
+/// members of the committee using `keypairs`.
+pub fn signed_attestation<T: ClientDB, F: ForkChoice>(
+    chain: &BeaconChain<T, F>,
+    slot: u64,
+    beacon_block_hash: Hash256,
+    keypairs: &[Keypair],
+) -> Attestation {
+    let cry_state = &chain.crystallized_states.values().next().unwrap();
+    let shard_and_committee = &cry_state.shard_and_committee_for_slots[slot as usize][0];
+
+    let mut attestation = Attestation::zero();
+    attestation.data.slot = slot;
+    attestation.data.shard = u64::from(shard_and_committee.shard);
+    attestation.data.beacon_block_hash = beacon_block_hash;
+    attestation.data.justified_block_hash = chain.genesis_block_hash;
+
+    let domain = cry_state
+        .fork_data()
+        .domain(slot, chain.spec.domain_attestation)
+        .unwrap();
+    let message = attestation.data.signing_message(domain);
+
+    let committee = &shard_and_committee.committee;
+    attestation.participation_bitfield = Bitfield::from_elem(committee.len(), true);
+    let mut aggregate_sig = AggregateSignature::new();
+    for validator_index in committee {
+        aggregate_sig.add(&Signature::new(&message, &keypairs[*validator_index].sk));
+    }
+    attestation.aggregate_sig = aggregate_sig;
+    attestation
+}
+
+pub fn process<T: ClientDB, F: ForkChoice>(
+    chain: &mut BeaconChain<T, F>,
+    block: &BeaconBlock,
+    present_slot: u64,
+) -> Result<(BlockProcessingOutcome, Hash256), BlockProcessingError> {
+    chain.process_block(&ssz_encode(block), present_slot)
+}
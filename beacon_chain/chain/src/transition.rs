@@ -1,29 +1,94 @@
 use super::BeaconChain;
 use db::ClientDB;
-use state_transition::{extend_active_state, StateTransitionError};
+use fork_choice::ForkChoice;
+use state_transition::{
+    extend_active_state, recalculate_crystallized_state, update_crystallized_state,
+    StateTransitionError,
+};
+use std::borrow::Cow;
 use types::{ActiveState, BeaconBlock, CrystallizedState, Hash256};
 
-impl<T> BeaconChain<T>
+impl<T, F> BeaconChain<T, F>
 where
     T: ClientDB + Sized,
+    F: ForkChoice,
 {
-    pub(crate) fn transition_states(
+    /// Returns the states to which a block at `slot` is applied (with `apply_block`), given the
+    /// states of its parent.
+    ///
+    /// If `slot` is at least one cycle beyond the last state recalculation of the parent, the
+    /// states are recalculated and returned as `Cow::Owned`, in which case the recalculated
+    /// crystallized state assigns the committees and proposer of `slot`. Otherwise the states of
+    /// the parent are returned as `Cow::Borrowed`.
+    ///
+    /// Block processing, block production and the test utils all use this function, so that they
+    /// agree on the states to which a block is applied.
+    pub(crate) fn pre_block_states<'a>(
+        &self,
+        act_state: &'a ActiveState,
+        cry_state: &'a CrystallizedState,
+        slot: u64,
+    ) -> Result<(Cow<'a, ActiveState>, Cow<'a, CrystallizedState>), StateTransitionError> {
+        match self.recalculate_states(act_state, cry_state, slot)? {
+            Some((act_state, cry_state)) => Ok((Cow::Owned(act_state), Cow::Owned(cry_state))),
+            None => Ok((Cow::Borrowed(act_state), Cow::Borrowed(cry_state))),
+        }
+    }
+
+    /// If `slot` is at least one cycle beyond the last state recalculation, recalculate the
+    /// crystallized state (once for each elapsed cycle).
+    ///
+    /// Returns `None` if no recalculation is due.
+    fn recalculate_states(
         &self,
         act_state: &ActiveState,
         cry_state: &CrystallizedState,
-        block: &BeaconBlock,
-        block_hash: &Hash256,
-    ) -> Result<(ActiveState, Option<CrystallizedState>), StateTransitionError> {
-        let state_recalc_distance = block
-            .slot
+        slot: u64,
+    ) -> Result<Option<(ActiveState, CrystallizedState)>, StateTransitionError> {
+        let state_recalc_distance = slot
             .checked_sub(cry_state.last_state_recalculation_slot)
             .ok_or(StateTransitionError::BlockSlotBeforeRecalcSlot)?;
 
-        if state_recalc_distance >= u64::from(self.config.cycle_length) {
-            panic!("Not implemented!")
-        } else {
-            let new_act_state = extend_active_state(act_state, block, block_hash)?;
-            Ok((new_act_state, None))
+        let cycle_length = self.spec.epoch_length;
+
+        if state_recalc_distance < cycle_length {
+            return Ok(None);
+        }
+
+        let (mut new_cry_state, mut new_act_state) =
+            recalculate_crystallized_state(cry_state, act_state, &self.spec)?;
+
+        /*
+         * If several cycles have passed without a block, recalculate for each of them.
+         */
+        while slot - new_cry_state.last_state_recalculation_slot >= cycle_length {
+            let (cry_state, act_state) =
+                recalculate_crystallized_state(&new_cry_state, &new_act_state, &self.spec)?;
+            new_cry_state = cry_state;
+            new_act_state = act_state;
         }
+
+        Ok(Some((new_act_state, new_cry_state)))
+    }
+
+    /// Apply `block` to the given states, which must have been recalculated up to the slot of the
+    /// block (see `pre_block_states`).
+    ///
+    /// The randao reveal of the block is verified against the commitment of its proposer and
+    /// replaces it, then the specials of the block are applied to the validators.
+    ///
+    /// The `recent_block_hashes` of the active state are extended with `parent_hash`, the hash of
+    /// the parent of the block. A block commits to the roots of its post-states, so they cannot
+    /// contain the hash of the block itself.
+    pub(crate) fn apply_block(
+        &self,
+        act_state: &ActiveState,
+        cry_state: &CrystallizedState,
+        block: &BeaconBlock,
+        parent_hash: &Hash256,
+    ) -> Result<(ActiveState, CrystallizedState), StateTransitionError> {
+        let new_cry_state = update_crystallized_state(cry_state, block, &self.spec)?;
+        let new_act_state = extend_active_state(act_state, block, parent_hash)?;
+        Ok((new_act_state, new_cry_state))
     }
 }
@@ -0,0 +1,88 @@
+extern crate db;
+extern crate ssz;
+extern crate types;
+
+mod lmd_ghost;
+mod naive;
+
+pub use lmd_ghost::LmdGhostForkChoice;
+pub use naive::NaiveForkChoice;
+
+use db::stores::BeaconBlockStoreError;
+use db::DBError;
+use ssz::DecodeError;
+use std::collections::HashSet;
+use types::{BeaconBlock, Hash256, ValidatorRecord};
+
+#[derive(Debug, PartialEq)]
+pub enum ForkChoiceError {
+    BadSszInDatabase,
+    MissingBlock,
+    DBError(String),
+}
+
+/// A rule for selecting the canonical head from a tree of blocks.
+///
+/// Implementations are informed of each valid block and of each validator attestation as they
+/// are processed, and may then be asked for the head of the chain at any time.
+///
+/// A block is tried against a clone of the fork choice, which only replaces the original once the
+/// block has been stored.
+pub trait ForkChoice: Clone {
+    /// Inform the fork choice of a new, valid block.
+    ///
+    /// The parent of the block must have been previously added, unless the block is the root of
+    /// the tree (e.g., the genesis block).
+    fn add_block(
+        &mut self,
+        block: &BeaconBlock,
+        block_hash: &Hash256,
+    ) -> Result<(), ForkChoiceError>;
+
+    /// Inform the fork choice that the validator at `validator_index` has attested to the block
+    /// with `target_block_hash`.
+    fn add_attestation(
+        &mut self,
+        validator_index: usize,
+        target_block_hash: &Hash256,
+    ) -> Result<(), ForkChoiceError>;
+
+    /// Forget the given blocks (e.g., blocks which have been pruned from the database).
+    ///
+    /// Attestations targeting the removed blocks are discarded.
+    fn remove_blocks(&mut self, block_hashes: &HashSet<Hash256>) -> Result<(), ForkChoiceError>;
+
+    /// Return the hash of the head block, searching only the descendants of
+    /// `justified_block_hash`.
+    ///
+    /// The `validators` are used to determine the weight of attestations.
+    fn find_head(
+        &mut self,
+        justified_block_hash: &Hash256,
+        validators: &[ValidatorRecord],
+    ) -> Result<Hash256, ForkChoiceError>;
+}
+
+impl From<DecodeError> for ForkChoiceError {
+    fn from(_: DecodeError) -> Self {
+        ForkChoiceError::BadSszInDatabase
+    }
+}
+
+impl From<DBError> for ForkChoiceError {
+    fn from(e: DBError) -> Self {
+        ForkChoiceError::DBError(e.message)
+    }
+}
+
+impl From<BeaconBlockStoreError> for ForkChoiceError {
+    fn from(e: BeaconBlockStoreError) -> Self {
+        match e {
+            BeaconBlockStoreError::UnknownBeaconBlock => ForkChoiceError::MissingBlock,
+            BeaconBlockStoreError::InvalidBeaconBlock
+            | BeaconBlockStoreError::InvalidBlockHash
+            | BeaconBlockStoreError::InvalidIndex => ForkChoiceError::BadSszInDatabase,
+            BeaconBlockStoreError::DBError(s) => ForkChoiceError::DBError(s),
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use super::{ForkChoice, ForkChoiceError};
+use db::stores::BeaconBlockStore;
+use db::ClientDB;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use types::{BeaconBlock, Hash256, ValidatorRecord, ValidatorStatus};
+
+/// A latest-message-driven GHOST ("greedy heaviest observed sub-tree") fork choice.
+///
+/// Starting from the justified block, the child with the greatest weight is selected until a
+/// block without children is reached. The weight of a block is the sum of the balances of the
+/// active validators whose latest attestation targets the block or one of its descendants.
+pub struct LmdGhostForkChoice<T>
+where
+    T: ClientDB + Sized,
+{
+    /// The store used to determine the ancestry of attestation targets.
+    block_store: Arc<BeaconBlockStore<T>>,
+    /// A map of validator index to the hash of the block targeted by its latest attestation.
+    latest_attestation_targets: HashMap<usize, Hash256>,
+    /// A map of block hash to the hashes of the known children of that block.
+    children: HashMap<Hash256, Vec<Hash256>>,
+    /// A map of block hash to the slot of that block.
+    block_slots: HashMap<Hash256, u64>,
+}
+
+impl<T> LmdGhostForkChoice<T>
+where
+    T: ClientDB + Sized,
+{
+    pub fn new(block_store: Arc<BeaconBlockStore<T>>) -> Self {
+        Self {
+            block_store,
+            latest_attestation_targets: HashMap::new(),
+            children: HashMap::new(),
+            block_slots: HashMap::new(),
+        }
+    }
+
+    /// Returns the sum of the attestation weights in `target_weights` which target `block_hash`
+    /// (at `slot`) or one of its descendants.
+    fn weight_of(
+        &self,
+        block_hash: &Hash256,
+        slot: u64,
+        target_weights: &HashMap<Hash256, u64>,
+    ) -> Result<u64, ForkChoiceError> {
+        let mut weight: u64 = 0;
+        for (target, target_weight) in target_weights {
+            let ancestor = self.block_store.block_at_slot(target, slot)?;
+            if let Some((ancestor_hash, _)) = ancestor {
+                if ancestor_hash[..] == block_hash[..] {
+                    weight = weight.saturating_add(*target_weight);
+                }
+            }
+        }
+        Ok(weight)
+    }
+}
+
+/*
+ * Implemented by hand as the derive would require `T: Clone`, although only the `Arc` of the store
+ * is cloned.
+ */
+impl<T> Clone for LmdGhostForkChoice<T>
+where
+    T: ClientDB + Sized,
+{
+    fn clone(&self) -> Self {
+        Self {
+            block_store: self.block_store.clone(),
+            latest_attestation_targets: self.latest_attestation_targets.clone(),
+            children: self.children.clone(),
+            block_slots: self.block_slots.clone(),
+        }
+    }
+}
+
+impl<T> ForkChoice for LmdGhostForkChoice<T>
+where
+    T: ClientDB + Sized,
+{
+    fn add_block(
+        &mut self,
+        block: &BeaconBlock,
+        block_hash: &Hash256,
+    ) -> Result<(), ForkChoiceError> {
+        if let Some(parent_hash) = block.ancestor_hashes.first() {
+            self.children
+                .entry(*parent_hash)
+                .or_default()
+                .push(*block_hash);
+        }
+        self.block_slots.insert(*block_hash, block.slot);
+        Ok(())
+    }
+
+    /// Record the attestation as the latest message of the validator.
+    ///
+    /// Attestations to unknown blocks are ignored, as are attestations which target a block at
+    /// a lower slot than the current latest message of the validator.
+    fn add_attestation(
+        &mut self,
+        validator_index: usize,
+        target_block_hash: &Hash256,
+    ) -> Result<(), ForkChoiceError> {
+        let target_slot = match self.block_slots.get(target_block_hash) {
+            Some(slot) => *slot,
+            None => return Ok(()),
+        };
+
+        let existing_slot = self
+            .latest_attestation_targets
+            .get(&validator_index)
+            .and_then(|existing| self.block_slots.get(existing));
+        let is_newer = match existing_slot {
+            Some(existing_slot) => target_slot >= *existing_slot,
+            None => true,
+        };
+
+        if is_newer {
+            self.latest_attestation_targets
+                .insert(validator_index, *target_block_hash);
+        }
+        Ok(())
+    }
+
+    fn remove_blocks(&mut self, block_hashes: &HashSet<Hash256>) -> Result<(), ForkChoiceError> {
+        self.latest_attestation_targets
+            .retain(|_, target| !block_hashes.contains(target));
+        self.children.retain(|hash, _| !block_hashes.contains(hash));
+        for children in self.children.values_mut() {
+            children.retain(|child| !block_hashes.contains(child));
+        }
+        self.block_slots
+            .retain(|hash, _| !block_hashes.contains(hash));
+        Ok(())
+    }
+
+    fn find_head(
+        &mut self,
+        justified_block_hash: &Hash256,
+        validators: &[ValidatorRecord],
+    ) -> Result<Hash256, ForkChoiceError> {
+        /*
+         * Sum the balances of the active validators attesting to each target so the ancestry of
+         * each target need only be checked once per child.
+         */
+        let mut target_weights: HashMap<Hash256, u64> = HashMap::new();
+        for (validator_index, target) in &self.latest_attestation_targets {
+            match validators.get(*validator_index) {
+                Some(validator) if validator.status_is(ValidatorStatus::Active) => {
+                    let weight = target_weights.entry(*target).or_insert(0);
+                    *weight = weight.saturating_add(validator.balance);
+                }
+                _ => (),
+            }
+        }
+
+        /*
+         * Walk down the tree, always choosing the heaviest child. Ties are broken by the highest
+         * block hash.
+         */
+        let mut head = *justified_block_hash;
+        loop {
+            let children = match self.children.get(&head) {
+                Some(children) if !children.is_empty() => children,
+                _ => return Ok(head),
+            };
+
+            let mut best: Option<(u64, Hash256)> = None;
+            for child in children {
+                let slot = *self
+                    .block_slots
+                    .get(child)
+                    .ok_or(ForkChoiceError::MissingBlock)?;
+                let candidate = (self.weight_of(child, slot, &target_weights)?, *child);
+                best = match best {
+                    Some(current) if current >= candidate => Some(current),
+                    _ => Some(candidate),
+                };
+            }
+
+            match best {
+                Some((_, hash)) => head = hash,
+                None => return Ok(head),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::MemoryDB;
+    use ssz::ssz_encode;
+
+    struct TestTree {
+        fork_choice: LmdGhostForkChoice<MemoryDB>,
+        block_store: Arc<BeaconBlockStore<MemoryDB>>,
+    }
+
+    impl TestTree {
+        fn new() -> Self {
+            let db = Arc::new(MemoryDB::open());
+            let block_store = Arc::new(BeaconBlockStore::new(db.clone()));
+            Self {
+                fork_choice: LmdGhostForkChoice::new(block_store.clone()),
+                block_store,
+            }
+        }
+
+        /// Store a block with the given hash, slot and parent and add it to the fork choice.
+        fn add(&mut self, name: &str, slot: u64, parent_hash: Hash256) -> Hash256 {
+            let hash = Hash256::from(name.as_bytes());
+            let mut block = BeaconBlock::zero();
+            block.slot = slot;
+            block.ancestor_hashes = vec![parent_hash];
+            self.block_store
+                .put_serialized_block(&hash, &ssz_encode(&block))
+                .unwrap();
+            self.fork_choice.add_block(&block, &hash).unwrap();
+            hash
+        }
+    }
+
+    fn validators(balances: &[u64]) -> Vec<ValidatorRecord> {
+        balances
+            .iter()
+            .map(|balance| {
+                let (mut validator, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+                validator.balance = *balance;
+                validator.status = ValidatorStatus::Active;
+                validator
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lmd_ghost_no_attestations() {
+        let mut tree = TestTree::new();
+        let genesis = tree.add("genesis", 0, Hash256::zero());
+
+        assert_eq!(tree.fork_choice.find_head(&genesis, &[]), Ok(genesis));
+
+        let a = tree.add("a", 1, genesis);
+        let b = tree.add("b", 2, a);
+        assert_eq!(tree.fork_choice.find_head(&genesis, &[]), Ok(b));
+    }
+
+    #[test]
+    fn test_lmd_ghost_heaviest_subtree() {
+        let mut tree = TestTree::new();
+        let genesis = tree.add("genesis", 0, Hash256::zero());
+
+        /*
+         * genesis -> a -> b -> c
+         *         \
+         *          -> x
+         */
+        let a = tree.add("a", 1, genesis);
+        let b = tree.add("b", 2, a);
+        let c = tree.add("c", 3, b);
+        let x = tree.add("x", 2, genesis);
+
+        let validators = validators(&[10, 10, 25]);
+
+        tree.fork_choice.add_attestation(0, &b).unwrap();
+        tree.fork_choice.add_attestation(1, &c).unwrap();
+        tree.fork_choice.add_attestation(2, &x).unwrap();
+
+        /*
+         * `x` has a single vote which outweighs the two votes for the descendants of `a`.
+         */
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(x));
+
+        /*
+         * Searching from `a` ignores the `x` fork entirely.
+         */
+        assert_eq!(tree.fork_choice.find_head(&a, &validators), Ok(c));
+
+        /*
+         * Validators which are not active do not contribute any weight.
+         */
+        let mut validators = validators;
+        validators[2].status = ValidatorStatus::PendingExit;
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(c));
+    }
+
+    #[test]
+    fn test_lmd_ghost_latest_message() {
+        let mut tree = TestTree::new();
+        let genesis = tree.add("genesis", 0, Hash256::zero());
+        let a = tree.add("a", 1, genesis);
+        let b = tree.add("b", 2, genesis);
+
+        let validators = validators(&[10, 20]);
+
+        tree.fork_choice.add_attestation(0, &a).unwrap();
+        tree.fork_choice.add_attestation(1, &a).unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(a));
+
+        /*
+         * Only the latest message of each validator counts.
+         */
+        tree.fork_choice.add_attestation(1, &b).unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(b));
+
+        /*
+         * Attestations to older blocks do not replace the latest message.
+         */
+        tree.fork_choice.add_attestation(1, &a).unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(b));
+
+        /*
+         * Attestations to unknown blocks are ignored.
+         */
+        tree.fork_choice
+            .add_attestation(0, &Hash256::from("unknown".as_bytes()))
+            .unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(b));
+    }
+
+    #[test]
+    fn test_lmd_ghost_tie_break() {
+        let mut tree = TestTree::new();
+        let genesis = tree.add("genesis", 0, Hash256::zero());
+        let a = tree.add("a", 1, genesis);
+        let b = tree.add("b", 1, genesis);
+
+        let high = if a > b { a } else { b };
+        assert_eq!(tree.fork_choice.find_head(&genesis, &[]), Ok(high));
+    }
+
+    #[test]
+    fn test_lmd_ghost_remove_blocks() {
+        let mut tree = TestTree::new();
+        let genesis = tree.add("genesis", 0, Hash256::zero());
+        let a = tree.add("a", 1, genesis);
+        let x = tree.add("x", 1, genesis);
+        let y = tree.add("y", 2, x);
+
+        let validators = validators(&[10, 20]);
+        tree.fork_choice.add_attestation(0, &a).unwrap();
+        tree.fork_choice.add_attestation(1, &y).unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(y));
+
+        /*
+         * Removing the `x` fork also discards the attestation to `y`.
+         */
+        let removed: HashSet<Hash256> = [x, y].iter().cloned().collect();
+        tree.fork_choice.remove_blocks(&removed).unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(a));
+
+        /*
+         * Attestations to removed blocks are ignored.
+         */
+        tree.fork_choice.add_attestation(1, &y).unwrap();
+        assert_eq!(tree.fork_choice.find_head(&genesis, &validators), Ok(a));
+    }
+}
@@ -0,0 +1,150 @@
+use super::{ForkChoice, ForkChoiceError};
+use std::collections::{HashMap, HashSet};
+use types::{BeaconBlock, Hash256, ValidatorRecord};
+
+/// A fork choice which selects the head block with the highest slot, ties are broken by the
+/// lowest block hash (as in the original `naive_fork_choice` function).
+///
+/// Attestations are ignored entirely. This rule is not safe for use on a real network, it exists
+/// as a simple and predictable rule for testing.
+#[derive(Default, Clone)]
+pub struct NaiveForkChoice {
+    /// A map of head block hashes (tips of chains) to their slot.
+    heads: HashMap<Hash256, u64>,
+}
+
+impl NaiveForkChoice {
+    pub fn new() -> Self {
+        Self {
+            heads: HashMap::new(),
+        }
+    }
+}
+
+impl ForkChoice for NaiveForkChoice {
+    fn add_block(
+        &mut self,
+        block: &BeaconBlock,
+        block_hash: &Hash256,
+    ) -> Result<(), ForkChoiceError> {
+        if let Some(parent_hash) = block.ancestor_hashes.first() {
+            self.heads.remove(parent_hash);
+        }
+        self.heads.insert(*block_hash, block.slot);
+        Ok(())
+    }
+
+    fn add_attestation(
+        &mut self,
+        _validator_index: usize,
+        _target_block_hash: &Hash256,
+    ) -> Result<(), ForkChoiceError> {
+        Ok(())
+    }
+
+    fn remove_blocks(&mut self, block_hashes: &HashSet<Hash256>) -> Result<(), ForkChoiceError> {
+        self.heads.retain(|hash, _| !block_hashes.contains(hash));
+        Ok(())
+    }
+
+    /// Return the head with the highest slot, preferring the lowest hash amongst heads of equal
+    /// slot.
+    ///
+    /// The justified block is only returned if no blocks have been added.
+    fn find_head(
+        &mut self,
+        justified_block_hash: &Hash256,
+        _validators: &[ValidatorRecord],
+    ) -> Result<Hash256, ForkChoiceError> {
+        let head = self
+            .heads
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(hash, _)| *hash)
+            .unwrap_or(*justified_block_hash);
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(slot: u64, parent_hash: Hash256) -> BeaconBlock {
+        let mut block = BeaconBlock::zero();
+        block.slot = slot;
+        block.ancestor_hashes = vec![parent_hash];
+        block
+    }
+
+    #[test]
+    fn test_naive_fork_choice() {
+        let genesis_hash = Hash256::from("genesis".as_bytes());
+        let a_hash = Hash256::from("a".as_bytes());
+        let b_hash = Hash256::from("b".as_bytes());
+        let c_hash = Hash256::from("c".as_bytes());
+
+        let mut fork_choice = NaiveForkChoice::new();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(genesis_hash));
+
+        fork_choice
+            .add_block(&block(0, Hash256::zero()), &genesis_hash)
+            .unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(genesis_hash));
+
+        fork_choice
+            .add_block(&block(2, genesis_hash), &a_hash)
+            .unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(a_hash));
+
+        /*
+         * A fork at a lower slot does not become the head.
+         */
+        fork_choice
+            .add_block(&block(1, genesis_hash), &b_hash)
+            .unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(a_hash));
+
+        /*
+         * Extending the fork beyond the slot of `a` causes a re-org.
+         */
+        fork_choice.add_block(&block(3, b_hash), &c_hash).unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(c_hash));
+    }
+
+    #[test]
+    fn test_naive_fork_choice_tie_break() {
+        let genesis_hash = Hash256::from("genesis".as_bytes());
+        let low_hash = Hash256::from([1; 32]);
+        let high_hash = Hash256::from([2; 32]);
+
+        let mut fork_choice = NaiveForkChoice::new();
+        fork_choice
+            .add_block(&block(1, genesis_hash), &high_hash)
+            .unwrap();
+        fork_choice
+            .add_block(&block(1, genesis_hash), &low_hash)
+            .unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(low_hash));
+    }
+
+    #[test]
+    fn test_naive_fork_choice_remove_blocks() {
+        let genesis_hash = Hash256::from("genesis".as_bytes());
+        let a_hash = Hash256::from("a".as_bytes());
+        let b_hash = Hash256::from("b".as_bytes());
+
+        let mut fork_choice = NaiveForkChoice::new();
+        fork_choice
+            .add_block(&block(2, genesis_hash), &a_hash)
+            .unwrap();
+        fork_choice
+            .add_block(&block(1, genesis_hash), &b_hash)
+            .unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(a_hash));
+
+        let removed: HashSet<Hash256> = [a_hash].iter().cloned().collect();
+        fork_choice.remove_blocks(&removed).unwrap();
+        assert_eq!(fork_choice.find_head(&genesis_hash, &[]), Ok(b_hash));
+    }
+}
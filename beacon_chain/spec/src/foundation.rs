@@ -2,6 +2,11 @@ use super::ChainSpec;
 
 use types::{Address, Hash256};
 
+/*
+ * Presently this is just some arbitrary time in Sept 2018.
+ */
+const GENESIS_TIME: u64 = 1_537_488_655;
+
 impl ChainSpec {
     /// Returns a `ChainSpec` compatible with the specification from Ethereum Foundation.
     pub fn foundation() -> Self {
@@ -17,6 +22,7 @@ impl ChainSpec {
             beacon_chain_shard_number: u64::max_value(),
             bls_withdrawal_prefix_byte: 0x00,
             max_casper_votes: 1_024,
+            latest_block_roots_length: u64::pow(2, 13),
             /*
              *  Deposit contract
              */
@@ -30,12 +36,15 @@ impl ChainSpec {
             initial_fork_version: 0,
             initial_slot_number: 0,
             zero_hash: Hash256::zero(),
+            genesis_time: GENESIS_TIME,
+            initial_validators: vec![],
             /*
              * Time parameters
              */
             slot_duration: 6,
             min_attestation_inclusion_delay: 4,
             epoch_length: 64,
+            randao_slots_per_layer: u64::pow(2, 12),
             min_validator_registry_change_interval: 256,
             pow_receipt_root_voting_period: 1_024,
             shard_persistent_committee_change_period: u64::pow(2, 17),
@@ -56,6 +65,12 @@ impl ChainSpec {
             max_attestations: 128,
             max_deposits: 16,
             max_exits: 16,
+            /*
+             * Signature domains
+             */
+            domain_attestation: 1,
+            domain_proposal: 2,
+            domain_logout: 3,
         }
     }
 }
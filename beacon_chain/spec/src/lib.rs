@@ -1,9 +1,11 @@
 extern crate types;
 
 mod foundation;
+mod minimal;
 
-use types::{Address, Hash256};
+use types::{Address, Hash256, ValidatorRegistration};
 
+#[derive(Debug, PartialEq, Clone)]
 pub struct ChainSpec {
     /*
      * Misc
@@ -16,6 +18,7 @@ pub struct ChainSpec {
     pub beacon_chain_shard_number: u64,
     pub bls_withdrawal_prefix_byte: u8,
     pub max_casper_votes: u64,
+    pub latest_block_roots_length: u64,
     /*
      *  Deposit contract
      */
@@ -29,12 +32,15 @@ pub struct ChainSpec {
     pub initial_fork_version: u64,
     pub initial_slot_number: u64,
     pub zero_hash: Hash256,
+    pub genesis_time: u64,
+    pub initial_validators: Vec<ValidatorRegistration>,
     /*
      * Time parameters
      */
     pub slot_duration: u64,
     pub min_attestation_inclusion_delay: u64,
     pub epoch_length: u64,
+    pub randao_slots_per_layer: u64,
     pub min_validator_registry_change_interval: u64,
     pub pow_receipt_root_voting_period: u64,
     pub shard_persistent_committee_change_period: u64,
@@ -55,4 +61,82 @@ pub struct ChainSpec {
     pub max_attestations: u64,
     pub max_deposits: u64,
     pub max_exits: u64,
+    /*
+     * Signature domains
+     */
+    pub domain_attestation: u64,
+    pub domain_proposal: u64,
+    pub domain_logout: u64,
+}
+
+impl ChainSpec {
+    /// Returns the balance of a validator with the maximum deposit, in Gwei.
+    ///
+    /// Returns `None` if the balance does not fit in a `u64`.
+    pub fn max_deposit_gwei(&self) -> Option<u64> {
+        self.max_deposit.checked_mul(self.gwei_per_eth)
+    }
+
+    /// Returns `true` if the chain may be run with this spec.
+    ///
+    /// Each slot of an epoch must be assigned at least one shard, otherwise committee assignment
+    /// will fail.
+    pub fn validate(&self) -> bool {
+        if self.epoch_length == 0 || self.shard_count / self.epoch_length == 0 {
+            return false;
+        }
+        self.max_deposit_gwei().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_deposit_gwei() {
+        assert_eq!(
+            ChainSpec::foundation().max_deposit_gwei(),
+            Some(32_000_000_000)
+        );
+        assert_eq!(
+            ChainSpec::minimal().max_deposit_gwei(),
+            Some(32_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_max_deposit_gwei_overflow() {
+        let mut spec = ChainSpec::minimal();
+        spec.max_deposit = 1 << 40;
+        assert_eq!(spec.max_deposit_gwei(), None);
+        assert!(!spec.validate());
+    }
+
+    #[test]
+    fn test_presets_assign_committees() {
+        /*
+         * Each slot of an epoch must be assigned at least one shard.
+         */
+        for spec in &[ChainSpec::foundation(), ChainSpec::minimal()] {
+            assert!(spec.shard_count / spec.epoch_length > 0);
+            assert!(spec.target_committee_size > 0);
+            assert!(spec.validate());
+        }
+    }
+
+    #[test]
+    fn test_validate_zero_shard_count_and_epoch_length() {
+        let mut spec = ChainSpec::minimal();
+        spec.shard_count = 0;
+        assert!(!spec.validate());
+
+        let mut spec = ChainSpec::minimal();
+        spec.epoch_length = 0;
+        assert!(!spec.validate());
+
+        let mut spec = ChainSpec::minimal();
+        spec.shard_count = spec.epoch_length - 1;
+        assert!(!spec.validate());
+    }
 }
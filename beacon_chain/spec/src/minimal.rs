@@ -0,0 +1,31 @@
+use super::ChainSpec;
+
+impl ChainSpec {
+    /// Returns a `ChainSpec` with few shards and short epochs, suitable for testing with a
+    /// small number of validators.
+    ///
+    /// Parameters not listed here are the same as `ChainSpec::foundation()`.
+    pub fn minimal() -> Self {
+        Self {
+            /*
+             * Misc
+             */
+            shard_count: 4,
+            target_committee_size: 2,
+            max_casper_votes: 16,
+            latest_block_roots_length: 64,
+            /*
+             * Time parameters
+             */
+            min_attestation_inclusion_delay: 1,
+            epoch_length: 4,
+            randao_slots_per_layer: 4,
+            min_validator_registry_change_interval: 8,
+            pow_receipt_root_voting_period: 16,
+            shard_persistent_committee_change_period: 64,
+            collective_penalty_calculation_period: 256,
+            zero_balance_validator_ttl: 1_024,
+            ..ChainSpec::foundation()
+        }
+    }
+}
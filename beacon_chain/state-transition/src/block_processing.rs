@@ -0,0 +1,405 @@
+use super::helpers::{
+    beacon_proposer_index, block_hash_at_slot, state_committee_for_shard, update_randao_commitment,
+    verify_randao_reveal,
+};
+use super::special_processing::{process_specials, SpecialProcessingError};
+use spec::ChainSpec;
+use types::{Attestation, BeaconBlock, BeaconState, PendingAttestationRecord};
+
+/// Reasons why a block could not be applied to a `BeaconState` by `per_block_processing`.
+#[derive(Debug, PartialEq)]
+pub enum BlockProcessingError {
+    /// The block is not for the slot of the state.
+    SlotMismatch,
+    /// The parent of the block is not the latest block recorded in the state.
+    ParentHashMismatch,
+    /// The state has no proposer for the slot of the block.
+    NoBlockProposer,
+    /// The `randao_reveal` does not hash to the `randao_commitment` of the proposer within the
+    /// number of layers allowed.
+    InvalidRandaoReveal,
+    TooManyAttestations,
+    AttestationTooRecent,
+    AttestationTooOld,
+    /// The attestation does not reference the justified slot of the epoch in which it was made.
+    InvalidJustifiedSlot,
+    /// The attestation does not reference the block at its justified slot.
+    InvalidJustifiedBlockHash,
+    /// There is no committee for the shard of the attestation at its slot.
+    NoCommitteeForShard,
+    /// The participation bitfield has bits set beyond the size of the committee.
+    InvalidParticipationBitfield,
+    SpecialProcessingError(SpecialProcessingError),
+}
+
+impl From<SpecialProcessingError> for BlockProcessingError {
+    fn from(e: SpecialProcessingError) -> BlockProcessingError {
+        BlockProcessingError::SpecialProcessingError(e)
+    }
+}
+
+/// Apply a block to the `state`, which must already have been advanced to the slot of the block
+/// by `per_slot_processing`. If the block is at the last slot of an epoch, the caller must then
+/// apply `per_epoch_processing`. The three are applied in that order by `transition_state`.
+///
+/// The attestations of the block are added to the `latest_attestations` of the state, to be
+/// processed by `per_epoch_processing`. The `randao_reveal` is verified against the commitment of
+/// the proposer, becomes their new commitment and is mixed into the `randao_mix`. The specials of
+/// the block (logouts, Casper slashings and randao changes) are applied to the
+/// validator registry.
+///
+/// Attestation signatures are not verified, see the `attestation_validation` crate. The `state` is
+/// unchanged if an error is returned.
+pub fn per_block_processing(
+    state: &mut BeaconState,
+    block: &BeaconBlock,
+    spec: &ChainSpec,
+) -> Result<(), BlockProcessingError> {
+    if block.slot != state.slot {
+        return Err(BlockProcessingError::SlotMismatch);
+    }
+    match (block.parent_hash(), state.latest_block_hashes.last()) {
+        (Some(parent_hash), Some(latest_hash)) if parent_hash == latest_hash => (),
+        _ => return Err(BlockProcessingError::ParentHashMismatch),
+    }
+    let proposer = validate_randao_reveal(state, block, spec)?;
+
+    if block.attestations.len() as u64 > spec.max_attestations {
+        return Err(BlockProcessingError::TooManyAttestations);
+    }
+    for attestation in &block.attestations {
+        validate_attestation(state, attestation, spec)?;
+    }
+
+    /*
+     * Most blocks have no specials, in which case the registry need not be copied.
+     *
+     * Otherwise, the specials are applied after the randao reveal of the proposer, so that a
+     * randao change by the proposer takes precedence.
+     */
+    let validator_registry = if block.specials.is_empty() {
+        None
+    } else {
+        let mut validators = state.validator_registry.clone();
+        update_randao_commitment(&mut validators[proposer], block);
+        Some(process_specials(
+            validators,
+            &block.specials,
+            state.slot,
+            proposer,
+            &state.fork_data,
+            spec,
+        )?)
+    };
+
+    /*
+     * The block is valid, so the state may now be modified.
+     */
+    for attestation in &block.attestations {
+        state.latest_attestations.push(PendingAttestationRecord {
+            data: attestation.data.clone(),
+            participation_bitfield: attestation.participation_bitfield.clone(),
+            custody_bitfield: attestation.custody_bitfield.clone(),
+            slot_included: block.slot,
+        });
+    }
+    state.randao_mix = state.randao_mix ^ block.randao_reveal;
+    match validator_registry {
+        Some(validator_registry) => state.validator_registry = validator_registry,
+        None => update_randao_commitment(&mut state.validator_registry[proposer], block),
+    }
+
+    Ok(())
+}
+
+/// Check that the `randao_reveal` of the block reaches the `randao_commitment` of the proposer
+/// when hashed no more than the number of layers allowed since the commitment last changed.
+///
+/// Returns the index of the proposer.
+fn validate_randao_reveal(
+    state: &BeaconState,
+    block: &BeaconBlock,
+    spec: &ChainSpec,
+) -> Result<usize, BlockProcessingError> {
+    let proposer = beacon_proposer_index(state, state.slot, spec)
+        .ok_or(BlockProcessingError::NoBlockProposer)?;
+    let validator = state
+        .validator_registry
+        .get(proposer)
+        .ok_or(BlockProcessingError::NoBlockProposer)?;
+
+    if verify_randao_reveal(validator, &block.randao_reveal, state.slot, spec) {
+        Ok(proposer)
+    } else {
+        Err(BlockProcessingError::InvalidRandaoReveal)
+    }
+}
+
+/// Check that an attestation may be included in a block at the slot of the `state`.
+fn validate_attestation(
+    state: &BeaconState,
+    attestation: &Attestation,
+    spec: &ChainSpec,
+) -> Result<(), BlockProcessingError> {
+    let data = &attestation.data;
+
+    /*
+     * An attestation must be at least `min_attestation_inclusion_delay` slots older than the
+     * block, but no more than an epoch older.
+     */
+    match data.slot.checked_add(spec.min_attestation_inclusion_delay) {
+        Some(slot) if slot <= state.slot => (),
+        _ => return Err(BlockProcessingError::AttestationTooRecent),
+    }
+    if data.slot < state.slot.saturating_sub(spec.epoch_length) {
+        return Err(BlockProcessingError::AttestationTooOld);
+    }
+
+    /*
+     * Attestations made since the last epoch processing reference the current justified slot,
+     * older attestations reference the justified slot prior to it.
+     */
+    let justified_slot = if data.slot >= state.latest_state_recalculation_slot {
+        state.justified_slot
+    } else {
+        state.previous_justified_slot
+    };
+    if data.justified_slot != justified_slot {
+        return Err(BlockProcessingError::InvalidJustifiedSlot);
+    }
+    if block_hash_at_slot(state, data.justified_slot) != Some(&data.justified_block_hash) {
+        return Err(BlockProcessingError::InvalidJustifiedBlockHash);
+    }
+
+    let shard_and_committee = state_committee_for_shard(state, data.slot, data.shard, spec)
+        .ok_or(BlockProcessingError::NoCommitteeForShard)?;
+    if let Some(highest_set_bit) = attestation.participation_bitfield.highest_set_bit() {
+        if highest_set_bit >= shard_and_committee.committee.len() {
+            return Err(BlockProcessingError::InvalidParticipationBitfield);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls::Signature;
+    use ssz::ssz_encode;
+    use test_utils::{
+        test_attestation, test_block, test_hash_onion, test_state, test_state_with_keypairs,
+    };
+    use types::{Bitfield, Hash256, RandaoChangeSpecial, SpecialRecord};
+
+    /// Returns a state at slot 5, where slot 4 was the first slot of the current epoch, and a
+    /// valid block for that state with an attestation for slot 4.
+    fn state_and_block(spec: &ChainSpec) -> (BeaconState, BeaconBlock) {
+        let mut state = test_state(4, spec);
+        state.slot = 5;
+        state.latest_state_recalculation_slot = 4;
+        let attestation = test_attestation(&state, 4, spec);
+        let block = test_block(&state, vec![attestation], spec);
+        (state, block)
+    }
+
+    #[test]
+    fn test_per_block_processing() {
+        let spec = ChainSpec::minimal();
+        let (mut state, block) = state_and_block(&spec);
+        state.randao_mix = Hash256::from(1);
+
+        per_block_processing(&mut state, &block, &spec).unwrap();
+
+        assert_eq!(
+            state.latest_attestations,
+            vec![PendingAttestationRecord {
+                data: block.attestations[0].data.clone(),
+                participation_bitfield: block.attestations[0].participation_bitfield.clone(),
+                custody_bitfield: block.attestations[0].custody_bitfield.clone(),
+                slot_included: 5,
+            }]
+        );
+        assert_eq!(state.randao_mix, Hash256::from(1) ^ block.randao_reveal);
+
+        /*
+         * Validator 1 is the proposer at slot 5.
+         */
+        assert_eq!(
+            state.validator_registry[1].randao_commitment,
+            block.randao_reveal
+        );
+        assert_eq!(state.validator_registry[1].randao_last_change, 5);
+    }
+
+    #[test]
+    fn test_per_block_processing_randao_reveal() {
+        let spec = ChainSpec::minimal();
+        let (state, mut block) = state_and_block(&spec);
+        let onion = test_hash_onion(1);
+        let commitment = state.validator_registry[1].randao_commitment;
+
+        /*
+         * Two layers may be revealed at slot 5 when the commitment last changed at slot 0.
+         */
+        block.randao_reveal = onion.reveal(&commitment, 2).unwrap();
+        per_block_processing(&mut state.clone(), &block, &spec).unwrap();
+
+        let mut recent_change = state.clone();
+        recent_change.validator_registry[1].randao_last_change = 2;
+        assert_eq!(
+            per_block_processing(&mut recent_change, &block, &spec),
+            Err(BlockProcessingError::InvalidRandaoReveal)
+        );
+
+        block.randao_reveal = commitment;
+        assert_eq!(
+            per_block_processing(&mut state.clone(), &block, &spec),
+            Err(BlockProcessingError::InvalidRandaoReveal)
+        );
+
+        block.randao_reveal = test_hash_onion(2)
+            .reveal(&test_hash_onion(2).commitment(), 1)
+            .unwrap();
+        assert_eq!(
+            per_block_processing(&mut state.clone(), &block, &spec),
+            Err(BlockProcessingError::InvalidRandaoReveal)
+        );
+    }
+
+    #[test]
+    fn test_per_block_processing_invalid_blocks() {
+        let spec = ChainSpec::minimal();
+        let (state, block) = state_and_block(&spec);
+
+        let check = |block: &BeaconBlock, error: BlockProcessingError| {
+            let mut new_state = state.clone();
+            assert_eq!(
+                per_block_processing(&mut new_state, block, &spec),
+                Err(error)
+            );
+            assert_eq!(new_state, state);
+        };
+
+        let mut invalid = block.clone();
+        invalid.slot = 6;
+        check(&invalid, BlockProcessingError::SlotMismatch);
+
+        let mut invalid = block.clone();
+        invalid.ancestor_hashes = vec![Hash256::from("unknown".as_bytes())];
+        check(&invalid, BlockProcessingError::ParentHashMismatch);
+
+        let mut invalid = block.clone();
+        invalid.ancestor_hashes = vec![];
+        check(&invalid, BlockProcessingError::ParentHashMismatch);
+
+        let mut invalid = block.clone();
+        invalid.attestations =
+            vec![block.attestations[0].clone(); spec.max_attestations as usize + 1];
+        check(&invalid, BlockProcessingError::TooManyAttestations);
+
+        let mut invalid = block.clone();
+        invalid.specials = vec![SpecialRecord::logout(&[])];
+        let decode_error = invalid.specials[0].decode_payload().unwrap_err();
+        check(
+            &invalid,
+            BlockProcessingError::SpecialProcessingError(SpecialProcessingError::InvalidPayload(
+                decode_error,
+            )),
+        );
+    }
+
+    #[test]
+    fn test_per_block_processing_specials() {
+        let spec = ChainSpec::minimal();
+        let (mut state, keypairs) = test_state_with_keypairs(4, &spec);
+        state.slot = 5;
+        state.latest_state_recalculation_slot = 4;
+        let mut block = test_block(&state, vec![], &spec);
+        let mut change = RandaoChangeSpecial {
+            proposer_index: 1,
+            randao_commitment: Hash256::from("commitment".as_bytes()),
+            signature: Signature::empty_signature(),
+        };
+        let domain = state.fork_data.domain(5, spec.domain_proposal).unwrap();
+        change.signature = Signature::new(&change.signing_message(domain), &keypairs[1].sk);
+        block.specials = vec![SpecialRecord::randao_change(&ssz_encode(&change))];
+
+        per_block_processing(&mut state, &block, &spec).unwrap();
+
+        assert_eq!(
+            state.validator_registry[1].randao_commitment,
+            change.randao_commitment
+        );
+        assert_eq!(state.validator_registry[1].randao_last_change, 5);
+    }
+
+    #[test]
+    fn test_per_block_processing_invalid_attestations() {
+        let spec = ChainSpec::minimal();
+        let (state, block) = state_and_block(&spec);
+
+        let check = |attestation: Attestation, error: BlockProcessingError| {
+            let mut new_state = state.clone();
+            let mut invalid = block.clone();
+            invalid.attestations.push(attestation);
+            assert_eq!(
+                per_block_processing(&mut new_state, &invalid, &spec),
+                Err(error)
+            );
+            assert_eq!(new_state, state);
+        };
+
+        check(
+            test_attestation(&state, 5, &spec),
+            BlockProcessingError::AttestationTooRecent,
+        );
+
+        let mut attestation = test_attestation(&state, 4, &spec);
+        attestation.data.slot = 0;
+        check(attestation, BlockProcessingError::AttestationTooOld);
+
+        let mut attestation = test_attestation(&state, 4, &spec);
+        attestation.data.justified_slot = 1;
+        check(attestation, BlockProcessingError::InvalidJustifiedSlot);
+
+        let mut attestation = test_attestation(&state, 4, &spec);
+        attestation.data.justified_block_hash = Hash256::from("unknown".as_bytes());
+        check(attestation, BlockProcessingError::InvalidJustifiedBlockHash);
+
+        let mut attestation = test_attestation(&state, 4, &spec);
+        attestation.data.shard = spec.shard_count;
+        check(attestation, BlockProcessingError::NoCommitteeForShard);
+
+        let mut attestation = test_attestation(&state, 4, &spec);
+        attestation.participation_bitfield = Bitfield::from_elem(5, true);
+        check(
+            attestation,
+            BlockProcessingError::InvalidParticipationBitfield,
+        );
+    }
+
+    #[test]
+    fn test_per_block_processing_previous_epoch_attestation() {
+        /*
+         * Attestations made prior to the last epoch processing must reference the previous
+         * justified slot.
+         */
+        let spec = ChainSpec::minimal();
+        let (mut state, _) = state_and_block(&spec);
+        state.previous_justified_slot = 0;
+        state.justified_slot = 4;
+
+        let mut attestation = test_attestation(&state, 3, &spec);
+        assert_eq!(attestation.data.justified_slot, 0);
+        let block = test_block(&state, vec![attestation.clone()], &spec);
+        per_block_processing(&mut state.clone(), &block, &spec).unwrap();
+
+        attestation.data.justified_slot = 4;
+        let block = test_block(&state, vec![attestation], &spec);
+        assert_eq!(
+            per_block_processing(&mut state, &block, &spec),
+            Err(BlockProcessingError::InvalidJustifiedSlot)
+        );
+    }
+}
@@ -0,0 +1,494 @@
+use super::helpers::{
+    active_balance, attesters, block_hash_at_slot, committee_for_shard, is_supermajority,
+    total_active_balance,
+};
+use spec::ChainSpec;
+use std::cmp::max;
+use std::collections::{HashMap, HashSet};
+use types::{
+    BeaconState, CrosslinkRecord, Hash256, PendingAttestationRecord, ShardAndCommittee,
+    ValidatorStatus,
+};
+use validator_change::{update_validator_set, UpdateValidatorSetError};
+use validator_shuffling::{shard_and_committees_for_cycle, ValidatorAssignmentError};
+
+/// Reasons why `per_epoch_processing` could not be applied to a `BeaconState`.
+#[derive(Debug, PartialEq)]
+pub enum EpochProcessingError {
+    /// The state is not at the last slot of the epoch which began at its
+    /// `latest_state_recalculation_slot`.
+    NotAtEpochBoundary,
+    ArithmeticOverflow,
+    ValidatorSetUpdateError(UpdateValidatorSetError),
+    ValidatorAssignmentError(ValidatorAssignmentError),
+}
+
+impl From<UpdateValidatorSetError> for EpochProcessingError {
+    fn from(e: UpdateValidatorSetError) -> EpochProcessingError {
+        EpochProcessingError::ValidatorSetUpdateError(e)
+    }
+}
+
+impl From<ValidatorAssignmentError> for EpochProcessingError {
+    fn from(e: ValidatorAssignmentError) -> EpochProcessingError {
+        EpochProcessingError::ValidatorAssignmentError(e)
+    }
+}
+
+/// Process the epoch which ends with the slot of the `state`, which must be the last slot of the
+/// epoch beginning at its `latest_state_recalculation_slot`.
+///
+/// The `latest_attestations` for the previous and current epochs determine justification,
+/// finalization, crosslinks and validator rewards/penalties. If there has been a finalization
+/// and every shard has been crosslinked since the last change, the validator registry is
+/// updated. The validators are then shuffled into committees for the next epoch.
+///
+/// The `latest_state_recalculation_slot`, the `slot` of each new crosslink and (if the registry is
+/// updated) the `validator_registry_latest_change_slot` are set to the first slot of the next
+/// epoch (i.e., `state.slot + 1`), although the `state` has not yet reached it. That is the slot
+/// from which the new committees and registry apply.
+///
+/// This should be called after `per_slot_processing` and `per_block_processing` (if there is a
+/// block) at the last slot of each epoch, as is done by `transition_state`, so that the block at
+/// the first slot of the next epoch is proposed by a member of that epoch's committees. The
+/// `state` is unchanged if an error is returned.
+pub fn per_epoch_processing(
+    state: &mut BeaconState,
+    spec: &ChainSpec,
+) -> Result<(), EpochProcessingError> {
+    let epoch_length = spec.epoch_length;
+    let next_epoch_start = state
+        .slot
+        .checked_add(1)
+        .ok_or(EpochProcessingError::ArithmeticOverflow)?;
+    if state
+        .latest_state_recalculation_slot
+        .checked_add(epoch_length)
+        != Some(next_epoch_start)
+    {
+        return Err(EpochProcessingError::NotAtEpochBoundary);
+    }
+    let this_epoch_start = state.latest_state_recalculation_slot;
+    let previous_epoch_start = this_epoch_start.saturating_sub(epoch_length);
+
+    /*
+     * Tally the validators who attested to the epoch boundary block of each epoch, and to each
+     * shard block.
+     *
+     * The `shard_and_committee_for_slots` begin at the start of the previous epoch.
+     */
+    let this_boundary_hash = block_hash_at_slot(state, this_epoch_start).cloned();
+    let previous_boundary_hash = block_hash_at_slot(state, previous_epoch_start).cloned();
+    let mut this_boundary_attesters: HashSet<usize> = HashSet::new();
+    let mut previous_boundary_attesters: HashSet<usize> = HashSet::new();
+    let mut crosslink_votes: HashMap<(u64, u16, Hash256), (HashSet<usize>, &ShardAndCommittee)> =
+        HashMap::new();
+    for attestation in &state.latest_attestations {
+        let data = &attestation.data;
+        if data.slot < previous_epoch_start || data.slot >= next_epoch_start {
+            continue;
+        }
+        let shard_and_committee = match committee_for_shard(
+            &state.shard_and_committee_for_slots,
+            data.slot - previous_epoch_start,
+            data.shard,
+        ) {
+            Some(shard_and_committee) => shard_and_committee,
+            None => continue,
+        };
+        let attesters = attesters(
+            &shard_and_committee.committee,
+            &attestation.participation_bitfield,
+        );
+
+        let (boundary_hash, boundary_attesters) = if data.slot >= this_epoch_start {
+            (this_boundary_hash, &mut this_boundary_attesters)
+        } else {
+            (previous_boundary_hash, &mut previous_boundary_attesters)
+        };
+        if boundary_hash == Some(data.epoch_boundary_hash) {
+            boundary_attesters.extend(attesters.iter().cloned());
+        }
+
+        let crosslink_key = (data.slot, shard_and_committee.shard, data.shard_block_hash);
+        let (crosslink_voters, _) = crosslink_votes
+            .entry(crosslink_key)
+            .or_insert_with(|| (HashSet::new(), shard_and_committee));
+        crosslink_voters.extend(attesters);
+    }
+
+    let validators = &state.validator_registry;
+    let total_balance =
+        total_active_balance(validators).ok_or(EpochProcessingError::ArithmeticOverflow)?;
+    let this_boundary_balance = active_balance(validators, this_boundary_attesters.iter())
+        .ok_or(EpochProcessingError::ArithmeticOverflow)?;
+    let previous_boundary_balance = active_balance(validators, previous_boundary_attesters.iter())
+        .ok_or(EpochProcessingError::ArithmeticOverflow)?;
+
+    /*
+     * Determine justification.
+     *
+     * An epoch is justified if two-thirds of the total active balance attested to its boundary
+     * block. The `justified_slot_bitfield` records the justification of each of the most recent
+     * epochs, with the lowest bit being the current epoch.
+     */
+    let previous_justified_slot = state.justified_slot;
+    let mut justified_slot = state.justified_slot;
+    let mut justified_slot_bitfield = state.justified_slot_bitfield << 1;
+    if is_supermajority(previous_boundary_balance, total_balance)
+        .ok_or(EpochProcessingError::ArithmeticOverflow)?
+    {
+        justified_slot_bitfield |= 2;
+        justified_slot = previous_epoch_start;
+    }
+    if is_supermajority(this_boundary_balance, total_balance)
+        .ok_or(EpochProcessingError::ArithmeticOverflow)?
+    {
+        justified_slot_bitfield |= 1;
+        justified_slot = this_epoch_start;
+    }
+
+    /*
+     * Determine finalization.
+     *
+     * The previously justified slot is finalized if it was justified some epochs ago and every
+     * epoch since (allowing the current epoch to be unjustified after three epochs) has also been
+     * justified.
+     */
+    let previous_justified_epochs_ago = |epochs: u64| {
+        epoch_length
+            .checked_mul(epochs)
+            .and_then(|distance| next_epoch_start.checked_sub(distance))
+            == Some(previous_justified_slot)
+    };
+    let mut finalized_slot = state.finalized_slot;
+    if (previous_justified_epochs_ago(2) && justified_slot_bitfield % 4 == 3)
+        || (previous_justified_epochs_ago(3) && justified_slot_bitfield % 8 == 7)
+        || (previous_justified_epochs_ago(4) && [14, 15].contains(&(justified_slot_bitfield % 16)))
+    {
+        finalized_slot = max(finalized_slot, previous_justified_slot);
+    }
+
+    /*
+     * Update the crosslink for any shard where two-thirds of the balance of a committee attested
+     * to the same shard block. Later committees take precedence.
+     */
+    let mut latest_crosslinks = state.latest_crosslinks.clone();
+    let mut crosslink_votes: Vec<_> = crosslink_votes.into_iter().collect();
+    crosslink_votes.sort_by_key(|((slot, shard, _), _)| (*slot, *shard));
+    for ((_, shard, shard_block_hash), (voters, shard_and_committee)) in crosslink_votes {
+        let voted_balance = active_balance(validators, voters.iter())
+            .ok_or(EpochProcessingError::ArithmeticOverflow)?;
+        let committee_balance = active_balance(validators, shard_and_committee.committee.iter())
+            .ok_or(EpochProcessingError::ArithmeticOverflow)?;
+        if is_supermajority(voted_balance, committee_balance)
+            .ok_or(EpochProcessingError::ArithmeticOverflow)?
+        {
+            if let Some(crosslink) = latest_crosslinks.get_mut(shard as usize) {
+                *crosslink = CrosslinkRecord {
+                    slot: next_epoch_start,
+                    shard_block_hash,
+                };
+            }
+        }
+    }
+
+    /*
+     * Apply rewards and penalties.
+     *
+     * Active validators who attested to the boundary of the previous epoch are rewarded in
+     * proportion to the total participation, all other active validators are penalized.
+     */
+    let mut validator_registry = state.validator_registry.clone();
+    if previous_epoch_start < this_epoch_start && total_balance > 0 {
+        for (i, validator) in validator_registry.iter_mut().enumerate() {
+            if !validator.status_is(ValidatorStatus::Active) {
+                continue;
+            }
+            let base_reward = validator.balance / spec.base_reward_quotient;
+            if previous_boundary_attesters.contains(&i) {
+                let reward = u128::from(base_reward) * u128::from(previous_boundary_balance)
+                    / u128::from(total_balance);
+                validator.balance = validator
+                    .balance
+                    .checked_add(reward as u64)
+                    .ok_or(EpochProcessingError::ArithmeticOverflow)?;
+            } else {
+                validator.balance = validator.balance.saturating_sub(base_reward);
+            }
+        }
+    }
+
+    /*
+     * Update the validator registry if there has been a finalization and every shard has been
+     * crosslinked since the last change.
+     */
+    let mut validator_registry_latest_change_slot = state.validator_registry_latest_change_slot;
+    let mut validator_registry_delta_chain_tip = state.validator_registry_delta_chain_tip;
+    let all_shards_crosslinked = latest_crosslinks
+        .iter()
+        .all(|crosslink| crosslink.slot > validator_registry_latest_change_slot);
+    if finalized_slot > validator_registry_latest_change_slot && all_shards_crosslinked {
+        validator_registry_delta_chain_tip = update_validator_set(
+            &mut validator_registry,
+            validator_registry_delta_chain_tip,
+            next_epoch_start,
+            spec,
+        )?;
+        validator_registry_latest_change_slot = next_epoch_start;
+    }
+
+    /*
+     * Shuffle the validators for the next epoch, continuing from the shard after the last shard
+     * to be assigned a committee.
+     */
+    let shard_and_committee_for_slots = {
+        let crosslinking_shard_start = state
+            .shard_and_committee_for_slots
+            .last()
+            .and_then(|slot| slot.last())
+            .and_then(|shard_and_committee| {
+                (u64::from(shard_and_committee.shard) + 1).checked_rem(spec.shard_count)
+            })
+            .unwrap_or(0) as u16;
+        let mut new_shuffling = shard_and_committees_for_cycle(
+            &state.randao_mix,
+            &validator_registry,
+            crosslinking_shard_start,
+            spec,
+        )?;
+        let mut shard_and_committee_for_slots: Vec<Vec<ShardAndCommittee>> = state
+            .shard_and_committee_for_slots
+            .iter()
+            .skip(epoch_length as usize)
+            .cloned()
+            .collect();
+        shard_and_committee_for_slots.append(&mut new_shuffling);
+        shard_and_committee_for_slots
+    };
+
+    /*
+     * Attestations for the current epoch are kept, they are for the previous epoch when the next
+     * epoch is processed.
+     */
+    let latest_attestations: Vec<PendingAttestationRecord> = state
+        .latest_attestations
+        .iter()
+        .filter(|attestation| attestation.data.slot >= this_epoch_start)
+        .cloned()
+        .collect();
+
+    state.validator_registry = validator_registry;
+    state.validator_registry_latest_change_slot = validator_registry_latest_change_slot;
+    state.validator_registry_delta_chain_tip = validator_registry_delta_chain_tip;
+    state.shard_and_committee_for_slots = shard_and_committee_for_slots;
+    state.previous_justified_slot = previous_justified_slot;
+    state.justified_slot = justified_slot;
+    state.justified_slot_bitfield = justified_slot_bitfield;
+    state.finalized_slot = finalized_slot;
+    state.latest_crosslinks = latest_crosslinks;
+    state.latest_state_recalculation_slot = next_epoch_start;
+    state.latest_attestations = latest_attestations;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::block_processing::per_block_processing;
+    use super::super::slot_processing::per_slot_processing;
+    use super::*;
+    use test_utils::{test_attestations, test_block, test_state};
+    use types::PendingAttestationRecord;
+
+    /// Returns a state at slot 7, the last slot of the epoch beginning at slot 4.
+    fn second_epoch_state(spec: &ChainSpec) -> BeaconState {
+        let mut state = test_state(4, spec);
+        state.slot = 7;
+        state.latest_state_recalculation_slot = 4;
+        state
+    }
+
+    /// Returns the records of `test_attestations` for each slot in `slots`.
+    fn pending_attestations<I>(
+        state: &BeaconState,
+        slots: I,
+        spec: &ChainSpec,
+    ) -> Vec<PendingAttestationRecord>
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        slots
+            .into_iter()
+            .flat_map(|slot| test_attestations(state, slot, spec))
+            .map(|attestation| PendingAttestationRecord {
+                data: attestation.data,
+                participation_bitfield: attestation.participation_bitfield,
+                custody_bitfield: attestation.custody_bitfield,
+                slot_included: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_per_epoch_processing_not_at_boundary() {
+        let spec = ChainSpec::minimal();
+        let mut state = second_epoch_state(&spec);
+        state.slot = 8;
+        let original = state.clone();
+
+        assert_eq!(
+            per_epoch_processing(&mut state, &spec),
+            Err(EpochProcessingError::NotAtEpochBoundary)
+        );
+        assert_eq!(state, original);
+    }
+
+    #[test]
+    fn test_per_epoch_processing_justifies_and_crosslinks() {
+        let spec = ChainSpec::minimal();
+        let mut state = second_epoch_state(&spec);
+        state.latest_attestations = pending_attestations(&state, 0..8, &spec);
+        let original = state.clone();
+
+        per_epoch_processing(&mut state, &spec).unwrap();
+
+        assert_eq!(state.latest_state_recalculation_slot, 8);
+        assert_eq!(state.previous_justified_slot, 0);
+        assert_eq!(state.justified_slot, 4);
+        assert_eq!(state.justified_slot_bitfield, 0b11);
+        assert_eq!(state.finalized_slot, 0);
+        for crosslink in &state.latest_crosslinks {
+            assert_eq!(
+                *crosslink,
+                CrosslinkRecord {
+                    slot: 8,
+                    shard_block_hash: Hash256::from("shard_block".as_bytes()),
+                }
+            );
+        }
+
+        /*
+         * Only the attestations for the current epoch are kept.
+         */
+        assert_eq!(
+            state.latest_attestations,
+            pending_attestations(&original, 4..8, &spec)
+        );
+
+        /*
+         * The committees for the current epoch become those for the previous epoch.
+         */
+        assert_eq!(
+            state.shard_and_committee_for_slots.len(),
+            original.shard_and_committee_for_slots.len()
+        );
+        assert_eq!(
+            state.shard_and_committee_for_slots[0..4],
+            original.shard_and_committee_for_slots[4..8]
+        );
+
+        /*
+         * All validators attested, so all validators should be rewarded.
+         */
+        for validator in &state.validator_registry {
+            assert!(validator.balance > spec.max_deposit_gwei().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_per_epoch_processing_without_supermajority() {
+        let spec = ChainSpec::minimal();
+        let mut state = second_epoch_state(&spec);
+        state.justified_slot_bitfield = 0b1;
+        state.latest_attestations = pending_attestations(&state, 0..8, &spec);
+        for attestation in &mut state.latest_attestations {
+            attestation.participation_bitfield.set(2, false);
+            attestation.participation_bitfield.set(3, false);
+        }
+
+        per_epoch_processing(&mut state, &spec).unwrap();
+
+        assert_eq!(state.justified_slot, 0);
+        assert_eq!(state.justified_slot_bitfield, 0b10);
+        assert_eq!(state.finalized_slot, 0);
+        assert_eq!(state.latest_crosslinks[0], CrosslinkRecord::zero());
+
+        /*
+         * Participants are rewarded, absent validators are penalized.
+         */
+        assert!(state.validator_registry[0].balance > spec.max_deposit_gwei().unwrap());
+        assert!(state.validator_registry[1].balance > spec.max_deposit_gwei().unwrap());
+        assert!(state.validator_registry[2].balance < spec.max_deposit_gwei().unwrap());
+        assert!(state.validator_registry[3].balance < spec.max_deposit_gwei().unwrap());
+    }
+
+    #[test]
+    fn test_per_epoch_processing_ignores_wrong_epoch_boundary() {
+        let spec = ChainSpec::minimal();
+        let mut state = second_epoch_state(&spec);
+        state.latest_attestations = pending_attestations(&state, 4..8, &spec);
+        for attestation in &mut state.latest_attestations {
+            attestation.data.epoch_boundary_hash = Hash256::from("other_fork".as_bytes());
+        }
+
+        per_epoch_processing(&mut state, &spec).unwrap();
+
+        assert_eq!(state.justified_slot_bitfield, 0);
+        assert_eq!(state.justified_slot, 0);
+    }
+
+    #[test]
+    fn test_per_epoch_processing_finalizes() {
+        let spec = ChainSpec::minimal();
+        let mut state = second_epoch_state(&spec);
+        state.slot = 11;
+        state.latest_state_recalculation_slot = 8;
+        state.justified_slot = 4;
+        state.justified_slot_bitfield = 0b11;
+        state.latest_attestations = pending_attestations(&state, 4..12, &spec);
+
+        per_epoch_processing(&mut state, &spec).unwrap();
+
+        assert_eq!(state.previous_justified_slot, 4);
+        assert_eq!(state.justified_slot, 8);
+        assert_eq!(state.justified_slot_bitfield, 0b111);
+        assert_eq!(state.finalized_slot, 4);
+
+        /*
+         * Every shard was crosslinked after a finalization, so the registry is updated.
+         */
+        assert_eq!(state.validator_registry_latest_change_slot, 12);
+    }
+
+    #[test]
+    fn test_slot_then_block_then_epoch_processing_justifies_and_finalizes_each_epoch() {
+        let spec = ChainSpec::minimal();
+        let mut state = test_state(8, &spec);
+        let mut latest_block_hash = Hash256::from("genesis".as_bytes());
+
+        /*
+         * Each slot is processed, then its block, then (at the last slot of an epoch) the epoch.
+         */
+        for slot in 1..=spec.epoch_length * 4 {
+            per_slot_processing(&mut state, &latest_block_hash).unwrap();
+
+            let block = test_block(&state, test_attestations(&state, slot - 1, &spec), &spec);
+            per_block_processing(&mut state, &block, &spec).unwrap();
+            latest_block_hash = Hash256::from(slot);
+
+            let next_epoch_start = slot + 1;
+            if next_epoch_start % spec.epoch_length == 0 {
+                per_epoch_processing(&mut state, &spec).unwrap();
+
+                assert_eq!(state.latest_state_recalculation_slot, next_epoch_start);
+                assert_eq!(state.justified_slot, next_epoch_start - spec.epoch_length);
+                assert_eq!(
+                    state.finalized_slot,
+                    next_epoch_start.saturating_sub(spec.epoch_length * 2)
+                );
+            }
+        }
+        assert_eq!(state.justified_slot_bitfield, 0b1111);
+    }
+}
@@ -0,0 +1,169 @@
+use hashing::canonical_hash;
+use spec::ChainSpec;
+use types::{
+    BeaconBlock, BeaconState, Bitfield, Hash256, ShardAndCommittee, ValidatorRecord,
+    ValidatorStatus,
+};
+
+/// Returns the `ShardAndCommittee` for some shard at some index into the
+/// `shard_and_committee_for_slots`, if any.
+pub fn committee_for_shard(
+    shard_and_committee_for_slots: &[Vec<ShardAndCommittee>],
+    index: u64,
+    shard: u64,
+) -> Option<&ShardAndCommittee> {
+    shard_and_committee_for_slots
+        .get(index as usize)?
+        .iter()
+        .find(|shard_and_committee| u64::from(shard_and_committee.shard) == shard)
+}
+
+/// Returns the `ShardAndCommittee` for some shard at some slot of a `BeaconState`, if any.
+///
+/// The `shard_and_committee_for_slots` of a state begin one epoch prior to its
+/// `latest_state_recalculation_slot` (or at slot zero, for the genesis state).
+pub fn state_committee_for_shard<'a>(
+    state: &'a BeaconState,
+    slot: u64,
+    shard: u64,
+    spec: &ChainSpec,
+) -> Option<&'a ShardAndCommittee> {
+    let earliest_slot = state
+        .latest_state_recalculation_slot
+        .saturating_sub(spec.epoch_length);
+    let index = slot.checked_sub(earliest_slot)?;
+    committee_for_shard(&state.shard_and_committee_for_slots, index, shard)
+}
+
+/// Returns the index of the validator who is to propose the block at some slot of a
+/// `BeaconState`, if any.
+pub fn beacon_proposer_index(state: &BeaconState, slot: u64, spec: &ChainSpec) -> Option<usize> {
+    proposer_index(
+        &state.shard_and_committee_for_slots,
+        state.latest_state_recalculation_slot,
+        slot,
+        spec,
+    )
+}
+
+/// Returns the index of the validator who is to propose the block at some slot, if any, where
+/// the `shard_and_committee_for_slots` begin one epoch prior to `latest_state_recalculation_slot`
+/// (or at slot zero).
+///
+/// The proposer is chosen from the first committee of the slot, rotating with the slot.
+pub fn proposer_index(
+    shard_and_committee_for_slots: &[Vec<ShardAndCommittee>],
+    latest_state_recalculation_slot: u64,
+    slot: u64,
+    spec: &ChainSpec,
+) -> Option<usize> {
+    let earliest_slot = latest_state_recalculation_slot.saturating_sub(spec.epoch_length);
+    let index = slot.checked_sub(earliest_slot)?;
+    let committee = &shard_and_committee_for_slots
+        .get(index as usize)?
+        .first()?
+        .committee;
+    let position = (slot as usize).checked_rem(committee.len())?;
+    committee.get(position).cloned()
+}
+
+/// Returns the greatest number of times that the RANDAO reveal of a block at `slot` may need to
+/// be hashed to reach the `randao_commitment` of its proposer, which last changed at
+/// `randao_last_change`.
+///
+/// One layer may be revealed for the block, plus one for every `randao_slots_per_layer` slots
+/// since the last change.
+pub fn max_randao_layers(slot: u64, randao_last_change: u64, spec: &ChainSpec) -> u64 {
+    slot.saturating_sub(randao_last_change)
+        .checked_div(spec.randao_slots_per_layer)
+        .unwrap_or(0)
+        .saturating_add(1)
+}
+
+/// Returns `true` if `randao_reveal` reaches the `randao_commitment` of the `proposer` of a block
+/// at `slot` when hashed no more than the number of layers allowed since the commitment last
+/// changed.
+pub fn verify_randao_reveal(
+    proposer: &ValidatorRecord,
+    randao_reveal: &Hash256,
+    slot: u64,
+    spec: &ChainSpec,
+) -> bool {
+    let mut layer = *randao_reveal;
+    for _ in 0..max_randao_layers(slot, proposer.randao_last_change, spec) {
+        layer = Hash256::from(&canonical_hash(&layer)[..]);
+        if layer == proposer.randao_commitment {
+            return true;
+        }
+    }
+    false
+}
+
+/// Replace the `randao_commitment` of the proposer with the reveal of their block.
+pub fn update_randao_commitment(proposer: &mut ValidatorRecord, block: &BeaconBlock) {
+    proposer.randao_commitment = block.randao_reveal;
+    proposer.randao_last_change = block.slot;
+}
+
+/// Returns the hash of the latest block at or prior to `slot`, if `slot` is prior to the slot of
+/// the `state` and recent enough to be held in its `latest_block_hashes`.
+pub fn block_hash_at_slot(state: &BeaconState, slot: u64) -> Option<&Hash256> {
+    let distance = state.slot.checked_sub(slot)?;
+    if distance == 0 {
+        return None;
+    }
+    let index = (state.latest_block_hashes.len() as u64).checked_sub(distance)?;
+    state.latest_block_hashes.get(index as usize)
+}
+
+/// Returns the indices of the members of `committee` who are marked as participating in
+/// `participation_bitfield`.
+pub fn attesters(committee: &[usize], participation_bitfield: &Bitfield) -> Vec<usize> {
+    committee
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| participation_bitfield.get(*i).unwrap_or(false))
+        .map(|(_, validator_index)| *validator_index)
+        .collect()
+}
+
+/// Returns the sum of the balances of the active validators in `indices`, or `None` if the sum
+/// overflows.
+///
+/// Indices which do not refer to a validator are ignored.
+pub fn active_balance<'a, I>(validators: &[ValidatorRecord], indices: I) -> Option<u64>
+where
+    I: IntoIterator<Item = &'a usize>,
+{
+    let mut balance: u64 = 0;
+    for i in indices {
+        if let Some(validator) = validators.get(*i) {
+            if validator.status_is(ValidatorStatus::Active) {
+                balance = balance.checked_add(validator.balance)?;
+            }
+        }
+    }
+    Some(balance)
+}
+
+/// Returns the sum of the balances of all active validators, or `None` if the sum overflows.
+pub fn total_active_balance(validators: &[ValidatorRecord]) -> Option<u64> {
+    let mut balance: u64 = 0;
+    for validator in validators {
+        if validator.status_is(ValidatorStatus::Active) {
+            balance = balance.checked_add(validator.balance)?;
+        }
+    }
+    Some(balance)
+}
+
+/// Returns `true` if `voted_balance` is at least two-thirds of a non-zero `total_balance`, or
+/// `None` if the comparison overflows.
+pub fn is_supermajority(voted_balance: u64, total_balance: u64) -> Option<bool> {
+    if total_balance == 0 {
+        return Some(false);
+    }
+    let voted = voted_balance.checked_mul(3)?;
+    let total = total_balance.checked_mul(2)?;
+    Some(voted >= total)
+}
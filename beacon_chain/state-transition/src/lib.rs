@@ -1,14 +1,70 @@
+extern crate bls;
+extern crate hashing;
+extern crate spec;
+extern crate ssz;
 extern crate types;
-
-use types::{ActiveState, BeaconBlock, Hash256};
+extern crate validator_change;
+#[cfg(test)]
+extern crate validator_client;
+extern crate validator_shuffling;
+
+mod block_processing;
+mod epoch_processing;
+mod helpers;
+mod recalculation;
+mod slot_processing;
+mod special_processing;
+#[cfg(test)]
+mod test_utils;
+mod transition;
+
+use helpers::{proposer_index, update_randao_commitment, verify_randao_reveal};
+use spec::ChainSpec;
+use types::{ActiveState, BeaconBlock, CrystallizedState, Hash256};
+use validator_change::UpdateValidatorSetError;
+use validator_shuffling::ValidatorAssignmentError;
+
+pub use block_processing::{per_block_processing, BlockProcessingError};
+pub use epoch_processing::{per_epoch_processing, EpochProcessingError};
+pub use recalculation::recalculate_crystallized_state;
+pub use slot_processing::{per_slot_processing, SlotProcessingError};
+pub use special_processing::{process_specials, SpecialProcessingError};
+pub use transition::{transition_state, TransitionError};
 
 #[derive(Debug, PartialEq)]
 pub enum StateTransitionError {
     BlockSlotBeforeRecalcSlot,
     InvalidParentHashes,
+    ArithmeticOverflow,
+    ValidatorSetUpdateError(UpdateValidatorSetError),
+    ValidatorAssignmentError(ValidatorAssignmentError),
+    /// The crystallized state has no proposer for the slot of the block.
+    NoBlockProposer,
+    /// The `randao_reveal` does not hash to the `randao_commitment` of the proposer within the
+    /// number of layers allowed.
+    InvalidRandaoReveal,
+    SpecialProcessingError(SpecialProcessingError),
     DBError(String),
 }
 
+impl From<UpdateValidatorSetError> for StateTransitionError {
+    fn from(e: UpdateValidatorSetError) -> StateTransitionError {
+        StateTransitionError::ValidatorSetUpdateError(e)
+    }
+}
+
+impl From<ValidatorAssignmentError> for StateTransitionError {
+    fn from(e: ValidatorAssignmentError) -> StateTransitionError {
+        StateTransitionError::ValidatorAssignmentError(e)
+    }
+}
+
+impl From<SpecialProcessingError> for StateTransitionError {
+    fn from(e: SpecialProcessingError) -> StateTransitionError {
+        StateTransitionError::SpecialProcessingError(e)
+    }
+}
+
 pub fn extend_active_state(
     act_state: &ActiveState,
     block: &BeaconBlock,
@@ -60,6 +116,50 @@ pub fn extend_active_state(
     })
 }
 
+/// Apply `block` to the validators of `cry_state`, which must assign the proposer of the block
+/// (i.e., it must have been recalculated up to the slot of the block).
+///
+/// The `randao_reveal` of the block is verified against the commitment of the proposer and
+/// becomes their new commitment. The specials of the block are then applied, so that a randao
+/// change by the proposer takes precedence.
+pub fn update_crystallized_state(
+    cry_state: &CrystallizedState,
+    block: &BeaconBlock,
+    spec: &ChainSpec,
+) -> Result<CrystallizedState, StateTransitionError> {
+    let proposer = proposer_index(
+        &cry_state.shard_and_committee_for_slots,
+        cry_state.last_state_recalculation_slot,
+        block.slot,
+        spec,
+    )
+    .ok_or(StateTransitionError::NoBlockProposer)?;
+
+    let mut validators = cry_state.validators.clone();
+    {
+        let validator = validators
+            .get_mut(proposer)
+            .ok_or(StateTransitionError::NoBlockProposer)?;
+        if !verify_randao_reveal(validator, &block.randao_reveal, block.slot, spec) {
+            return Err(StateTransitionError::InvalidRandaoReveal);
+        }
+        update_randao_commitment(validator, block);
+    }
+
+    let validators = process_specials(
+        validators,
+        &block.specials,
+        block.slot,
+        proposer,
+        &cry_state.fork_data(),
+        spec,
+    )?;
+    Ok(CrystallizedState {
+        validators,
+        ..cry_state.clone()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
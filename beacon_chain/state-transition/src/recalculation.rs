@@ -0,0 +1,435 @@
+use super::helpers::{
+    active_balance, attesters, committee_for_shard, is_supermajority, total_active_balance,
+};
+use super::StateTransitionError;
+use spec::ChainSpec;
+use std::cmp::max;
+use std::collections::{HashMap, HashSet};
+use types::{
+    ActiveState, Attestation, CrosslinkRecord, CrystallizedState, Hash256, ShardAndCommittee,
+    ValidatorStatus,
+};
+use validator_change::update_validator_set;
+use validator_shuffling::shard_and_committees_for_cycle;
+
+/// Perform a crystallized state recalculation, advancing `last_state_recalculation_slot` by one
+/// cycle.
+///
+/// The pending attestations in the active state which reference the cycle prior to the
+/// `last_state_recalculation_slot` are processed to determine justification, finalization,
+/// crosslinks and validator rewards/penalties. Those attestations are then removed from the
+/// returned active state.
+///
+/// The `shard_and_committee_for_slots` of the `cry_state` are expected to begin at
+/// `last_state_recalculation_slot - cycle_length` (saturating at zero) and span two cycles.
+pub fn recalculate_crystallized_state(
+    cry_state: &CrystallizedState,
+    act_state: &ActiveState,
+    spec: &ChainSpec,
+) -> Result<(CrystallizedState, ActiveState), StateTransitionError> {
+    let cycle_length = spec.epoch_length;
+    let last_state_recalculation_slot = cry_state.last_state_recalculation_slot;
+    let new_state_recalculation_slot = last_state_recalculation_slot
+        .checked_add(cycle_length)
+        .ok_or(StateTransitionError::ArithmeticOverflow)?;
+    let earliest_slot = last_state_recalculation_slot.saturating_sub(cycle_length);
+
+    /*
+     * Split the pending attestations into those which should be processed during this
+     * recalculation and those which must wait until the next.
+     */
+    let (processed_attestations, pending_attestations): (Vec<Attestation>, Vec<Attestation>) =
+        act_state
+            .pending_attestations
+            .iter()
+            .cloned()
+            .partition(|a| a.data.slot < last_state_recalculation_slot);
+
+    /*
+     * Tally the validators who attested to each slot and to each shard block.
+     */
+    let mut slot_voters: HashMap<u64, HashSet<usize>> = HashMap::new();
+    let mut crosslink_votes: HashMap<(u16, Hash256), (HashSet<usize>, u64)> = HashMap::new();
+    let mut participants: HashSet<usize> = HashSet::new();
+    for attestation in &processed_attestations {
+        let data = &attestation.data;
+        if data.slot < earliest_slot {
+            continue;
+        }
+        let shard_and_committee = match committee_for_shard(
+            &cry_state.shard_and_committee_for_slots,
+            data.slot - earliest_slot,
+            data.shard,
+        ) {
+            Some(shard_and_committee) => shard_and_committee,
+            None => continue,
+        };
+
+        let attesters = attesters(
+            &shard_and_committee.committee,
+            &attestation.participation_bitfield,
+        );
+
+        let committee_balance =
+            active_balance(&cry_state.validators, shard_and_committee.committee.iter())
+                .ok_or(StateTransitionError::ArithmeticOverflow)?;
+        let crosslink_key = (shard_and_committee.shard, data.shard_block_hash);
+        let (crosslink_voters, _) = crosslink_votes
+            .entry(crosslink_key)
+            .or_insert_with(|| (HashSet::new(), committee_balance));
+
+        for validator_index in attesters {
+            slot_voters
+                .entry(data.slot)
+                .or_insert_with(HashSet::new)
+                .insert(validator_index);
+            crosslink_voters.insert(validator_index);
+            participants.insert(validator_index);
+        }
+    }
+
+    let total_balance = total_active_balance(&cry_state.validators)
+        .ok_or(StateTransitionError::ArithmeticOverflow)?;
+
+    /*
+     * Determine justification and finalization.
+     *
+     * A slot is justified if two-thirds of the total active balance attested to it. If there is
+     * an unbroken streak of justified slots longer than a cycle, the slot at the start of that
+     * streak is finalized.
+     */
+    let mut last_justified_slot = cry_state.last_justified_slot;
+    let mut last_finalized_slot = cry_state.last_finalized_slot;
+    let mut justified_streak = cry_state.justified_streak;
+    for slot in earliest_slot..last_state_recalculation_slot {
+        let voted_balance = match slot_voters.get(&slot) {
+            Some(voters) => active_balance(&cry_state.validators, voters.iter())
+                .ok_or(StateTransitionError::ArithmeticOverflow)?,
+            None => 0,
+        };
+        if is_supermajority(voted_balance, total_balance)
+            .ok_or(StateTransitionError::ArithmeticOverflow)?
+        {
+            last_justified_slot = max(last_justified_slot, slot);
+            justified_streak += 1;
+        } else {
+            justified_streak = 0;
+        }
+        if justified_streak > cycle_length {
+            last_finalized_slot = max(last_finalized_slot, slot.saturating_sub(cycle_length + 1));
+        }
+    }
+
+    /*
+     * Update the crosslink for any shard where two-thirds of the committee balance attested to
+     * the same shard block.
+     */
+    let mut crosslinks = cry_state.crosslinks.clone();
+    for ((shard, shard_block_hash), (voters, committee_balance)) in &crosslink_votes {
+        let voted_balance = active_balance(&cry_state.validators, voters.iter())
+            .ok_or(StateTransitionError::ArithmeticOverflow)?;
+        if is_supermajority(voted_balance, *committee_balance)
+            .ok_or(StateTransitionError::ArithmeticOverflow)?
+        {
+            if let Some(crosslink) = crosslinks.get_mut(*shard as usize) {
+                *crosslink = CrosslinkRecord {
+                    slot: new_state_recalculation_slot,
+                    shard_block_hash: *shard_block_hash,
+                };
+            }
+        }
+    }
+
+    /*
+     * Apply rewards and penalties.
+     *
+     * Active validators who attested during the previous cycle are rewarded in proportion to the
+     * total participation, all other active validators are penalized.
+     */
+    let mut validators = cry_state.validators.clone();
+    if earliest_slot < last_state_recalculation_slot && total_balance > 0 {
+        let participated_balance = active_balance(&validators, participants.iter())
+            .ok_or(StateTransitionError::ArithmeticOverflow)?;
+        for (i, validator) in validators.iter_mut().enumerate() {
+            if !validator.status_is(ValidatorStatus::Active) {
+                continue;
+            }
+            let base_reward = validator.balance / spec.base_reward_quotient;
+            if participants.contains(&i) {
+                let reward = u128::from(base_reward) * u128::from(participated_balance)
+                    / u128::from(total_balance);
+                validator.balance = validator
+                    .balance
+                    .checked_add(reward as u64)
+                    .ok_or(StateTransitionError::ArithmeticOverflow)?;
+            } else {
+                validator.balance = validator.balance.saturating_sub(base_reward);
+            }
+        }
+    }
+
+    /*
+     * Change the validator set if there has been a finalization and every shard has been
+     * crosslinked since the last change.
+     */
+    let mut validator_set_change_slot = cry_state.validator_set_change_slot;
+    let mut validator_set_delta_hash_chain = cry_state.validator_set_delta_hash_chain;
+    let all_shards_crosslinked = crosslinks
+        .iter()
+        .all(|crosslink| crosslink.slot > validator_set_change_slot);
+    if last_finalized_slot > validator_set_change_slot && all_shards_crosslinked {
+        validator_set_delta_hash_chain = update_validator_set(
+            &mut validators,
+            validator_set_delta_hash_chain,
+            new_state_recalculation_slot,
+            spec,
+        )?;
+        validator_set_change_slot = new_state_recalculation_slot;
+    }
+
+    /*
+     * Shuffle the validators for the next cycle, continuing from the shard after the last shard
+     * to be assigned a committee.
+     */
+    let shard_and_committee_for_slots = {
+        let crosslinking_shard_start = cry_state
+            .shard_and_committee_for_slots
+            .last()
+            .and_then(|slot| slot.last())
+            .and_then(|shard_and_committee| {
+                (u64::from(shard_and_committee.shard) + 1).checked_rem(spec.shard_count)
+            })
+            .unwrap_or(0) as u16;
+        let mut new_shuffling = shard_and_committees_for_cycle(
+            &act_state.randao_mix,
+            &validators,
+            crosslinking_shard_start,
+            spec,
+        )?;
+        let mut shard_and_committee_for_slots: Vec<Vec<ShardAndCommittee>> = cry_state
+            .shard_and_committee_for_slots
+            .iter()
+            .skip(cycle_length as usize)
+            .cloned()
+            .collect();
+        shard_and_committee_for_slots.append(&mut new_shuffling);
+        shard_and_committee_for_slots
+    };
+
+    let new_cry_state = CrystallizedState {
+        validator_set_change_slot,
+        validators,
+        crosslinks,
+        last_state_recalculation_slot: new_state_recalculation_slot,
+        last_finalized_slot,
+        last_justified_slot,
+        previous_justified_slot: cry_state.last_justified_slot,
+        justified_streak,
+        shard_and_committee_for_slots,
+        deposits_penalized_in_period: cry_state.deposits_penalized_in_period.clone(),
+        validator_set_delta_hash_chain,
+        pre_fork_version: cry_state.pre_fork_version,
+        post_fork_version: cry_state.post_fork_version,
+        fork_slot_number: cry_state.fork_slot_number,
+    };
+
+    /*
+     * The pending specials were applied to the validators as their blocks were processed.
+     */
+    let new_act_state = ActiveState {
+        pending_attestations,
+        pending_specials: vec![],
+        recent_block_hashes: act_state.recent_block_hashes.clone(),
+        randao_mix: act_state.randao_mix,
+    };
+
+    Ok((new_cry_state, new_act_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{Bitfield, ValidatorRecord};
+
+    const VALIDATOR_BALANCE: u64 = 32_000_000_000;
+
+    fn test_spec() -> ChainSpec {
+        ChainSpec {
+            epoch_length: 2,
+            shard_count: 2,
+            target_committee_size: 2,
+            ..ChainSpec::foundation()
+        }
+    }
+
+    /// Returns a crystallized state with `n` active validators, all of which are members of a
+    /// single committee for shard 0 in each slot.
+    fn test_cry_state(
+        n: usize,
+        last_state_recalculation_slot: u64,
+        spec: &ChainSpec,
+    ) -> CrystallizedState {
+        let validators = (0..n)
+            .map(|_| {
+                let (mut validator, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+                validator.status = ValidatorStatus::Active;
+                validator.balance = VALIDATOR_BALANCE;
+                validator
+            })
+            .collect();
+        let shard_and_committee_for_slots = (0..spec.epoch_length * 2)
+            .map(|_| {
+                vec![ShardAndCommittee {
+                    shard: 0,
+                    committee: (0..n).collect(),
+                }]
+            })
+            .collect();
+        CrystallizedState {
+            validator_set_change_slot: 0,
+            validators,
+            crosslinks: vec![CrosslinkRecord::zero(); spec.shard_count as usize],
+            last_state_recalculation_slot,
+            last_finalized_slot: 0,
+            last_justified_slot: 0,
+            previous_justified_slot: 0,
+            justified_streak: 0,
+            shard_and_committee_for_slots,
+            deposits_penalized_in_period: vec![],
+            validator_set_delta_hash_chain: Hash256::zero(),
+            pre_fork_version: 0,
+            post_fork_version: 0,
+            fork_slot_number: 0,
+        }
+    }
+
+    fn test_act_state(pending_attestations: Vec<Attestation>) -> ActiveState {
+        ActiveState {
+            pending_attestations,
+            pending_specials: vec![],
+            recent_block_hashes: vec![Hash256::zero()],
+            randao_mix: Hash256::zero(),
+        }
+    }
+
+    /// Returns an attestation for shard 0 at `slot` where the first `participants` committee
+    /// members have participated.
+    fn test_attestation(slot: u64, participants: usize, committee_size: usize) -> Attestation {
+        let mut participation_bitfield = Bitfield::with_capacity(committee_size);
+        for i in 0..participants {
+            participation_bitfield.set(i, true);
+        }
+        let mut attestation = Attestation::zero();
+        attestation.data.slot = slot;
+        attestation.data.shard_block_hash = Hash256::from("shard_block".as_bytes());
+        attestation.participation_bitfield = participation_bitfield;
+        attestation
+    }
+
+    #[test]
+    fn test_recalculation_advances_cycle() {
+        let spec = test_spec();
+        let cry_state = test_cry_state(4, 0, &spec);
+        let act_state = test_act_state(vec![]);
+
+        let (new_cry_state, new_act_state) =
+            recalculate_crystallized_state(&cry_state, &act_state, &spec).unwrap();
+
+        assert_eq!(new_cry_state.last_state_recalculation_slot, 2);
+        assert_eq!(new_cry_state.shard_and_committee_for_slots.len(), 4);
+        assert_eq!(
+            new_cry_state.shard_and_committee_for_slots[0..2],
+            cry_state.shard_and_committee_for_slots[2..4]
+        );
+        /*
+         * No slots were processed, so no balances should have changed.
+         */
+        assert_eq!(new_cry_state.validators, cry_state.validators);
+        assert_eq!(new_act_state, act_state);
+    }
+
+    #[test]
+    fn test_recalculation_justifies_and_crosslinks() {
+        let spec = test_spec();
+        let cry_state = test_cry_state(4, 2, &spec);
+        let act_state = test_act_state(vec![
+            test_attestation(0, 4, 4),
+            test_attestation(1, 3, 4),
+            test_attestation(2, 4, 4),
+        ]);
+
+        let (new_cry_state, new_act_state) =
+            recalculate_crystallized_state(&cry_state, &act_state, &spec).unwrap();
+
+        assert_eq!(new_cry_state.last_state_recalculation_slot, 4);
+        assert_eq!(new_cry_state.last_justified_slot, 1);
+        assert_eq!(new_cry_state.justified_streak, 2);
+        assert_eq!(new_cry_state.last_finalized_slot, 0);
+        assert_eq!(
+            new_cry_state.crosslinks[0],
+            CrosslinkRecord {
+                slot: 4,
+                shard_block_hash: Hash256::from("shard_block".as_bytes()),
+            }
+        );
+        assert_eq!(new_cry_state.crosslinks[1], CrosslinkRecord::zero());
+
+        /*
+         * The attestation for the current cycle should remain pending.
+         */
+        assert_eq!(
+            new_act_state.pending_attestations,
+            vec![test_attestation(2, 4, 4)]
+        );
+
+        /*
+         * All validators participated, so all validators should be rewarded.
+         */
+        for validator in &new_cry_state.validators {
+            assert!(validator.balance > VALIDATOR_BALANCE);
+        }
+    }
+
+    #[test]
+    fn test_recalculation_without_supermajority() {
+        let spec = test_spec();
+        let cry_state = test_cry_state(4, 2, &spec);
+        let act_state = test_act_state(vec![test_attestation(0, 2, 4)]);
+
+        let (new_cry_state, _) =
+            recalculate_crystallized_state(&cry_state, &act_state, &spec).unwrap();
+
+        assert_eq!(new_cry_state.last_justified_slot, 0);
+        assert_eq!(new_cry_state.justified_streak, 0);
+        assert_eq!(new_cry_state.crosslinks[0], CrosslinkRecord::zero());
+
+        /*
+         * Participants are rewarded, absent validators are penalized.
+         */
+        assert!(new_cry_state.validators[0].balance > VALIDATOR_BALANCE);
+        assert!(new_cry_state.validators[1].balance > VALIDATOR_BALANCE);
+        assert!(new_cry_state.validators[2].balance < VALIDATOR_BALANCE);
+        assert!(new_cry_state.validators[3].balance < VALIDATOR_BALANCE);
+    }
+
+    #[test]
+    fn test_recalculation_finalizes() {
+        let spec = test_spec();
+        let mut cry_state = test_cry_state(4, 6, &spec);
+        cry_state.last_justified_slot = 3;
+        cry_state.justified_streak = 2;
+        let act_state = test_act_state(vec![test_attestation(4, 4, 4), test_attestation(5, 4, 4)]);
+
+        let (new_cry_state, _) =
+            recalculate_crystallized_state(&cry_state, &act_state, &spec).unwrap();
+
+        assert_eq!(new_cry_state.justified_streak, 4);
+        assert_eq!(new_cry_state.last_justified_slot, 5);
+        assert_eq!(new_cry_state.previous_justified_slot, 3);
+        /*
+         * A streak of more than one cycle finalizes the slot a cycle (plus one) prior.
+         */
+        assert_eq!(new_cry_state.last_finalized_slot, 2);
+        assert_eq!(new_cry_state.validator_set_change_slot, 0);
+    }
+}
@@ -0,0 +1,93 @@
+use types::{BeaconState, Hash256};
+
+/// Reasons why `per_slot_processing` could not advance a `BeaconState`.
+#[derive(Debug, PartialEq)]
+pub enum SlotProcessingError {
+    /// The state has no `latest_block_hashes` in which to record the previous block.
+    NoLatestBlockHashes,
+    SlotOverflow,
+}
+
+/// Advance the `state` to the next slot, recording the hash of the latest block prior to that
+/// slot.
+///
+/// The `previous_block_hash` is the hash of the block at the current slot of the `state`, or of
+/// the latest block before it if there was no block at that slot. The `latest_block_hashes` are
+/// a window over the most recent slots, so the earliest hash is dropped.
+///
+/// This must be called for every slot, including those without a block, prior to
+/// `per_block_processing` (see `transition_state`). The `state` is unchanged if an error is
+/// returned.
+pub fn per_slot_processing(
+    state: &mut BeaconState,
+    previous_block_hash: &Hash256,
+) -> Result<(), SlotProcessingError> {
+    if state.latest_block_hashes.is_empty() {
+        return Err(SlotProcessingError::NoLatestBlockHashes);
+    }
+    state.slot = state
+        .slot
+        .checked_add(1)
+        .ok_or(SlotProcessingError::SlotOverflow)?;
+
+    state.latest_block_hashes.rotate_left(1);
+    if let Some(latest) = state.latest_block_hashes.last_mut() {
+        *latest = *previous_block_hash;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spec::ChainSpec;
+    use test_utils::test_state;
+
+    #[test]
+    fn test_per_slot_processing() {
+        let spec = ChainSpec::minimal();
+        let mut state = test_state(4, &spec);
+        state.latest_block_hashes = vec![
+            Hash256::from("zero".as_bytes()),
+            Hash256::from("one".as_bytes()),
+            Hash256::from("two".as_bytes()),
+        ];
+
+        per_slot_processing(&mut state, &Hash256::from("three".as_bytes())).unwrap();
+
+        assert_eq!(state.slot, 1);
+        assert_eq!(
+            state.latest_block_hashes,
+            vec![
+                Hash256::from("one".as_bytes()),
+                Hash256::from("two".as_bytes()),
+                Hash256::from("three".as_bytes()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_per_slot_processing_errors() {
+        let spec = ChainSpec::minimal();
+        let mut state = test_state(4, &spec);
+        state.latest_block_hashes = vec![];
+        let original = state.clone();
+
+        assert_eq!(
+            per_slot_processing(&mut state, &Hash256::zero()),
+            Err(SlotProcessingError::NoLatestBlockHashes)
+        );
+        assert_eq!(state, original);
+
+        let mut state = test_state(4, &spec);
+        state.slot = u64::max_value();
+        let original = state.clone();
+
+        assert_eq!(
+            per_slot_processing(&mut state, &Hash256::zero()),
+            Err(SlotProcessingError::SlotOverflow)
+        );
+        assert_eq!(state, original);
+    }
+}
@@ -0,0 +1,603 @@
+use bls::AggregatePublicKey;
+use spec::ChainSpec;
+use ssz::DecodeError;
+use std::collections::HashSet;
+use types::{
+    AttestationData, CasperSlashingSpecial, ForkData, LogoutSpecial, RandaoChangeSpecial,
+    SlashableVote, SpecialRecord, SpecialRecordPayload, ValidatorRecord, ValidatorStatus,
+};
+
+/// Reasons why the specials of a block could not be applied to a `BeaconState`.
+#[derive(Debug, PartialEq)]
+pub enum SpecialProcessingError {
+    /// The `data` of a special could not be decoded as the payload of its kind.
+    InvalidPayload(DecodeError),
+    TooManyLogouts,
+    TooManyCasperSlashings,
+    /// A special refers to a validator which is not in the validator registry.
+    UnknownValidator,
+    /// Only active validators may log out.
+    LogoutValidatorNotActive,
+    InvalidLogoutSignature,
+    TooManyCasperVotes,
+    /// Both votes of a slashing are for the same data.
+    IdenticalVotes,
+    /// The votes of a slashing are not for the same slot, nor does one surround the other.
+    VotesNotSlashable,
+    /// No validator who signed both votes of a slashing remains to be penalized.
+    NoSlashableValidators,
+    InvalidVoteSignature,
+    /// A randao change was not for the proposer of the block.
+    RandaoChangeNotByProposer,
+    /// A randao change was not signed by the proposer of the block.
+    InvalidRandaoChangeSignature,
+    /// The fork version at the slot of the block (or of a vote) cannot be included in a signature
+    /// domain.
+    InvalidSignatureDomain,
+    BalanceOverflow,
+}
+
+impl From<DecodeError> for SpecialProcessingError {
+    fn from(e: DecodeError) -> SpecialProcessingError {
+        SpecialProcessingError::InvalidPayload(e)
+    }
+}
+
+/// Apply the `specials` of a block at `slot`, proposed by the validator at index `proposer`, to
+/// `validators`, returning the validator registry which results.
+///
+/// The `validators` are the registry as modified earlier in the block and `fork_data` is that of
+/// the state to which the block is applied. The specials are applied in order, each being checked
+/// against the registry as it has been modified by those before it (e.g., a validator may not log
+/// out twice in one block).
+pub fn process_specials(
+    mut validators: Vec<ValidatorRecord>,
+    specials: &[SpecialRecord],
+    slot: u64,
+    proposer: usize,
+    fork_data: &ForkData,
+    spec: &ChainSpec,
+) -> Result<Vec<ValidatorRecord>, SpecialProcessingError> {
+    let payloads = specials
+        .iter()
+        .map(|special| special.decode_payload())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut logouts: u64 = 0;
+    let mut casper_slashings: u64 = 0;
+    for payload in &payloads {
+        match payload {
+            SpecialRecordPayload::Logout(_) => logouts += 1,
+            SpecialRecordPayload::CasperSlashing(_) => casper_slashings += 1,
+            SpecialRecordPayload::RandaoChange(_) => (),
+        }
+    }
+    if logouts > spec.max_exits {
+        return Err(SpecialProcessingError::TooManyLogouts);
+    }
+    if casper_slashings > spec.max_casper_slashings {
+        return Err(SpecialProcessingError::TooManyCasperSlashings);
+    }
+
+    for payload in &payloads {
+        match payload {
+            SpecialRecordPayload::Logout(logout) => {
+                process_logout(&mut validators, logout, slot, fork_data, spec)?
+            }
+            SpecialRecordPayload::CasperSlashing(slashing) => {
+                process_casper_slashing(&mut validators, slashing, slot, proposer, fork_data, spec)?
+            }
+            SpecialRecordPayload::RandaoChange(change) => {
+                process_randao_change(&mut validators, change, slot, proposer, fork_data, spec)?
+            }
+        }
+    }
+    Ok(validators)
+}
+
+/// Mark an active validator as pending exit, if the logout is signed by that validator in the
+/// logout domain at `slot`.
+///
+/// The validator exits at the next change of the validator registry.
+fn process_logout(
+    validators: &mut [ValidatorRecord],
+    logout: &LogoutSpecial,
+    slot: u64,
+    fork_data: &ForkData,
+    spec: &ChainSpec,
+) -> Result<(), SpecialProcessingError> {
+    let validator = validators
+        .get_mut(logout.validator_index as usize)
+        .ok_or(SpecialProcessingError::UnknownValidator)?;
+    if !validator.status_is(ValidatorStatus::Active) {
+        return Err(SpecialProcessingError::LogoutValidatorNotActive);
+    }
+
+    let domain = fork_data
+        .domain(slot, spec.domain_logout)
+        .ok_or(SpecialProcessingError::InvalidSignatureDomain)?;
+    let message = LogoutSpecial::signing_message(domain);
+    if !logout.signature.verify(&message, &validator.pubkey) {
+        return Err(SpecialProcessingError::InvalidLogoutSignature);
+    }
+
+    validator.status = ValidatorStatus::PendingExit;
+    Ok(())
+}
+
+/// Penalize each validator who signed both of two slashable votes.
+///
+/// A fraction of the balance of each penalized validator is given to the proposer of the block,
+/// as the whistleblower.
+fn process_casper_slashing(
+    validators: &mut [ValidatorRecord],
+    slashing: &CasperSlashingSpecial,
+    slot: u64,
+    whistleblower: usize,
+    fork_data: &ForkData,
+    spec: &ChainSpec,
+) -> Result<(), SpecialProcessingError> {
+    let (vote_1, vote_2) = (&slashing.vote_1, &slashing.vote_2);
+    if vote_1.aggregate_sig_indices.len() as u64 > spec.max_casper_votes
+        || vote_2.aggregate_sig_indices.len() as u64 > spec.max_casper_votes
+    {
+        return Err(SpecialProcessingError::TooManyCasperVotes);
+    }
+    if vote_1.data == vote_2.data {
+        return Err(SpecialProcessingError::IdenticalVotes);
+    }
+    if !is_slashable(&vote_1.data, &vote_2.data) {
+        return Err(SpecialProcessingError::VotesNotSlashable);
+    }
+    verify_vote_signature(validators, vote_1, fork_data, spec)?;
+    verify_vote_signature(validators, vote_2, fork_data, spec)?;
+
+    /*
+     * Validators who have already been penalized are not penalized again.
+     */
+    let vote_2_indices: HashSet<u64> = vote_2.aggregate_sig_indices.iter().cloned().collect();
+    let mut slashed: Vec<usize> = vote_1
+        .aggregate_sig_indices
+        .iter()
+        .filter(|i| vote_2_indices.contains(i))
+        .map(|i| *i as usize)
+        .filter(|i| !validators[*i].status_is(ValidatorStatus::Penalized))
+        .collect();
+    slashed.sort();
+    slashed.dedup();
+    if slashed.is_empty() {
+        return Err(SpecialProcessingError::NoSlashableValidators);
+    }
+
+    if whistleblower >= validators.len() {
+        return Err(SpecialProcessingError::UnknownValidator);
+    }
+    for i in slashed {
+        let reward = validators[i]
+            .balance
+            .checked_div(spec.whistleblower_reward_quotient)
+            .unwrap_or(0);
+        validators[i].balance -= reward;
+        validators[i].status = ValidatorStatus::Penalized;
+        validators[i].exit_slot = slot;
+        validators[whistleblower].balance = validators[whistleblower]
+            .balance
+            .checked_add(reward)
+            .ok_or(SpecialProcessingError::BalanceOverflow)?;
+    }
+    Ok(())
+}
+
+/// Returns `true` if a validator who voted for both `data_1` and `data_2` has violated a Casper
+/// slashing condition, i.e., the votes are for the same slot or one vote surrounds the other.
+fn is_slashable(data_1: &AttestationData, data_2: &AttestationData) -> bool {
+    let surrounds = |outer: &AttestationData, inner: &AttestationData| {
+        outer.justified_slot < inner.justified_slot && inner.slot < outer.slot
+    };
+    data_1.slot == data_2.slot || surrounds(data_1, data_2) || surrounds(data_2, data_1)
+}
+
+/// Verify that the aggregate signature of a vote was made by all of its validators, in the
+/// attestation domain at the slot of the vote.
+fn verify_vote_signature(
+    validators: &[ValidatorRecord],
+    vote: &SlashableVote,
+    fork_data: &ForkData,
+    spec: &ChainSpec,
+) -> Result<(), SpecialProcessingError> {
+    let domain = fork_data
+        .domain(vote.data.slot, spec.domain_attestation)
+        .ok_or(SpecialProcessingError::InvalidSignatureDomain)?;
+    let mut agg_pub_key = AggregatePublicKey::new();
+    for i in &vote.aggregate_sig_indices {
+        let validator = validators
+            .get(*i as usize)
+            .ok_or(SpecialProcessingError::UnknownValidator)?;
+        agg_pub_key.add(&validator.pubkey);
+    }
+    if !vote
+        .aggregate_sig
+        .verify(&vote.signing_message(domain), &agg_pub_key)
+    {
+        return Err(SpecialProcessingError::InvalidVoteSignature);
+    }
+    Ok(())
+}
+
+/// Replace the `randao_commitment` of the proposer of the block, if the change is signed by the
+/// proposer in the proposal domain at `slot`.
+fn process_randao_change(
+    validators: &mut [ValidatorRecord],
+    change: &RandaoChangeSpecial,
+    slot: u64,
+    proposer: usize,
+    fork_data: &ForkData,
+    spec: &ChainSpec,
+) -> Result<(), SpecialProcessingError> {
+    if change.proposer_index != proposer as u64 {
+        return Err(SpecialProcessingError::RandaoChangeNotByProposer);
+    }
+
+    let validator = validators
+        .get_mut(proposer)
+        .ok_or(SpecialProcessingError::UnknownValidator)?;
+    let domain = fork_data
+        .domain(slot, spec.domain_proposal)
+        .ok_or(SpecialProcessingError::InvalidSignatureDomain)?;
+    let message = change.signing_message(domain);
+    if !change.signature.verify(&message, &validator.pubkey) {
+        return Err(SpecialProcessingError::InvalidRandaoChangeSignature);
+    }
+
+    validator.randao_commitment = change.randao_commitment;
+    validator.randao_last_change = slot;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::helpers::beacon_proposer_index;
+    use super::*;
+    use bls::{AggregateSignature, Keypair, Signature};
+    use ssz::ssz_encode;
+    use test_utils::test_state_with_keypairs;
+    use types::{BeaconState, Hash256};
+
+    /// Apply `specials` to the registry of the `state`, as for a block at the slot of the `state`.
+    fn process_state_specials(
+        state: &BeaconState,
+        specials: &[SpecialRecord],
+        spec: &ChainSpec,
+    ) -> Result<Vec<ValidatorRecord>, SpecialProcessingError> {
+        let proposer = beacon_proposer_index(state, state.slot, spec).unwrap();
+        process_specials(
+            state.validator_registry.clone(),
+            specials,
+            state.slot,
+            proposer,
+            &state.fork_data,
+            spec,
+        )
+    }
+
+    fn logout(keypairs: &[Keypair], validator_index: u64, domain: u64) -> SpecialRecord {
+        let message = LogoutSpecial::signing_message(domain);
+        let payload = LogoutSpecial {
+            validator_index,
+            signature: Signature::new(&message, &keypairs[validator_index as usize].sk),
+        };
+        SpecialRecord::logout(&ssz_encode(&payload))
+    }
+
+    /// Returns a vote on `data` by the validators at `indices`, signed for the fork of the `state`.
+    fn vote(
+        state: &BeaconState,
+        keypairs: &[Keypair],
+        indices: &[u64],
+        data: AttestationData,
+        spec: &ChainSpec,
+    ) -> SlashableVote {
+        let mut vote = SlashableVote {
+            aggregate_sig_indices: indices.to_vec(),
+            data,
+            aggregate_sig: AggregateSignature::new(),
+        };
+        let domain = state
+            .fork_data
+            .domain(vote.data.slot, spec.domain_attestation)
+            .unwrap();
+        let message = vote.signing_message(domain);
+        for i in indices {
+            vote.aggregate_sig
+                .add(&Signature::new(&message, &keypairs[*i as usize].sk));
+        }
+        vote
+    }
+
+    fn casper_slashing(vote_1: SlashableVote, vote_2: SlashableVote) -> SpecialRecord {
+        SpecialRecord::casper_slashing(&ssz_encode(&CasperSlashingSpecial { vote_1, vote_2 }))
+    }
+
+    /// Returns two votes for the same slot by validators 0 and 1, and 1 and 2, respectively.
+    fn double_votes(
+        state: &BeaconState,
+        keypairs: &[Keypair],
+        spec: &ChainSpec,
+    ) -> (SlashableVote, SlashableVote) {
+        let mut data_1 = AttestationData::zero();
+        data_1.slot = 2;
+        let mut data_2 = data_1.clone();
+        data_2.shard_block_hash = Hash256::from("other".as_bytes());
+        (
+            vote(state, keypairs, &[0, 1], data_1, spec),
+            vote(state, keypairs, &[1, 2], data_2, spec),
+        )
+    }
+
+    /// Returns a randao change for `proposer_index`, signed by `signer` for a block at the slot of
+    /// the `state`.
+    fn randao_change(
+        state: &BeaconState,
+        proposer_index: u64,
+        signer: &Keypair,
+        spec: &ChainSpec,
+    ) -> SpecialRecord {
+        let mut payload = RandaoChangeSpecial {
+            proposer_index,
+            randao_commitment: Hash256::from("commitment".as_bytes()),
+            signature: Signature::empty_signature(),
+        };
+        let domain = state
+            .fork_data
+            .domain(state.slot, spec.domain_proposal)
+            .unwrap();
+        payload.signature = Signature::new(&payload.signing_message(domain), &signer.sk);
+        SpecialRecord::randao_change(&ssz_encode(&payload))
+    }
+
+    #[test]
+    fn test_process_logout() {
+        let spec = ChainSpec::minimal();
+        let (mut state, keypairs) = test_state_with_keypairs(4, &spec);
+        state.fork_data.post_fork_version = 1;
+        let domain = state.fork_data.domain(0, spec.domain_logout).unwrap();
+
+        let validators =
+            process_state_specials(&state, &[logout(&keypairs, 2, domain)], &spec).unwrap();
+
+        let mut expected = state.validator_registry.clone();
+        expected[2].status = ValidatorStatus::PendingExit;
+        assert_eq!(validators, expected);
+    }
+
+    #[test]
+    fn test_process_logout_invalid() {
+        let spec = ChainSpec::minimal();
+        let (mut state, keypairs) = test_state_with_keypairs(4, &spec);
+        state.fork_data.post_fork_version = 1;
+        let domain = state.fork_data.domain(0, spec.domain_logout).unwrap();
+
+        let check = |specials: &[SpecialRecord], error: SpecialProcessingError| {
+            assert_eq!(process_state_specials(&state, specials, &spec), Err(error));
+        };
+
+        /*
+         * A logout signed for the previous fork version, or in another domain, is invalid.
+         */
+        check(
+            &[logout(&keypairs, 2, spec.domain_logout)],
+            SpecialProcessingError::InvalidLogoutSignature,
+        );
+        check(
+            &[logout(
+                &keypairs,
+                2,
+                state.fork_data.domain(0, spec.domain_proposal).unwrap(),
+            )],
+            SpecialProcessingError::InvalidLogoutSignature,
+        );
+        check(
+            &[logout(&keypairs, 2, domain), logout(&keypairs, 2, domain)],
+            SpecialProcessingError::LogoutValidatorNotActive,
+        );
+
+        let mut special = logout(&keypairs, 2, domain);
+        special.data[7] = 4;
+        check(&[special], SpecialProcessingError::UnknownValidator);
+
+        let mut special = logout(&keypairs, 2, domain);
+        special.data[7] = 3;
+        check(&[special], SpecialProcessingError::InvalidLogoutSignature);
+
+        check(
+            &vec![logout(&keypairs, 2, domain); spec.max_exits as usize + 1],
+            SpecialProcessingError::TooManyLogouts,
+        );
+
+        /*
+         * No signature is valid if the fork version does not fit in a domain.
+         */
+        state.fork_data.post_fork_version = 1 << 32;
+        assert_eq!(
+            process_state_specials(&state, &[logout(&keypairs, 2, domain)], &spec),
+            Err(SpecialProcessingError::InvalidSignatureDomain)
+        );
+    }
+
+    #[test]
+    fn test_process_casper_slashing() {
+        let spec = ChainSpec::minimal();
+        let (mut state, keypairs) = test_state_with_keypairs(4, &spec);
+        state.slot = 3;
+        let (vote_1, vote_2) = double_votes(&state, &keypairs, &spec);
+
+        let validators =
+            process_state_specials(&state, &[casper_slashing(vote_1, vote_2)], &spec).unwrap();
+
+        /*
+         * Validator 1 signed both votes and validator 3 is the proposer at slot 3.
+         */
+        let balance = spec.max_deposit_gwei().unwrap();
+        let reward = balance / spec.whistleblower_reward_quotient;
+        let mut expected = state.validator_registry.clone();
+        expected[1].status = ValidatorStatus::Penalized;
+        expected[1].exit_slot = 3;
+        expected[1].balance = balance - reward;
+        expected[3].balance = balance + reward;
+        assert_eq!(validators, expected);
+    }
+
+    #[test]
+    fn test_process_casper_slashing_surround_vote() {
+        let spec = ChainSpec::minimal();
+        let (state, keypairs) = test_state_with_keypairs(4, &spec);
+        let mut outer = AttestationData::zero();
+        outer.justified_slot = 0;
+        outer.slot = 8;
+        let mut inner = AttestationData::zero();
+        inner.justified_slot = 4;
+        inner.slot = 6;
+
+        let special = casper_slashing(
+            vote(&state, &keypairs, &[0, 1], inner, &spec),
+            vote(&state, &keypairs, &[1], outer, &spec),
+        );
+        let validators = process_state_specials(&state, &[special], &spec).unwrap();
+
+        assert!(validators[1].status_is(ValidatorStatus::Penalized));
+        assert!(validators[0].status_is(ValidatorStatus::Active));
+    }
+
+    #[test]
+    fn test_process_casper_slashing_invalid() {
+        let spec = ChainSpec::minimal();
+        let (state, keypairs) = test_state_with_keypairs(4, &spec);
+        let (vote_1, vote_2) = double_votes(&state, &keypairs, &spec);
+
+        let check = |specials: &[SpecialRecord], error: SpecialProcessingError| {
+            assert_eq!(process_state_specials(&state, specials, &spec), Err(error));
+        };
+
+        check(
+            &[casper_slashing(vote_1.clone(), vote_1.clone())],
+            SpecialProcessingError::IdenticalVotes,
+        );
+
+        let mut later = vote_2.data.clone();
+        later.slot = 3;
+        check(
+            &[casper_slashing(
+                vote_1.clone(),
+                vote(&state, &keypairs, &[1, 2], later, &spec),
+            )],
+            SpecialProcessingError::VotesNotSlashable,
+        );
+
+        check(
+            &[casper_slashing(
+                vote_1.clone(),
+                vote(&state, &keypairs, &[2, 3], vote_2.data.clone(), &spec),
+            )],
+            SpecialProcessingError::NoSlashableValidators,
+        );
+
+        let mut forged = vote_2.clone();
+        forged.aggregate_sig_indices = vec![1, 3];
+        check(
+            &[casper_slashing(vote_1.clone(), forged)],
+            SpecialProcessingError::InvalidVoteSignature,
+        );
+
+        let mut unknown = vote_2.clone();
+        unknown.aggregate_sig_indices = vec![1, 4];
+        check(
+            &[casper_slashing(vote_1.clone(), unknown)],
+            SpecialProcessingError::UnknownValidator,
+        );
+
+        let mut too_many = vote_2.clone();
+        too_many.aggregate_sig_indices = vec![1; spec.max_casper_votes as usize + 1];
+        check(
+            &[casper_slashing(vote_1.clone(), too_many)],
+            SpecialProcessingError::TooManyCasperVotes,
+        );
+
+        check(
+            &[
+                casper_slashing(vote_1.clone(), vote_2.clone()),
+                casper_slashing(vote_1.clone(), vote_2.clone()),
+            ],
+            SpecialProcessingError::NoSlashableValidators,
+        );
+    }
+
+    #[test]
+    fn test_process_randao_change() {
+        let spec = ChainSpec::minimal();
+        let (mut state, keypairs) = test_state_with_keypairs(4, &spec);
+        state.slot = 2;
+
+        let change = randao_change(&state, 2, &keypairs[2], &spec);
+        let validators = process_state_specials(&state, &[change], &spec).unwrap();
+
+        let mut expected = state.validator_registry.clone();
+        expected[2].randao_commitment = Hash256::from("commitment".as_bytes());
+        expected[2].randao_last_change = 2;
+        assert_eq!(validators, expected);
+
+        let change = randao_change(&state, 1, &keypairs[1], &spec);
+        assert_eq!(
+            process_state_specials(&state, &[change], &spec),
+            Err(SpecialProcessingError::RandaoChangeNotByProposer)
+        );
+    }
+
+    #[test]
+    fn test_process_randao_change_invalid_signature() {
+        let spec = ChainSpec::minimal();
+        let (mut state, keypairs) = test_state_with_keypairs(4, &spec);
+        state.slot = 2;
+
+        /*
+         * Naming the proposer is not enough, the change must be signed by them.
+         */
+        let change = randao_change(&state, 2, &keypairs[1], &spec);
+        assert_eq!(
+            process_state_specials(&state, &[change], &spec),
+            Err(SpecialProcessingError::InvalidRandaoChangeSignature)
+        );
+
+        /*
+         * A change signed for another fork version is not valid.
+         */
+        let change = randao_change(&state, 2, &keypairs[2], &spec);
+        state.fork_data.post_fork_version = 1;
+        assert_eq!(
+            process_state_specials(&state, &[change], &spec),
+            Err(SpecialProcessingError::InvalidRandaoChangeSignature)
+        );
+    }
+
+    #[test]
+    fn test_process_specials_invalid_payload() {
+        let spec = ChainSpec::minimal();
+        let (state, _) = test_state_with_keypairs(4, &spec);
+
+        let special = SpecialRecord {
+            kind: 88,
+            data: vec![],
+        };
+        let decode_error = special.decode_payload().unwrap_err();
+        assert_eq!(
+            process_state_specials(&state, &[special], &spec),
+            Err(SpecialProcessingError::InvalidPayload(decode_error))
+        );
+
+        let special = SpecialRecord::randao_change(&[1, 2, 3]);
+        let decode_error = special.decode_payload().unwrap_err();
+        assert_eq!(
+            process_state_specials(&state, &[special], &spec),
+            Err(SpecialProcessingError::InvalidPayload(decode_error))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use bls::Keypair;
+use helpers::{beacon_proposer_index, block_hash_at_slot};
+use spec::ChainSpec;
+use types::{
+    Attestation, BeaconBlock, BeaconState, Bitfield, CrosslinkRecord, ForkData, Hash256,
+    ShardAndCommittee, ValidatorRecord, ValidatorStatus,
+};
+
+pub use validator_client::test_utils::test_hash_onion;
+
+/// Returns a state at slot zero with `validator_count` active validators, all of which are
+/// members of a single committee in each slot. Each slot is assigned a shard in turn. The
+/// `randao_commitment` of each validator is the commitment of its `test_hash_onion`.
+///
+/// Each of the `latest_block_hashes` is distinct.
+pub fn test_state(validator_count: usize, spec: &ChainSpec) -> BeaconState {
+    let (state, _) = test_state_with_keypairs(validator_count, spec);
+    state
+}
+
+/// Returns a state as per `test_state`, along with the keypair of each validator.
+pub fn test_state_with_keypairs(
+    validator_count: usize,
+    spec: &ChainSpec,
+) -> (BeaconState, Vec<Keypair>) {
+    let (validator_registry, keypairs) = (0..validator_count)
+        .map(|i| {
+            let (mut validator, keypair) = ValidatorRecord::zero_with_thread_rand_keypair();
+            validator.status = ValidatorStatus::Active;
+            validator.balance = spec.max_deposit_gwei().unwrap();
+            validator.randao_commitment = test_hash_onion(i).commitment();
+            (validator, keypair)
+        })
+        .unzip();
+    let shard_and_committee_for_slots = (0..spec.epoch_length * 2)
+        .map(|slot| {
+            vec![ShardAndCommittee {
+                shard: (slot % spec.shard_count) as u16,
+                committee: (0..validator_count).collect(),
+            }]
+        })
+        .collect();
+    let state = BeaconState {
+        slot: 0,
+        validator_registry,
+        validator_registry_latest_change_slot: 0,
+        validator_registry_exit_count: 0,
+        validator_registry_delta_chain_tip: Hash256::zero(),
+        randao_mix: Hash256::zero(),
+        next_seed: Hash256::zero(),
+        shard_and_committee_for_slots,
+        persistent_committees: vec![],
+        persistent_committee_reassignments: vec![],
+        previous_justified_slot: 0,
+        justified_slot: 0,
+        justified_slot_bitfield: 0,
+        finalized_slot: 0,
+        latest_crosslinks: vec![CrosslinkRecord::zero(); spec.shard_count as usize],
+        latest_state_recalculation_slot: 0,
+        latest_block_hashes: (0..spec.latest_block_roots_length)
+            .map(|i| Hash256::from(i + 1))
+            .collect(),
+        latest_penalized_exit_balances: vec![],
+        latest_attestations: vec![],
+        processed_pow_receipt_root: Hash256::zero(),
+        candidate_pow_receipt_roots: vec![],
+        genesis_time: 0,
+        fork_data: ForkData {
+            pre_fork_version: 0,
+            post_fork_version: 0,
+            fork_slot: 0,
+        },
+    };
+    (state, keypairs)
+}
+
+/// Returns an attestation for each committee at `slot`, in which every member of the committee
+/// has participated and which may be included in a block at the slot of the `state`.
+pub fn test_attestations(state: &BeaconState, slot: u64, spec: &ChainSpec) -> Vec<Attestation> {
+    let earliest_slot = state
+        .latest_state_recalculation_slot
+        .saturating_sub(spec.epoch_length);
+    let epoch_start = slot - slot % spec.epoch_length;
+    let justified_slot = if slot >= state.latest_state_recalculation_slot {
+        state.justified_slot
+    } else {
+        state.previous_justified_slot
+    };
+
+    state.shard_and_committee_for_slots[(slot - earliest_slot) as usize]
+        .iter()
+        .map(|shard_and_committee| {
+            let mut attestation = Attestation::zero();
+            attestation.data.slot = slot;
+            attestation.data.shard = u64::from(shard_and_committee.shard);
+            attestation.data.epoch_boundary_hash = block_hash_at_slot(state, epoch_start)
+                .cloned()
+                .unwrap_or_else(Hash256::zero);
+            attestation.data.shard_block_hash = Hash256::from("shard_block".as_bytes());
+            attestation.data.justified_slot = justified_slot;
+            attestation.data.justified_block_hash = block_hash_at_slot(state, justified_slot)
+                .cloned()
+                .unwrap_or_else(Hash256::zero);
+            attestation.participation_bitfield =
+                Bitfield::from_elem(shard_and_committee.committee.len(), true);
+            attestation
+        })
+        .collect()
+}
+
+/// Returns the attestation for the first committee at `slot`, see `test_attestations`.
+pub fn test_attestation(state: &BeaconState, slot: u64, spec: &ChainSpec) -> Attestation {
+    test_attestations(state, slot, spec).remove(0)
+}
+
+/// Returns a block at the slot of the `state`, which is a child of the latest block of the
+/// `state`. The proposer reveals the next layer of their `test_hash_onion`.
+pub fn test_block(
+    state: &BeaconState,
+    attestations: Vec<Attestation>,
+    spec: &ChainSpec,
+) -> BeaconBlock {
+    let proposer = beacon_proposer_index(state, state.slot, spec).unwrap();
+    let commitment = state.validator_registry[proposer].randao_commitment;
+    let mut block = BeaconBlock::zero();
+    block.slot = state.slot;
+    block.ancestor_hashes = vec![*state.latest_block_hashes.last().unwrap()];
+    block.randao_reveal = test_hash_onion(proposer).reveal(&commitment, 1).unwrap();
+    block.attestations = attestations;
+    block
+}
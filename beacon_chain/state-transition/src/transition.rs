@@ -0,0 +1,143 @@
+use super::block_processing::{per_block_processing, BlockProcessingError};
+use super::epoch_processing::{per_epoch_processing, EpochProcessingError};
+use super::slot_processing::{per_slot_processing, SlotProcessingError};
+use spec::ChainSpec;
+use types::{BeaconBlock, BeaconState, Hash256};
+
+/// Reasons why `transition_state` could not advance a `BeaconState`.
+#[derive(Debug, PartialEq)]
+pub enum TransitionError {
+    SlotProcessingError(SlotProcessingError),
+    BlockProcessingError(BlockProcessingError),
+    EpochProcessingError(EpochProcessingError),
+}
+
+impl From<SlotProcessingError> for TransitionError {
+    fn from(e: SlotProcessingError) -> TransitionError {
+        TransitionError::SlotProcessingError(e)
+    }
+}
+
+impl From<BlockProcessingError> for TransitionError {
+    fn from(e: BlockProcessingError) -> TransitionError {
+        TransitionError::BlockProcessingError(e)
+    }
+}
+
+impl From<EpochProcessingError> for TransitionError {
+    fn from(e: EpochProcessingError) -> TransitionError {
+        TransitionError::EpochProcessingError(e)
+    }
+}
+
+/// Advance the `state` to the next slot and apply the `block` at that slot, if there is one.
+///
+/// This is `per_slot_processing`, then `per_block_processing` and, if the new slot is the last
+/// slot of an epoch, `per_epoch_processing`. The `previous_block_hash` is as per
+/// `per_slot_processing`.
+///
+/// The `state` is unchanged if an error is returned.
+pub fn transition_state(
+    state: &mut BeaconState,
+    previous_block_hash: &Hash256,
+    block: Option<&BeaconBlock>,
+    spec: &ChainSpec,
+) -> Result<(), TransitionError> {
+    let mut new_state = state.clone();
+
+    per_slot_processing(&mut new_state, previous_block_hash)?;
+    if let Some(block) = block {
+        per_block_processing(&mut new_state, block, spec)?;
+    }
+    let is_epoch_end = new_state.slot.checked_add(1)
+        == new_state
+            .latest_state_recalculation_slot
+            .checked_add(spec.epoch_length);
+    if is_epoch_end {
+        per_epoch_processing(&mut new_state, spec)?;
+    }
+
+    *state = new_state;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::{test_attestations, test_block, test_state};
+
+    /// Returns a block at the slot following that of the `state`, as per `test_block`.
+    fn next_block(
+        state: &BeaconState,
+        previous_block_hash: &Hash256,
+        spec: &ChainSpec,
+    ) -> BeaconBlock {
+        let mut next_state = state.clone();
+        per_slot_processing(&mut next_state, previous_block_hash).unwrap();
+        let attestations = test_attestations(&next_state, state.slot, spec);
+        test_block(&next_state, attestations, spec)
+    }
+
+    #[test]
+    fn test_transition_state_justifies_each_epoch() {
+        let spec = ChainSpec::minimal();
+        let mut state = test_state(8, &spec);
+        let mut latest_block_hash = Hash256::from("genesis".as_bytes());
+
+        for slot in 1..=spec.epoch_length * 4 {
+            let block = next_block(&state, &latest_block_hash, &spec);
+            transition_state(&mut state, &latest_block_hash, Some(&block), &spec).unwrap();
+            latest_block_hash = Hash256::from(slot);
+            assert_eq!(state.slot, slot);
+
+            /*
+             * The epoch is processed at its last slot.
+             */
+            let next_epoch_start = slot + 1;
+            if next_epoch_start % spec.epoch_length == 0 {
+                assert_eq!(state.latest_state_recalculation_slot, next_epoch_start);
+                assert_eq!(state.justified_slot, next_epoch_start - spec.epoch_length);
+            } else {
+                assert_eq!(
+                    state.latest_state_recalculation_slot,
+                    slot - slot % spec.epoch_length
+                );
+            }
+        }
+        assert_eq!(state.justified_slot_bitfield, 0b1111);
+    }
+
+    #[test]
+    fn test_transition_state_without_blocks() {
+        let spec = ChainSpec::minimal();
+        let mut state = test_state(8, &spec);
+
+        for _ in 0..spec.epoch_length {
+            transition_state(&mut state, &Hash256::zero(), None, &spec).unwrap();
+        }
+        assert_eq!(state.slot, spec.epoch_length);
+        assert_eq!(state.latest_state_recalculation_slot, spec.epoch_length);
+        assert_eq!(state.latest_attestations, vec![]);
+    }
+
+    #[test]
+    fn test_transition_state_error() {
+        let spec = ChainSpec::minimal();
+        let mut state = test_state(8, &spec);
+        let original = state.clone();
+
+        /*
+         * The state is not modified by a failed transition, although the slot is processed before
+         * the block.
+         */
+        let mut block = next_block(&state, &Hash256::zero(), &spec);
+        block.slot += 1;
+        assert_eq!(
+            transition_state(&mut state, &Hash256::zero(), Some(&block), &spec),
+            Err(TransitionError::BlockProcessingError(
+                BlockProcessingError::SlotMismatch
+            ))
+        );
+        assert_eq!(state, original);
+    }
+}
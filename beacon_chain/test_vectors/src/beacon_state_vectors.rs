@@ -0,0 +1,342 @@
+use super::check_eq;
+use super::parse;
+use super::spec::ChainSpec;
+use super::state_transition::{
+    per_block_processing, per_epoch_processing, per_slot_processing, transition_state,
+};
+use super::types::{
+    AttestationData, BeaconBlock, BeaconState, Bitfield, CrosslinkRecord, ForkData, Hash256,
+    PendingAttestationRecord, ShardAndCommittee, ValidatorRecord, ValidatorStatus,
+};
+use super::yaml_rust::Yaml;
+
+/// Runs a test case for the `state_transition` test suite with one of the functions which
+/// transition a `BeaconState`: `per_slot_processing`, `per_block_processing`,
+/// `per_epoch_processing` or `transition_state`.
+///
+/// The functions are run with `ChainSpec::minimal()`. Each case has the `pre` state and either the
+/// `post` state or the name of the expected error as the `error`. Cases for `per_slot_processing`
+/// and `transition_state` have a `previous_block_hash`, cases for `per_block_processing` have a
+/// `block`, which is optional for `transition_state`.
+///
+/// Fields omitted from the `pre` state (or from one of its validators) are zero, whereas fields
+/// omitted from the `post` state (or from one of its validators) are not checked. Validator public
+/// keys are not read, each validator of the `pre` state is given a random key.
+pub fn run_case(function: &str, case: &Yaml) -> Result<(), String> {
+    let spec = ChainSpec::minimal();
+    let mut state = read_state(parse::field(case, "pre")?, &empty_state())?;
+
+    let result = match function {
+        "per_slot_processing" => {
+            let previous_block_hash = parse::as_hash(parse::field(case, "previous_block_hash")?)?;
+            per_slot_processing(&mut state, &previous_block_hash).map_err(|e| format!("{:?}", e))
+        }
+        "per_block_processing" => {
+            let block = read_block(parse::field(case, "block")?)?;
+            per_block_processing(&mut state, &block, &spec).map_err(|e| format!("{:?}", e))
+        }
+        "per_epoch_processing" => {
+            per_epoch_processing(&mut state, &spec).map_err(|e| format!("{:?}", e))
+        }
+        "transition_state" => {
+            let previous_block_hash = parse::as_hash(parse::field(case, "previous_block_hash")?)?;
+            let block = match parse::optional_field(case, "block") {
+                Some(block) => Some(read_block(block)?),
+                None => None,
+            };
+            transition_state(&mut state, &previous_block_hash, block.as_ref(), &spec)
+                .map_err(|e| format!("{:?}", e))
+        }
+        _ => return Err(format!("unknown function {:?}", function)),
+    };
+
+    match parse::optional_field(case, "error") {
+        Some(error) => {
+            let error = parse::as_str(error)?;
+            match result {
+                Ok(()) => Err(format!("expected {}, got {:?}", error, state)),
+                Err(e) => check_eq("error", &error.to_string(), &e),
+            }
+        }
+        None => {
+            result.map_err(|e| format!("unexpected error: {}", e))?;
+            let expected = read_state(parse::field(case, "post")?, &state)?;
+            check_eq("post", &expected, &state)
+        }
+    }
+}
+
+/// Returns the value of `key` read with `f`, or `default` if `key` is absent.
+fn read_or<T, F>(yaml: &Yaml, key: &str, f: F, default: &T) -> Result<T, String>
+where
+    T: Clone,
+    F: Fn(&Yaml) -> Result<T, String>,
+{
+    match parse::optional_field(yaml, key) {
+        Some(value) => f(value),
+        None => Ok(default.clone()),
+    }
+}
+
+/// Reads a state, taking any omitted fields from `base`.
+///
+/// The `persistent_committees`, `persistent_committee_reassignments`,
+/// `candidate_pow_receipt_roots` and `fork_data` are not read.
+fn read_state(yaml: &Yaml, base: &BeaconState) -> Result<BeaconState, String> {
+    let read_u64 = |key: &str, default: &u64| read_or(yaml, key, parse::as_u64, default);
+    let read_hash = |key: &str, default: &Hash256| read_or(yaml, key, parse::as_hash, default);
+
+    Ok(BeaconState {
+        slot: read_u64("slot", &base.slot)?,
+        validator_registry: read_or(
+            yaml,
+            "validator_registry",
+            |validators| read_validators(validators, &base.validator_registry),
+            &base.validator_registry,
+        )?,
+        validator_registry_latest_change_slot: read_u64(
+            "validator_registry_latest_change_slot",
+            &base.validator_registry_latest_change_slot,
+        )?,
+        validator_registry_exit_count: read_u64(
+            "validator_registry_exit_count",
+            &base.validator_registry_exit_count,
+        )?,
+        validator_registry_delta_chain_tip: read_hash(
+            "validator_registry_delta_chain_tip",
+            &base.validator_registry_delta_chain_tip,
+        )?,
+        randao_mix: read_hash("randao_mix", &base.randao_mix)?,
+        next_seed: read_hash("next_seed", &base.next_seed)?,
+        shard_and_committee_for_slots: read_or(
+            yaml,
+            "shard_and_committee_for_slots",
+            |slots| {
+                parse::as_list_of(slots, |slot| {
+                    parse::as_list_of(slot, read_shard_and_committee)
+                })
+            },
+            &base.shard_and_committee_for_slots,
+        )?,
+        persistent_committees: base.persistent_committees.clone(),
+        persistent_committee_reassignments: base.persistent_committee_reassignments.clone(),
+        previous_justified_slot: read_u64(
+            "previous_justified_slot",
+            &base.previous_justified_slot,
+        )?,
+        justified_slot: read_u64("justified_slot", &base.justified_slot)?,
+        justified_slot_bitfield: read_u64(
+            "justified_slot_bitfield",
+            &base.justified_slot_bitfield,
+        )?,
+        finalized_slot: read_u64("finalized_slot", &base.finalized_slot)?,
+        latest_crosslinks: read_or(
+            yaml,
+            "latest_crosslinks",
+            |crosslinks| parse::as_list_of(crosslinks, read_crosslink),
+            &base.latest_crosslinks,
+        )?,
+        latest_state_recalculation_slot: read_u64(
+            "latest_state_recalculation_slot",
+            &base.latest_state_recalculation_slot,
+        )?,
+        latest_block_hashes: read_or(
+            yaml,
+            "latest_block_hashes",
+            |hashes| parse::as_list_of(hashes, parse::as_hash),
+            &base.latest_block_hashes,
+        )?,
+        latest_penalized_exit_balances: read_or(
+            yaml,
+            "latest_penalized_exit_balances",
+            |balances| parse::as_list_of(balances, parse::as_u64),
+            &base.latest_penalized_exit_balances,
+        )?,
+        latest_attestations: read_or(
+            yaml,
+            "latest_attestations",
+            |attestations| parse::as_list_of(attestations, read_pending_attestation),
+            &base.latest_attestations,
+        )?,
+        processed_pow_receipt_root: read_hash(
+            "processed_pow_receipt_root",
+            &base.processed_pow_receipt_root,
+        )?,
+        candidate_pow_receipt_roots: base.candidate_pow_receipt_roots.clone(),
+        genesis_time: read_u64("genesis_time", &base.genesis_time)?,
+        fork_data: base.fork_data.clone(),
+    })
+}
+
+/// Returns a state in which every field is zero or empty.
+fn empty_state() -> BeaconState {
+    BeaconState {
+        slot: 0,
+        validator_registry: vec![],
+        validator_registry_latest_change_slot: 0,
+        validator_registry_exit_count: 0,
+        validator_registry_delta_chain_tip: Hash256::zero(),
+        randao_mix: Hash256::zero(),
+        next_seed: Hash256::zero(),
+        shard_and_committee_for_slots: vec![],
+        persistent_committees: vec![],
+        persistent_committee_reassignments: vec![],
+        previous_justified_slot: 0,
+        justified_slot: 0,
+        justified_slot_bitfield: 0,
+        finalized_slot: 0,
+        latest_crosslinks: vec![],
+        latest_state_recalculation_slot: 0,
+        latest_block_hashes: vec![],
+        latest_penalized_exit_balances: vec![],
+        latest_attestations: vec![],
+        processed_pow_receipt_root: Hash256::zero(),
+        candidate_pow_receipt_roots: vec![],
+        genesis_time: 0,
+        fork_data: ForkData {
+            pre_fork_version: 0,
+            post_fork_version: 0,
+            fork_slot: 0,
+        },
+    }
+}
+
+/// Reads a list of validators, taking any omitted fields from the validator with the same index
+/// in `base`, or from a new validator if there is none.
+fn read_validators(yaml: &Yaml, base: &[ValidatorRecord]) -> Result<Vec<ValidatorRecord>, String> {
+    parse::as_list(yaml)?
+        .iter()
+        .enumerate()
+        .map(|(i, validator)| match base.get(i) {
+            Some(base) => read_validator(validator, base),
+            None => read_validator(
+                validator,
+                &ValidatorRecord::zero_with_thread_rand_keypair().0,
+            ),
+        })
+        .collect()
+}
+
+fn read_validator(yaml: &Yaml, base: &ValidatorRecord) -> Result<ValidatorRecord, String> {
+    Ok(ValidatorRecord {
+        pubkey: base.pubkey.clone(),
+        withdrawal_shard: read_or(
+            yaml,
+            "withdrawal_shard",
+            |shard| parse::as_uint(shard, 0xffff).map(|shard| shard as u16),
+            &base.withdrawal_shard,
+        )?,
+        withdrawal_address: read_or(
+            yaml,
+            "withdrawal_address",
+            parse::as_address,
+            &base.withdrawal_address,
+        )?,
+        randao_commitment: read_or(
+            yaml,
+            "randao_commitment",
+            parse::as_hash,
+            &base.randao_commitment,
+        )?,
+        randao_last_change: read_or(
+            yaml,
+            "randao_last_change",
+            parse::as_u64,
+            &base.randao_last_change,
+        )?,
+        balance: read_or(yaml, "balance", parse::as_u64, &base.balance)?,
+        status: read_or(yaml, "status", read_status, &base.status)?,
+        exit_slot: read_or(yaml, "exit_slot", parse::as_u64, &base.exit_slot)?,
+    })
+}
+
+/// Reads a `ValidatorStatus` from its byte (e.g., `1` for `Active`).
+fn read_status(yaml: &Yaml) -> Result<ValidatorStatus, String> {
+    let status = parse::as_uint(yaml, 0xff)? as u8;
+    ValidatorStatus::from_u8(status).ok_or_else(|| format!("unknown validator status {}", status))
+}
+
+fn read_shard_and_committee(yaml: &Yaml) -> Result<ShardAndCommittee, String> {
+    Ok(ShardAndCommittee {
+        shard: parse::as_uint(parse::field(yaml, "shard")?, 0xffff)? as u16,
+        committee: parse::as_list_of(parse::field(yaml, "committee")?, |index| {
+            parse::as_u64(index).map(|index| index as usize)
+        })?,
+    })
+}
+
+fn read_crosslink(yaml: &Yaml) -> Result<CrosslinkRecord, String> {
+    Ok(CrosslinkRecord {
+        slot: parse::as_u64(parse::field(yaml, "slot")?)?,
+        shard_block_hash: parse::as_hash(parse::field(yaml, "shard_block_hash")?)?,
+    })
+}
+
+/// Reads a pending attestation, where omitted fields (including those of its `data`) are zero.
+///
+/// Bitfields are bytes, the first bit of which is the most significant bit of the first byte
+/// (e.g., `'0xc0'` for the first two members of a committee).
+fn read_pending_attestation(yaml: &Yaml) -> Result<PendingAttestationRecord, String> {
+    let data = parse::field(yaml, "data")?;
+    let zero = AttestationData::zero();
+    let read_bitfield = |key: &str| {
+        read_or(
+            yaml,
+            key,
+            |bytes| parse::as_bytes(bytes).map(|bytes| Bitfield::from_bytes(&bytes)),
+            &Bitfield::new(),
+        )
+    };
+
+    Ok(PendingAttestationRecord {
+        data: AttestationData {
+            slot: read_or(data, "slot", parse::as_u64, &zero.slot)?,
+            shard: read_or(data, "shard", parse::as_u64, &zero.shard)?,
+            beacon_block_hash: read_or(
+                data,
+                "beacon_block_hash",
+                parse::as_hash,
+                &zero.beacon_block_hash,
+            )?,
+            epoch_boundary_hash: read_or(
+                data,
+                "epoch_boundary_hash",
+                parse::as_hash,
+                &zero.epoch_boundary_hash,
+            )?,
+            shard_block_hash: read_or(
+                data,
+                "shard_block_hash",
+                parse::as_hash,
+                &zero.shard_block_hash,
+            )?,
+            latest_crosslink_hash: read_or(
+                data,
+                "latest_crosslink_hash",
+                parse::as_hash,
+                &zero.latest_crosslink_hash,
+            )?,
+            justified_slot: read_or(data, "justified_slot", parse::as_u64, &zero.justified_slot)?,
+            justified_block_hash: read_or(
+                data,
+                "justified_block_hash",
+                parse::as_hash,
+                &zero.justified_block_hash,
+            )?,
+        },
+        participation_bitfield: read_bitfield("participation_bitfield")?,
+        custody_bitfield: read_bitfield("custody_bitfield")?,
+        slot_included: read_or(yaml, "slot_included", parse::as_u64, &0)?,
+    })
+}
+
+/// Reads a block with a `slot`, `ancestor_hashes` and a `randao_reveal`. Attestations and
+/// specials are not supported.
+fn read_block(yaml: &Yaml) -> Result<BeaconBlock, String> {
+    Ok(BeaconBlock {
+        slot: parse::as_u64(parse::field(yaml, "slot")?)?,
+        ancestor_hashes: parse::as_list_of(parse::field(yaml, "ancestor_hashes")?, parse::as_hash)?,
+        randao_reveal: parse::as_hash(parse::field(yaml, "randao_reveal")?)?,
+        ..BeaconBlock::zero()
+    })
+}
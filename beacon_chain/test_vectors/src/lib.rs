@@ -0,0 +1,250 @@
+extern crate hashing;
+extern crate hex;
+extern crate spec;
+extern crate ssz;
+extern crate state_transition;
+extern crate types;
+extern crate validator_shuffling;
+extern crate vec_shuffle;
+extern crate yaml_rust;
+
+mod beacon_state_vectors;
+mod parse;
+mod shard_and_committee_vectors;
+mod shuffle_vectors;
+mod ssz_vectors;
+mod state_transition_vectors;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use yaml_rust::{ScanError, Yaml, YamlLoader};
+
+/// The file extensions of test vector files. JSON is read as YAML, of which it is a subset.
+const VECTOR_EXTENSIONS: &[&str] = &["yaml", "yml", "json"];
+
+#[derive(Debug)]
+pub enum TestVectorError {
+    Io(PathBuf, io::Error),
+    Yaml(PathBuf, ScanError),
+    /// The file does not have the `title`, `test_suite` and `test_cases` fields.
+    InvalidFile(PathBuf, String),
+    UnknownTestSuite(PathBuf, String),
+}
+
+/// The result of running a single test case.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CaseResult {
+    pub path: PathBuf,
+    pub title: String,
+    /// The `name` of the case, or its index in `test_cases` if it has none.
+    pub name: String,
+    /// A description of the failure if the case did not pass.
+    pub result: Result<(), String>,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Runs every test case in a test vector file.
+///
+/// Each file must hold a `title`, a `test_suite` which determines how the cases are run and a list
+/// of `test_cases`, see the `README.md` for the fields of each suite.
+///
+/// A malformed test case is reported as a failure of that case, whereas a malformed file is an
+/// error.
+pub fn run_file(path: &Path) -> Result<Vec<CaseResult>, TestVectorError> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| TestVectorError::Io(path.to_path_buf(), e))?;
+    let docs = YamlLoader::load_from_str(&contents)
+        .map_err(|e| TestVectorError::Yaml(path.to_path_buf(), e))?;
+    let invalid = |reason: String| TestVectorError::InvalidFile(path.to_path_buf(), reason);
+
+    let doc = docs
+        .first()
+        .ok_or_else(|| invalid("empty file".to_string()))?;
+    let title = parse::field(doc, "title")
+        .and_then(parse::as_str)
+        .map_err(&invalid)?;
+    let test_suite = parse::field(doc, "test_suite")
+        .and_then(parse::as_str)
+        .map_err(&invalid)?;
+    let test_cases = parse::field(doc, "test_cases")
+        .and_then(parse::as_list)
+        .map_err(&invalid)?;
+
+    let run_case: fn(&Yaml) -> Result<(), String> = match test_suite {
+        "ssz" => ssz_vectors::run_case,
+        /*
+         * `Shuffling` is the suite of the vectors generated by sigp/shuffling_sandbox.
+         */
+        "shuffle" | "Shuffling" => shuffle_vectors::run_case,
+        "shard_and_committees" => shard_and_committee_vectors::run_case,
+        "state_transition" => state_transition_vectors::run_case,
+        _ => {
+            return Err(TestVectorError::UnknownTestSuite(
+                path.to_path_buf(),
+                test_suite.to_string(),
+            ))
+        }
+    };
+
+    Ok(test_cases
+        .iter()
+        .enumerate()
+        .map(|(i, case)| CaseResult {
+            path: path.to_path_buf(),
+            title: title.to_string(),
+            name: case["name"]
+                .as_str()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| i.to_string()),
+            result: run_case(case),
+        })
+        .collect())
+}
+
+/// Runs every test vector file in `dir` and its subdirectories, in order of their paths.
+pub fn run_dir(dir: &Path) -> Result<Vec<CaseResult>, TestVectorError> {
+    let mut paths = vec![];
+    find_vector_files(dir, &mut paths)?;
+    paths.sort();
+
+    let mut results = vec![];
+    for path in paths {
+        results.append(&mut run_file(&path)?);
+    }
+    Ok(results)
+}
+
+fn find_vector_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), TestVectorError> {
+    let io_error = |e| TestVectorError::Io(dir.to_path_buf(), e);
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        let is_vector_file = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => VECTOR_EXTENSIONS.contains(&ext),
+            None => false,
+        };
+        if path.is_dir() {
+            find_vector_files(&path, paths)?;
+        } else if is_vector_file {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns an error describing the difference if `expected != actual`.
+fn check_eq<T: PartialEq + std::fmt::Debug>(
+    field: &str,
+    expected: &T,
+    actual: &T,
+) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}: expected {:?}, got {:?}",
+            field, expected, actual
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("vectors")
+    }
+
+    fn reference_shuffle_vectors() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../utils/vec_shuffle/src/specs/shuffle_test_vectors.yaml")
+    }
+
+    /// Writes `contents` to a file in the temporary directory which is unique to this process, so
+    /// that concurrent test runs do not share it.
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("test_vectors_{}_{}.yaml", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_bundled_vectors() {
+        let results = run_dir(&vectors_dir()).unwrap();
+        assert!(!results.is_empty());
+
+        let failures: Vec<&CaseResult> = results.iter().filter(|r| !r.passed()).collect();
+        assert!(failures.is_empty(), "failed cases: {:#?}", failures);
+
+        /*
+         * Each test suite has some bundled vectors.
+         */
+        for dir in &[
+            "regression/shard_and_committees",
+            "regression/shuffle",
+            "regression/state_transition",
+            "ssz",
+        ] {
+            assert!(results
+                .iter()
+                .any(|r| r.path.starts_with(vectors_dir().join(dir))));
+        }
+    }
+
+    /// The reference vectors were generated using blake2 rather than keccak, so they will not pass
+    /// until one of the two is changed. Run with `cargo test -- --ignored`.
+    ///
+    /// https://github.com/sigp/lighthouse/issues/121
+    #[test]
+    #[ignore]
+    fn test_reference_shuffle_vectors() {
+        let results = run_file(&reference_shuffle_vectors()).unwrap();
+        assert_eq!(results.len(), 42);
+
+        let failures: Vec<&CaseResult> = results.iter().filter(|r| !r.passed()).collect();
+        assert!(failures.is_empty(), "failed cases: {:#?}", failures);
+    }
+
+    #[test]
+    fn test_failing_case_is_reported() {
+        let path = write_temp_file(
+            "failing_case",
+            "title: failing\ntest_suite: ssz\ntest_cases:\n\
+             - {name: wrong, type: uint8, value: 1, ssz: '0x02'}\n\
+             - {name: malformed, type: uint8, ssz: '0x02'}\n\
+             - {type: uint8, value: 2, ssz: '0x02'}\n",
+        );
+        let results = run_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].name, "wrong");
+        assert!(!results[0].passed());
+        assert_eq!(results[1].name, "malformed");
+        assert_eq!(results[1].result, Err("missing field `value`".to_string()));
+        assert_eq!(results[2].name, "2");
+        assert!(results[2].passed());
+    }
+
+    #[test]
+    fn test_unknown_test_suite() {
+        let path = write_temp_file(
+            "unknown_suite",
+            "title: unknown\ntest_suite: nothing\ntest_cases: []\n",
+        );
+        let result = run_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(TestVectorError::UnknownTestSuite(_, suite)) => assert_eq!(suite, "nothing"),
+            other => panic!("expected UnknownTestSuite, got {:?}", other),
+        }
+    }
+}
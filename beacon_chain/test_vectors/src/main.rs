@@ -0,0 +1,44 @@
+extern crate test_vectors;
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+/// Runs the test vectors in the directory given as the first argument, or the bundled vectors if
+/// no directory is given.
+fn main() {
+    let dir = env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("vectors"));
+
+    let results = match test_vectors::run_dir(&dir) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Unable to run test vectors: {:?}", e);
+            process::exit(2);
+        }
+    };
+
+    let mut failures = 0;
+    for case in &results {
+        match case.result {
+            Ok(()) => println!("PASS {} / {}", case.title, case.name),
+            Err(ref reason) => {
+                failures += 1;
+                println!(
+                    "FAIL {} / {} ({}): {}",
+                    case.title,
+                    case.name,
+                    case.path.display(),
+                    reason
+                );
+            }
+        }
+    }
+    println!("{} passed, {} failed", results.len() - failures, failures);
+
+    if failures > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,148 @@
+use super::hex;
+use super::types::{Address, Hash256};
+use super::yaml_rust::Yaml;
+
+/*
+ * Helpers for reading values from a test case, each returning a description of the problem if
+ * the value is missing or malformed.
+ */
+
+/// Returns the value of `key` in some YAML hash.
+pub fn field<'a>(yaml: &'a Yaml, key: &str) -> Result<&'a Yaml, String> {
+    match yaml[key] {
+        Yaml::BadValue => Err(format!("missing field `{}`", key)),
+        ref value => Ok(value),
+    }
+}
+
+/// Returns the value of `key` if it is present and not null.
+pub fn optional_field<'a>(yaml: &'a Yaml, key: &str) -> Option<&'a Yaml> {
+    match yaml[key] {
+        Yaml::BadValue | Yaml::Null => None,
+        ref value => Some(value),
+    }
+}
+
+pub fn as_str(yaml: &Yaml) -> Result<&str, String> {
+    yaml.as_str()
+        .ok_or_else(|| format!("expected a string, found {:?}", yaml))
+}
+
+pub fn as_list(yaml: &Yaml) -> Result<&[Yaml], String> {
+    yaml.as_vec()
+        .map(|list| &list[..])
+        .ok_or_else(|| format!("expected a list, found {:?}", yaml))
+}
+
+/// Reads an unsigned integer.
+///
+/// YAML integers are signed 64-bit, so values above `i64::max_value()` may be given as decimal strings.
+pub fn as_u64(yaml: &Yaml) -> Result<u64, String> {
+    match yaml {
+        Yaml::Integer(i) if *i >= 0 => Ok(*i as u64),
+        Yaml::String(s) => s
+            .parse()
+            .map_err(|_| format!("expected an unsigned integer, found {:?}", s)),
+        _ => Err(format!("expected an unsigned integer, found {:?}", yaml)),
+    }
+}
+
+/// Reads an unsigned integer which must fit in `max`.
+pub fn as_uint(yaml: &Yaml, max: u64) -> Result<u64, String> {
+    let n = as_u64(yaml)?;
+    if n > max {
+        Err(format!("{} exceeds the maximum of {}", n, max))
+    } else {
+        Ok(n)
+    }
+}
+
+pub fn as_bool(yaml: &Yaml) -> Result<bool, String> {
+    yaml.as_bool()
+        .ok_or_else(|| format!("expected a bool, found {:?}", yaml))
+}
+
+/// Reads bytes from a hex string, with or without a leading `0x`.
+///
+/// The string must be quoted in YAML, otherwise `0x..` is read as an integer.
+pub fn as_bytes(yaml: &Yaml) -> Result<Vec<u8>, String> {
+    let s = as_str(yaml)?;
+    let s = match s.get(..2) {
+        Some("0x") => &s[2..],
+        _ => s,
+    };
+    hex::decode(s).map_err(|e| format!("invalid hex {:?}: {}", s, e))
+}
+
+pub fn as_hash(yaml: &Yaml) -> Result<Hash256, String> {
+    let bytes = as_bytes(yaml)?;
+    if bytes.len() != 32 {
+        return Err(format!("expected 32 bytes, found {}", bytes.len()));
+    }
+    Ok(Hash256::from(&bytes[..]))
+}
+
+pub fn as_address(yaml: &Yaml) -> Result<Address, String> {
+    let bytes = as_bytes(yaml)?;
+    if bytes.len() != 20 {
+        return Err(format!("expected 20 bytes, found {}", bytes.len()));
+    }
+    Ok(Address::from(&bytes[..]))
+}
+
+/// Reads each element of a list with `f`.
+pub fn as_list_of<T, F>(yaml: &Yaml, f: F) -> Result<Vec<T>, String>
+where
+    F: Fn(&Yaml) -> Result<T, String>,
+{
+    as_list(yaml)?.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::yaml_rust::YamlLoader;
+    use super::*;
+
+    fn load(s: &str) -> Yaml {
+        YamlLoader::load_from_str(s).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_as_u64() {
+        let yaml = load("[0, 42, -1, '18446744073709551615', '18446744073709551616', 1.5]");
+        let list = as_list(&yaml).unwrap();
+
+        assert_eq!(as_u64(&list[0]), Ok(0));
+        assert_eq!(as_u64(&list[1]), Ok(42));
+        assert!(as_u64(&list[2]).is_err());
+        assert_eq!(as_u64(&list[3]), Ok(u64::max_value()));
+        assert!(as_u64(&list[4]).is_err());
+        assert!(as_u64(&list[5]).is_err());
+        assert_eq!(as_uint(&list[1], 42), Ok(42));
+        assert!(as_uint(&list[1], 41).is_err());
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let yaml = load("['0x0102ff', '0102', '', '0x1', 0x01]");
+        let list = as_list(&yaml).unwrap();
+
+        assert_eq!(as_bytes(&list[0]), Ok(vec![1, 2, 255]));
+        assert_eq!(as_bytes(&list[1]), Ok(vec![1, 2]));
+        assert_eq!(as_bytes(&list[2]), Ok(vec![]));
+        assert!(as_bytes(&list[3]).is_err());
+        assert!(as_bytes(&list[4]).is_err());
+        assert!(as_hash(&list[0]).is_err());
+    }
+
+    #[test]
+    fn test_field() {
+        let yaml = load("{a: 1, b: ~}");
+
+        assert_eq!(field(&yaml, "a"), Ok(&Yaml::Integer(1)));
+        assert_eq!(field(&yaml, "b"), Ok(&Yaml::Null));
+        assert!(field(&yaml, "c").is_err());
+        assert_eq!(optional_field(&yaml, "b"), None);
+        assert_eq!(optional_field(&yaml, "c"), None);
+    }
+}
@@ -0,0 +1,63 @@
+use super::check_eq;
+use super::parse;
+use super::spec::ChainSpec;
+use super::types::{ShardAndCommittee, ValidatorRecord, ValidatorStatus};
+use super::validator_shuffling::shard_and_committees_for_cycle;
+use super::yaml_rust::Yaml;
+
+/// Runs a test case for the `shard_and_committees` test suite.
+///
+/// Each case has a hex `seed`, the status code of each validator as `validator_statuses`, the
+/// `crosslinking_shard_start` and the `epoch_length`, `shard_count` and `target_committee_size`
+/// of the chain.
+///
+/// The expected `output` is a list of slots, each a list of `{shard, committee}`. Alternatively an
+/// `error` may be given, matching the name of the expected `ValidatorAssignmentError`.
+pub fn run_case(case: &Yaml) -> Result<(), String> {
+    let seed = parse::as_bytes(parse::field(case, "seed")?)?;
+    let validators = parse::as_list_of(parse::field(case, "validator_statuses")?, |status| {
+        let status = parse::as_uint(status, 0xff)? as u8;
+        let status = ValidatorStatus::from_u8(status)
+            .ok_or_else(|| format!("unknown validator status {}", status))?;
+        let (mut validator, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+        validator.status = status;
+        Ok(validator)
+    })?;
+    let crosslinking_shard_start =
+        parse::as_uint(parse::field(case, "crosslinking_shard_start")?, 0xffff)? as u16;
+    let spec = ChainSpec {
+        epoch_length: parse::as_u64(parse::field(case, "epoch_length")?)?,
+        shard_count: parse::as_u64(parse::field(case, "shard_count")?)?,
+        target_committee_size: parse::as_u64(parse::field(case, "target_committee_size")?)?,
+        ..ChainSpec::foundation()
+    };
+
+    let result =
+        shard_and_committees_for_cycle(&seed, &validators, crosslinking_shard_start, &spec);
+
+    match parse::optional_field(case, "error") {
+        Some(error) => {
+            let error = parse::as_str(error)?;
+            match result {
+                Ok(cycle) => Err(format!("expected {}, got {:?}", error, cycle)),
+                Err(e) => check_eq("error", &error.to_string(), &format!("{:?}", e)),
+            }
+        }
+        None => {
+            let output = parse::as_list_of(parse::field(case, "output")?, |slot| {
+                parse::as_list_of(slot, read_shard_and_committee)
+            })?;
+            let cycle = result.map_err(|e| format!("unexpected error: {:?}", e))?;
+            check_eq("output", &output, &cycle)
+        }
+    }
+}
+
+fn read_shard_and_committee(yaml: &Yaml) -> Result<ShardAndCommittee, String> {
+    Ok(ShardAndCommittee {
+        shard: parse::as_uint(parse::field(yaml, "shard")?, 0xffff)? as u16,
+        committee: parse::as_list_of(parse::field(yaml, "committee")?, |i| {
+            parse::as_u64(i).map(|i| i as usize)
+        })?,
+    })
+}
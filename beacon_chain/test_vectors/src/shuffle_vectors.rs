@@ -0,0 +1,52 @@
+use super::check_eq;
+use super::hashing::canonical_hash;
+use super::parse;
+use super::vec_shuffle::shuffle;
+use super::yaml_rust::Yaml;
+
+/// Runs a test case for the `shuffle` test suite.
+///
+/// Each case has a `seed`, a list of integers as the `input` and the shuffled `output`.
+///
+/// A `seed` beginning with `0x` is hex and used as the seed directly. Otherwise the seed is a raw
+/// string, as per the vectors generated by sigp/shuffling_sandbox: an empty string is an empty
+/// seed and any other string is hashed to give the seed.
+pub fn run_case(case: &Yaml) -> Result<(), String> {
+    let seed = seed(parse::field(case, "seed")?)?;
+    let input = parse::as_list_of(parse::field(case, "input")?, parse::as_u64)?;
+    let output = parse::as_list_of(parse::field(case, "output")?, parse::as_u64)?;
+
+    let shuffled = shuffle(&seed, input).map_err(|e| format!("shuffle: {:?}", e))?;
+    check_eq("output", &output, &shuffled)
+}
+
+fn seed(yaml: &Yaml) -> Result<Vec<u8>, String> {
+    let s = parse::as_str(yaml)?;
+    if s.get(..2) == Some("0x") {
+        parse::as_bytes(yaml)
+    } else if s.is_empty() {
+        Ok(vec![])
+    } else {
+        Ok(canonical_hash(s.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::yaml_rust::YamlLoader;
+    use super::*;
+
+    #[test]
+    fn test_seed() {
+        let yaml = YamlLoader::load_from_str("['0x0102', '0x', '', 'abc', 0x01]")
+            .unwrap()
+            .remove(0);
+        let list = parse::as_list(&yaml).unwrap();
+
+        assert_eq!(seed(&list[0]), Ok(vec![1, 2]));
+        assert_eq!(seed(&list[1]), Ok(vec![]));
+        assert_eq!(seed(&list[2]), Ok(vec![]));
+        assert_eq!(seed(&list[3]), Ok(canonical_hash(b"abc")));
+        assert!(seed(&list[4]).is_err());
+    }
+}
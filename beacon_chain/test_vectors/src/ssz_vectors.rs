@@ -0,0 +1,65 @@
+use super::check_eq;
+use super::parse;
+use super::ssz::{decode_ssz_exact, try_ssz_encode, Decodable, Encodable};
+use super::yaml_rust::Yaml;
+use std::fmt::Debug;
+
+/// Runs a test case for the `ssz` test suite.
+///
+/// A valid case has the `type` of the value, the `value` and its `ssz` bytes. The value must encode
+/// to the bytes and the bytes must decode to the value.
+///
+/// A case with `valid: false` has no `value`, and its `ssz` bytes must fail to decode.
+pub fn run_case(case: &Yaml) -> Result<(), String> {
+    let type_name = parse::as_str(parse::field(case, "type")?)?;
+    let ssz = parse::as_bytes(parse::field(case, "ssz")?)?;
+    let valid = match parse::optional_field(case, "valid") {
+        Some(valid) => parse::as_bool(valid)?,
+        None => true,
+    };
+    let value = if valid {
+        Some(parse::field(case, "value")?)
+    } else {
+        None
+    };
+
+    match type_name {
+        "uint8" => check(value, &ssz, |v| parse::as_uint(v, 0xff).map(|n| n as u8)),
+        "uint16" => check(value, &ssz, |v| parse::as_uint(v, 0xffff).map(|n| n as u16)),
+        "uint32" => check(value, &ssz, |v| {
+            parse::as_uint(v, 0xffff_ffff).map(|n| n as u32)
+        }),
+        "uint64" => check(value, &ssz, parse::as_u64),
+        "bool" => check(value, &ssz, parse::as_bool),
+        "hash32" => check(value, &ssz, parse::as_hash),
+        "address" => check(value, &ssz, parse::as_address),
+        "bytes" => check(value, &ssz, parse::as_bytes),
+        "uint16_list" => check(value, &ssz, |v| {
+            parse::as_list_of(v, |n| parse::as_uint(n, 0xffff).map(|n| n as u16))
+        }),
+        "uint64_list" => check(value, &ssz, |v| parse::as_list_of(v, parse::as_u64)),
+        "hash32_list" => check(value, &ssz, |v| parse::as_list_of(v, parse::as_hash)),
+        _ => Err(format!("unknown type {:?}", type_name)),
+    }
+}
+
+/// Checks `ssz` against the value read by `read`, or that it fails to decode if there is no value.
+fn check<T, F>(value: Option<&Yaml>, ssz: &[u8], read: F) -> Result<(), String>
+where
+    T: Encodable + Decodable + PartialEq + Debug,
+    F: Fn(&Yaml) -> Result<T, String>,
+{
+    let decoded = decode_ssz_exact::<T>(ssz, "test vector");
+    match value {
+        Some(value) => {
+            let value = read(value)?;
+            let encoded = try_ssz_encode(&value).map_err(|e| format!("encode: {:?}", e))?;
+            check_eq("encode", &ssz.to_vec(), &encoded)?;
+            check_eq("decode", &Ok(value), &decoded)
+        }
+        None => match decoded {
+            Ok(value) => Err(format!("decode: expected an error, got {:?}", value)),
+            Err(_) => Ok(()),
+        },
+    }
+}
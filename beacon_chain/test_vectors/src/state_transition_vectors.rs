@@ -0,0 +1,81 @@
+use super::beacon_state_vectors;
+use super::check_eq;
+use super::parse;
+use super::state_transition::extend_active_state;
+use super::types::{ActiveState, BeaconBlock, SpecialRecord};
+use super::yaml_rust::Yaml;
+
+/// Runs a test case for the `state_transition` test suite.
+///
+/// The `function` of the case is either `extend_active_state` or one of the functions which
+/// transition a `BeaconState` (see `beacon_state_vectors::run_case`).
+pub fn run_case(case: &Yaml) -> Result<(), String> {
+    match parse::as_str(parse::field(case, "function")?)? {
+        "extend_active_state" => run_extend_active_state_case(case),
+        function => beacon_state_vectors::run_case(function, case),
+    }
+}
+
+/// Runs a test case for `extend_active_state`.
+///
+/// Each case has the `pre` active state, the `block` and its `block_hash`, and either the `post`
+/// active state or the name of the expected `StateTransitionError` as the `error`.
+///
+/// Active states have `recent_block_hashes`, a `randao_mix` and optionally `pending_specials`,
+/// blocks have a `randao_reveal` and optionally `specials`. Attestations are not supported.
+fn run_extend_active_state_case(case: &Yaml) -> Result<(), String> {
+    let pre = read_active_state(parse::field(case, "pre")?)?;
+    let block_yaml = parse::field(case, "block")?;
+    let block = BeaconBlock {
+        randao_reveal: parse::as_hash(parse::field(block_yaml, "randao_reveal")?)?,
+        specials: read_specials(block_yaml)?,
+        ..BeaconBlock::zero()
+    };
+    let block_hash = parse::as_hash(parse::field(case, "block_hash")?)?;
+
+    let result = extend_active_state(&pre, &block, &block_hash);
+
+    match parse::optional_field(case, "error") {
+        Some(error) => {
+            let error = parse::as_str(error)?;
+            match result {
+                Ok(post) => Err(format!("expected {}, got {:?}", error, post)),
+                Err(e) => check_eq("error", &error.to_string(), &format!("{:?}", e)),
+            }
+        }
+        None => {
+            let expected = read_active_state(parse::field(case, "post")?)?;
+            let post = result.map_err(|e| format!("unexpected error: {:?}", e))?;
+            check_eq("post", &expected, &post)
+        }
+    }
+}
+
+fn read_active_state(yaml: &Yaml) -> Result<ActiveState, String> {
+    Ok(ActiveState {
+        pending_attestations: vec![],
+        pending_specials: match parse::optional_field(yaml, "pending_specials") {
+            Some(specials) => parse::as_list_of(specials, read_special)?,
+            None => vec![],
+        },
+        recent_block_hashes: parse::as_list_of(
+            parse::field(yaml, "recent_block_hashes")?,
+            parse::as_hash,
+        )?,
+        randao_mix: parse::as_hash(parse::field(yaml, "randao_mix")?)?,
+    })
+}
+
+fn read_specials(yaml: &Yaml) -> Result<Vec<SpecialRecord>, String> {
+    match parse::optional_field(yaml, "specials") {
+        Some(specials) => parse::as_list_of(specials, read_special),
+        None => Ok(vec![]),
+    }
+}
+
+fn read_special(yaml: &Yaml) -> Result<SpecialRecord, String> {
+    Ok(SpecialRecord {
+        kind: parse::as_uint(parse::field(yaml, "kind")?, 0xff)? as u8,
+        data: parse::as_bytes(parse::field(yaml, "data")?)?,
+    })
+}
@@ -1,7 +1,8 @@
+use super::ssz::TreeHash;
 use super::Hash256;
 use super::{Attestation, SpecialRecord};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct ActiveState {
     pub pending_attestations: Vec<Attestation>,
     pub pending_specials: Vec<SpecialRecord>,
@@ -10,8 +11,48 @@ pub struct ActiveState {
 }
 
 impl ActiveState {
-    // TODO: implement this.
     pub fn canonical_root(&self) -> Hash256 {
-        Hash256::zero()
+        Hash256::from(&self.hash_tree_root()[..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    fn test_active_state_canonical_root() {
+        let a = ActiveState {
+            pending_attestations: vec![],
+            pending_specials: vec![],
+            recent_block_hashes: vec![Hash256::zero()],
+            randao_mix: Hash256::zero(),
+        };
+        let mut b = ActiveState {
+            pending_attestations: vec![],
+            pending_specials: vec![],
+            recent_block_hashes: vec![Hash256::zero()],
+            randao_mix: Hash256::zero(),
+        };
+        assert_eq!(a.canonical_root(), b.canonical_root());
+
+        b.recent_block_hashes.push(Hash256::zero());
+        assert!(a.canonical_root() != b.canonical_root());
+    }
+
+    #[test]
+    fn test_active_state_ssz_round_trip() {
+        let original = ActiveState {
+            pending_attestations: vec![Attestation::zero(), Attestation::zero()],
+            pending_specials: vec![SpecialRecord::randao_change(&[1, 2, 3])],
+            recent_block_hashes: vec![Hash256::from("a".as_bytes()), Hash256::zero()],
+            randao_mix: Hash256::from("mix".as_bytes()),
+        };
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = ActiveState::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
     }
 }
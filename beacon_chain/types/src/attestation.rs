@@ -1,6 +1,6 @@
 use super::attestation_data::SSZ_ATTESTION_DATA_LENGTH;
 use super::bls::{AggregateSignature, BLS_AGG_SIG_BYTE_SIZE};
-use super::ssz::{decode_ssz_list, Decodable, DecodeError, Encodable, SszStream, LENGTH_BYTES};
+use super::ssz::LENGTH_BYTES;
 use super::{AttestationData, Bitfield};
 
 pub const MIN_SSZ_ATTESTION_RECORD_LENGTH: usize = {
@@ -10,42 +10,15 @@ pub const MIN_SSZ_ATTESTION_RECORD_LENGTH: usize = {
     LENGTH_BYTES + BLS_AGG_SIG_BYTE_SIZE // aggregate sig
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct Attestation {
     pub data: AttestationData,
     pub participation_bitfield: Bitfield,
     pub custody_bitfield: Bitfield,
+    #[ssz(bls)]
     pub aggregate_sig: AggregateSignature,
 }
 
-impl Encodable for Attestation {
-    fn ssz_append(&self, s: &mut SszStream) {
-        s.append(&self.data);
-        s.append(&self.participation_bitfield);
-        s.append(&self.custody_bitfield);
-        s.append_vec(&self.aggregate_sig.as_bytes());
-    }
-}
-
-impl Decodable for Attestation {
-    fn ssz_decode(bytes: &[u8], i: usize) -> Result<(Self, usize), DecodeError> {
-        let (data, i) = AttestationData::ssz_decode(bytes, i)?;
-        let (participation_bitfield, i) = Bitfield::ssz_decode(bytes, i)?;
-        let (custody_bitfield, i) = Bitfield::ssz_decode(bytes, i)?;
-        let (agg_sig_bytes, i) = decode_ssz_list(bytes, i)?;
-        let aggregate_sig =
-            AggregateSignature::from_bytes(&agg_sig_bytes).map_err(|_| DecodeError::TooShort)?; // also could be TooLong
-
-        let attestation_record = Self {
-            data,
-            participation_bitfield,
-            custody_bitfield,
-            aggregate_sig,
-        };
-        Ok((attestation_record, i))
-    }
-}
-
 impl Attestation {
     pub fn zero() -> Self {
         Self {
@@ -59,7 +32,7 @@ impl Attestation {
 
 #[cfg(test)]
 mod tests {
-    use super::super::ssz::ssz_encode;
+    use super::super::ssz::{ssz_encode, Decodable, DecodeError, DecodeErrorKind, SszStream};
     use super::*;
 
     #[test]
@@ -84,4 +57,30 @@ mod tests {
 
         assert_eq!(original, decoded);
     }
+
+    #[test]
+    pub fn test_attestation_record_ssz_decode_invalid_signature() {
+        let original = Attestation::zero();
+        let mut ssz_stream = SszStream::new();
+        ssz_stream.append(&original.data);
+        ssz_stream.append(&original.participation_bitfield);
+        ssz_stream.append(&original.custody_bitfield);
+        let agg_sig_index = ssz_stream.drain().len();
+
+        let mut ssz_stream = SszStream::new();
+        ssz_stream.append(&original.data);
+        ssz_stream.append(&original.participation_bitfield);
+        ssz_stream.append(&original.custody_bitfield);
+        ssz_stream.append_vec(&[42_u8; 3]);
+        let ssz = ssz_stream.drain();
+
+        assert_eq!(
+            Attestation::ssz_decode(&ssz, 0),
+            Err(DecodeError::new(
+                DecodeErrorKind::InvalidBlsPoint,
+                "AggregateSignature",
+                agg_sig_index
+            ))
+        );
+    }
 }
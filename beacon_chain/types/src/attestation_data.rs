@@ -1,4 +1,4 @@
-use super::ssz::{Decodable, DecodeError, Encodable, SszStream};
+use super::ssz::{ssz_encode, TreeHash};
 use super::Hash256;
 
 pub const SSZ_ATTESTION_DATA_LENGTH: usize = {
@@ -12,7 +12,7 @@ pub const SSZ_ATTESTION_DATA_LENGTH: usize = {
     32 // justified_block_hash
 };
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, Encode, Decode, TreeHash)]
 pub struct AttestationData {
     pub slot: u64,
     pub shard: u64,
@@ -38,54 +38,22 @@ impl AttestationData {
         }
     }
 
-    // TODO: Implement this as a merkle root, once tree_ssz is implemented.
-    // https://github.com/sigp/lighthouse/issues/92
     pub fn canonical_root(&self) -> Hash256 {
-        Hash256::zero()
+        Hash256::from(&self.hash_tree_root()[..])
     }
-}
-
-impl Encodable for AttestationData {
-    fn ssz_append(&self, s: &mut SszStream) {
-        s.append(&self.slot);
-        s.append(&self.shard);
-        s.append(&self.beacon_block_hash);
-        s.append(&self.epoch_boundary_hash);
-        s.append(&self.shard_block_hash);
-        s.append(&self.latest_crosslink_hash);
-        s.append(&self.justified_slot);
-        s.append(&self.justified_block_hash);
-    }
-}
-
-impl Decodable for AttestationData {
-    fn ssz_decode(bytes: &[u8], i: usize) -> Result<(Self, usize), DecodeError> {
-        let (slot, i) = u64::ssz_decode(bytes, i)?;
-        let (shard, i) = u64::ssz_decode(bytes, i)?;
-        let (beacon_block_hash, i) = Hash256::ssz_decode(bytes, i)?;
-        let (epoch_boundary_hash, i) = Hash256::ssz_decode(bytes, i)?;
-        let (shard_block_hash, i) = Hash256::ssz_decode(bytes, i)?;
-        let (latest_crosslink_hash, i) = Hash256::ssz_decode(bytes, i)?;
-        let (justified_slot, i) = u64::ssz_decode(bytes, i)?;
-        let (justified_block_hash, i) = Hash256::ssz_decode(bytes, i)?;
 
-        let attestation_data = AttestationData {
-            slot,
-            shard,
-            beacon_block_hash,
-            epoch_boundary_hash,
-            shard_block_hash,
-            latest_crosslink_hash,
-            justified_slot,
-            justified_block_hash,
-        };
-        Ok((attestation_data, i))
+    /// Returns the message which is signed by the attesters of this data in some signature
+    /// domain.
+    pub fn signing_message(&self, domain: u64) -> Vec<u8> {
+        let mut message = self.canonical_root().to_vec();
+        message.append(&mut ssz_encode(&domain));
+        message
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::ssz::ssz_encode;
+    use super::super::ssz::{ssz_encode, Decodable};
     use super::*;
 
     #[test]
@@ -107,4 +75,15 @@ mod tests {
 
         assert_eq!(original, decoded);
     }
+
+    #[test]
+    pub fn test_attestation_data_canonical_root() {
+        let a = AttestationData::zero();
+        let mut b = AttestationData::zero();
+        b.slot = 1;
+
+        assert!(!a.canonical_root().is_zero());
+        assert_eq!(a.canonical_root(), AttestationData::zero().canonical_root());
+        assert!(a.canonical_root() != b.canonical_root());
+    }
 }
@@ -1,6 +1,7 @@
 use super::attestation::Attestation;
+use super::bls::{Signature, BLS_AGG_SIG_BYTE_SIZE};
 use super::special_record::SpecialRecord;
-use super::ssz::{Decodable, DecodeError, Encodable, SszStream};
+use super::ssz::{hash_tree_roots, ssz_encode, TreeHash, LENGTH_BYTES};
 use super::Hash256;
 
 pub const MIN_SSZ_BLOCK_LENGTH: usize = {
@@ -11,20 +12,34 @@ pub const MIN_SSZ_BLOCK_LENGTH: usize = {
     32 +                // active_state_root
     32 +                // crystallized_state_root
     4 +                 // attestations (assuming empty)
-    4 // specials (assuming empty)
+    4 +                 // specials (assuming empty)
+    LENGTH_BYTES + BLS_AGG_SIG_BYTE_SIZE // signature
 };
 pub const MAX_SSZ_BLOCK_LENGTH: usize = MIN_SSZ_BLOCK_LENGTH + (1 << 24);
 
-#[derive(Debug, PartialEq, Clone, Default)]
+/*
+ * The most elements of each list a block may hold. Blocks with longer lists are rejected when
+ * they are decoded, before any limit of the chain spec is checked.
+ */
+pub const MAX_ANCESTOR_HASHES: usize = 32;
+pub const MAX_ATTESTATIONS_PER_BLOCK: usize = 128;
+pub const MAX_SPECIALS_PER_BLOCK: usize = 64;
+
+#[derive(Debug, PartialEq, Clone, Encode, Decode)]
 pub struct BeaconBlock {
     pub slot: u64,
     pub randao_reveal: Hash256,
     pub pow_chain_reference: Hash256,
+    #[ssz(max_len = "MAX_ANCESTOR_HASHES")]
     pub ancestor_hashes: Vec<Hash256>,
     pub active_state_root: Hash256,
     pub crystallized_state_root: Hash256,
+    #[ssz(max_len = "MAX_ATTESTATIONS_PER_BLOCK")]
     pub attestations: Vec<Attestation>,
+    #[ssz(max_len = "MAX_SPECIALS_PER_BLOCK")]
     pub specials: Vec<SpecialRecord>,
+    #[ssz(bls)]
+    pub signature: Signature,
 }
 
 impl BeaconBlock {
@@ -38,6 +53,7 @@ impl BeaconBlock {
             crystallized_state_root: Hash256::zero(),
             attestations: vec![],
             specials: vec![],
+            signature: Signature::empty_signature(),
         }
     }
 
@@ -47,47 +63,51 @@ impl BeaconBlock {
     pub fn parent_hash(&self) -> Option<&Hash256> {
         self.ancestor_hashes.get(0)
     }
-}
 
-impl Encodable for BeaconBlock {
-    fn ssz_append(&self, s: &mut SszStream) {
-        s.append(&self.slot);
-        s.append(&self.randao_reveal);
-        s.append(&self.pow_chain_reference);
-        s.append_vec(&self.ancestor_hashes);
-        s.append(&self.active_state_root);
-        s.append(&self.crystallized_state_root);
-        s.append_vec(&self.attestations);
-        s.append_vec(&self.specials);
+    /// Returns the root of the block excluding its `signature`.
+    ///
+    /// This is the root which is signed by the proposer of the block.
+    pub fn proposal_root(&self) -> Hash256 {
+        Hash256::from(&hash_tree_roots(&self.unsigned_field_roots())[..])
+    }
+
+    /// Returns the message which must be signed by the proposer of the block in some signature
+    /// domain.
+    pub fn proposal_signing_message(&self, domain: u64) -> Vec<u8> {
+        let mut message = self.proposal_root().to_vec();
+        message.append(&mut ssz_encode(&domain));
+        message
+    }
+
+    fn unsigned_field_roots(&self) -> Vec<Vec<u8>> {
+        vec![
+            self.slot.hash_tree_root(),
+            self.randao_reveal.hash_tree_root(),
+            self.pow_chain_reference.hash_tree_root(),
+            self.ancestor_hashes.hash_tree_root(),
+            self.active_state_root.hash_tree_root(),
+            self.crystallized_state_root.hash_tree_root(),
+            self.attestations.hash_tree_root(),
+            self.specials.hash_tree_root(),
+        ]
     }
 }
 
-impl Decodable for BeaconBlock {
-    fn ssz_decode(bytes: &[u8], i: usize) -> Result<(Self, usize), DecodeError> {
-        let (slot, i) = u64::ssz_decode(bytes, i)?;
-        let (randao_reveal, i) = Hash256::ssz_decode(bytes, i)?;
-        let (pow_chain_reference, i) = Hash256::ssz_decode(bytes, i)?;
-        let (ancestor_hashes, i) = Decodable::ssz_decode(bytes, i)?;
-        let (active_state_root, i) = Hash256::ssz_decode(bytes, i)?;
-        let (crystallized_state_root, i) = Hash256::ssz_decode(bytes, i)?;
-        let (attestations, i) = Decodable::ssz_decode(bytes, i)?;
-        let (specials, i) = Decodable::ssz_decode(bytes, i)?;
-        let block = BeaconBlock {
-            slot,
-            randao_reveal,
-            pow_chain_reference,
-            ancestor_hashes,
-            active_state_root,
-            crystallized_state_root,
-            attestations,
-            specials,
-        };
-        Ok((block, i))
+/*
+ * Not derived: the roots of the unsigned fields are shared with `proposal_root`.
+ */
+impl TreeHash for BeaconBlock {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        let mut roots = self.unsigned_field_roots();
+        roots.push(self.signature.as_bytes()[..].hash_tree_root());
+        hash_tree_roots(&roots)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::bls::Keypair;
+    use super::super::ssz::{Decodable, DecodeError, DecodeErrorKind, SszStream};
     use super::*;
 
     #[test]
@@ -101,12 +121,14 @@ mod tests {
         assert!(b.crystallized_state_root.is_zero());
         assert_eq!(b.attestations.len(), 0);
         assert_eq!(b.specials.len(), 0);
+        assert_eq!(b.signature, Signature::empty_signature());
     }
 
     #[test]
     pub fn test_block_ssz_encode_decode() {
         let mut b = BeaconBlock::zero();
         b.ancestor_hashes = vec![Hash256::zero(); 32];
+        b.signature = Signature::new(&b.proposal_signing_message(2), &Keypair::random().sk);
 
         let mut ssz_stream = SszStream::new();
         ssz_stream.append(&b);
@@ -117,6 +139,18 @@ mod tests {
         assert_eq!(b, b_decoded);
     }
 
+    #[test]
+    pub fn test_block_ssz_decode_too_many_ancestors() {
+        let mut b = BeaconBlock::zero();
+        b.ancestor_hashes = vec![Hash256::zero(); MAX_ANCESTOR_HASHES + 1];
+        let ssz = ssz_encode(&b);
+
+        assert_eq!(
+            BeaconBlock::ssz_decode(&ssz, 0),
+            Err(DecodeError::new(DecodeErrorKind::ListTooLong, "list", 72))
+        );
+    }
+
     #[test]
     pub fn test_block_min_ssz_length() {
         let b = BeaconBlock::zero();
@@ -139,4 +173,29 @@ mod tests {
 
         assert_eq!(b.parent_hash().unwrap(), &Hash256::from("cats".as_bytes()));
     }
+
+    #[test]
+    pub fn test_block_proposal_root() {
+        let keypair = Keypair::random();
+        let mut b = BeaconBlock::zero();
+        let root = b.proposal_root();
+
+        /*
+         * The proposal root does not commit to the signature.
+         */
+        b.signature = Signature::new(&b.proposal_signing_message(2), &keypair.sk);
+        assert_eq!(b.proposal_root(), root);
+        assert!(b
+            .signature
+            .verify(&b.proposal_signing_message(2), &keypair.pk));
+        assert!(!b
+            .signature
+            .verify(&b.proposal_signing_message(3), &keypair.pk));
+
+        b.slot = 1;
+        assert_ne!(b.proposal_root(), root);
+        assert!(!b
+            .signature
+            .verify(&b.proposal_signing_message(2), &keypair.pk));
+    }
 }
@@ -7,8 +7,9 @@ use super::shard_reassignment_record::ShardReassignmentRecord;
 use super::validator_record::ValidatorRecord;
 use super::Hash256;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct BeaconState {
+    pub slot: u64,
     pub validator_registry: Vec<ValidatorRecord>,
     pub validator_registry_latest_change_slot: u64,
     pub validator_registry_exit_count: u64,
@@ -32,3 +33,100 @@ pub struct BeaconState {
     pub genesis_time: u64,
     pub fork_data: ForkData,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::super::{AttestationData, Bitfield};
+    use super::*;
+
+    fn empty_state() -> BeaconState {
+        BeaconState {
+            slot: 0,
+            validator_registry: vec![],
+            validator_registry_latest_change_slot: 0,
+            validator_registry_exit_count: 0,
+            validator_registry_delta_chain_tip: Hash256::zero(),
+            randao_mix: Hash256::zero(),
+            next_seed: Hash256::zero(),
+            shard_and_committee_for_slots: vec![],
+            persistent_committees: vec![],
+            persistent_committee_reassignments: vec![],
+            previous_justified_slot: 0,
+            justified_slot: 0,
+            justified_slot_bitfield: 0,
+            finalized_slot: 0,
+            latest_crosslinks: vec![],
+            latest_state_recalculation_slot: 0,
+            latest_block_hashes: vec![],
+            latest_penalized_exit_balances: vec![],
+            latest_attestations: vec![],
+            processed_pow_receipt_root: Hash256::zero(),
+            candidate_pow_receipt_roots: vec![],
+            genesis_time: 0,
+            fork_data: ForkData {
+                pre_fork_version: 0,
+                post_fork_version: 0,
+                fork_slot: 0,
+            },
+        }
+    }
+
+    #[test]
+    pub fn test_beacon_state_ssz_empty_length() {
+        /*
+         * 9 u64 fields, 4 hashes, 9 empty lists and 3 u64 fork data fields.
+         */
+        let ssz = ssz_encode(&empty_state());
+        assert_eq!(ssz.len(), 9 * 8 + 4 * 32 + 9 * 4 + 3 * 8);
+        assert!(ssz.iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    pub fn test_beacon_state_ssz_round_trip() {
+        let (validator, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+
+        let mut original = empty_state();
+        original.slot = 12;
+        original.validator_registry = vec![validator.clone(), validator];
+        original.validator_registry_latest_change_slot = 1;
+        original.validator_registry_exit_count = 2;
+        original.randao_mix = Hash256::from("randao".as_bytes());
+        original.shard_and_committee_for_slots = vec![
+            vec![ShardAndCommittee {
+                shard: 1,
+                committee: vec![0, 1],
+            }],
+            vec![],
+        ];
+        original.persistent_committees = vec![vec![3, 4], vec![5]];
+        original.persistent_committee_reassignments = vec![ShardReassignmentRecord {
+            validator_index: 1,
+            shard: 2,
+            slot: 3,
+        }];
+        original.justified_slot_bitfield = 0b101;
+        original.finalized_slot = 8;
+        original.latest_crosslinks = vec![CrosslinkRecord::zero()];
+        original.latest_block_hashes = vec![Hash256::from("block".as_bytes())];
+        original.latest_penalized_exit_balances = vec![32, 64];
+        original.latest_attestations = vec![PendingAttestationRecord {
+            data: AttestationData::zero(),
+            participation_bitfield: Bitfield::from_bytes(&[1]),
+            custody_bitfield: Bitfield::from_bytes(&[2]),
+            slot_included: 4,
+        }];
+        original.candidate_pow_receipt_roots = vec![CandidatePoWReceiptRootRecord {
+            candidate_pow_receipt_root: Hash256::from("receipt".as_bytes()),
+            votes: 3,
+        }];
+        original.genesis_time = 1_000;
+        original.fork_data.post_fork_version = 1;
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = BeaconState::ssz_decode(&ssz, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
+    }
+}
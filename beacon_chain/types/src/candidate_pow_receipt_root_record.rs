@@ -1,7 +1,27 @@
 use super::Hash256;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct CandidatePoWReceiptRootRecord {
     pub candidate_pow_receipt_root: Hash256,
     pub votes: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    pub fn test_candidate_pow_receipt_root_record_ssz_round_trip() {
+        let original = CandidatePoWReceiptRootRecord {
+            candidate_pow_receipt_root: Hash256::from("receipt".as_bytes()),
+            votes: 5,
+        };
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = CandidatePoWReceiptRootRecord::ssz_decode(&ssz, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, 40);
+    }
+}
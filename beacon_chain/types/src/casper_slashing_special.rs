@@ -0,0 +1,58 @@
+use super::bls::AggregateSignature;
+use super::AttestationData;
+
+/// A vote on some `AttestationData` by a set of validators, with their aggregate signature.
+#[derive(Debug, PartialEq, Clone, Encode, Decode, TreeHash)]
+pub struct SlashableVote {
+    pub aggregate_sig_indices: Vec<u64>,
+    pub data: AttestationData,
+    #[ssz(bls)]
+    pub aggregate_sig: AggregateSignature,
+}
+
+impl SlashableVote {
+    /// Returns the message signed by the validators of the vote in some signature domain, which
+    /// is the same as is signed for an `Attestation` of the same `data`.
+    pub fn signing_message(&self, domain: u64) -> Vec<u8> {
+        self.data.signing_message(domain)
+    }
+}
+
+/// The payload of a `SpecialRecord` of kind `CasperSlashing`.
+///
+/// Proves that the validators common to both votes have violated a Casper slashing condition.
+#[derive(Debug, PartialEq, Clone, Encode, Decode, TreeHash)]
+pub struct CasperSlashingSpecial {
+    pub vote_1: SlashableVote,
+    pub vote_2: SlashableVote,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    fn test_casper_slashing_special_ssz_round_trip() {
+        let mut vote_2 = SlashableVote {
+            aggregate_sig_indices: vec![1, 2, 3],
+            data: AttestationData::zero(),
+            aggregate_sig: AggregateSignature::new(),
+        };
+        vote_2.data.slot = 12;
+        let original = CasperSlashingSpecial {
+            vote_1: SlashableVote {
+                aggregate_sig_indices: vec![],
+                data: AttestationData::zero(),
+                aggregate_sig: AggregateSignature::new(),
+            },
+            vote_2,
+        };
+
+        let bytes = ssz_encode(&original);
+        let (decoded, i) = CasperSlashingSpecial::ssz_decode(&bytes, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, bytes.len());
+    }
+}
@@ -1,6 +1,6 @@
 use super::Hash256;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Encode, Decode, TreeHash)]
 pub struct CrosslinkRecord {
     pub slot: u64,
     pub shard_block_hash: Hash256,
@@ -15,3 +15,24 @@ impl CrosslinkRecord {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    fn test_crosslink_record_ssz_round_trip() {
+        let original = CrosslinkRecord {
+            slot: 42,
+            shard_block_hash: Hash256::from("shard".as_bytes()),
+        };
+
+        let ssz = ssz_encode(&original);
+        assert_eq!(ssz.len(), 8 + 32);
+
+        let (decoded, i) = CrosslinkRecord::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
+    }
+}
@@ -1,9 +1,11 @@
 use super::crosslink_record::CrosslinkRecord;
+use super::fork_data::ForkData;
 use super::shard_and_committee::ShardAndCommittee;
+use super::ssz::TreeHash;
 use super::validator_record::ValidatorRecord;
 use super::Hash256;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct CrystallizedState {
     pub validator_set_change_slot: u64,
     pub validators: Vec<ValidatorRecord>,
@@ -11,6 +13,7 @@ pub struct CrystallizedState {
     pub last_state_recalculation_slot: u64,
     pub last_finalized_slot: u64,
     pub last_justified_slot: u64,
+    pub previous_justified_slot: u64,
     pub justified_streak: u64,
     pub shard_and_committee_for_slots: Vec<Vec<ShardAndCommittee>>,
     pub deposits_penalized_in_period: Vec<u32>,
@@ -21,8 +24,54 @@ pub struct CrystallizedState {
 }
 
 impl CrystallizedState {
-    // TODO: implement this.
     pub fn canonical_root(&self) -> Hash256 {
-        Hash256::zero()
+        Hash256::from(&self.hash_tree_root()[..])
+    }
+
+    /// Returns the fork versions of the state as `ForkData`.
+    pub fn fork_data(&self) -> ForkData {
+        ForkData {
+            pre_fork_version: u64::from(self.pre_fork_version),
+            post_fork_version: u64::from(self.post_fork_version),
+            fork_slot: u64::from(self.fork_slot_number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    fn test_crystallized_state_ssz_round_trip() {
+        let (validator, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+        let original = CrystallizedState {
+            validator_set_change_slot: 1,
+            validators: vec![validator.clone(), validator],
+            crosslinks: vec![CrosslinkRecord::zero()],
+            last_state_recalculation_slot: 2,
+            last_finalized_slot: 3,
+            last_justified_slot: 4,
+            previous_justified_slot: 11,
+            justified_streak: 5,
+            shard_and_committee_for_slots: vec![
+                vec![ShardAndCommittee {
+                    shard: 0,
+                    committee: vec![0, 1],
+                }],
+                vec![],
+            ],
+            deposits_penalized_in_period: vec![6, 7],
+            validator_set_delta_hash_chain: Hash256::from("delta".as_bytes()),
+            pre_fork_version: 8,
+            post_fork_version: 9,
+            fork_slot_number: 10,
+        };
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = CrystallizedState::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
     }
 }
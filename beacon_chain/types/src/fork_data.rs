@@ -1,6 +1,99 @@
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct ForkData {
     pub pre_fork_version: u64,
     pub post_fork_version: u64,
     pub fork_slot: u64,
 }
+
+impl ForkData {
+    /// Returns the fork version in effect at some slot.
+    pub fn version_at(&self, slot: u64) -> u64 {
+        if slot < self.fork_slot {
+            self.pre_fork_version
+        } else {
+            self.post_fork_version
+        }
+    }
+
+    /// Returns the signature domain of `domain_type` at some slot.
+    ///
+    /// The domain includes the fork version, so a signature made prior to a fork is not valid
+    /// after it. Returns `None` if either the fork version or `domain_type` does not fit in 32
+    /// bits, as the domain could then collide with that of some other version or type.
+    pub fn domain(&self, slot: u64, domain_type: u64) -> Option<u64> {
+        let version = self.version_at(slot);
+        if version >> 32 != 0 || domain_type >> 32 != 0 {
+            return None;
+        }
+        Some((version << 32) | domain_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    pub fn test_fork_data_ssz_encode() {
+        let fork_data = ForkData {
+            pre_fork_version: 1,
+            post_fork_version: 2,
+            fork_slot: 258,
+        };
+
+        let ssz = ssz_encode(&fork_data);
+        assert_eq!(
+            ssz,
+            vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 1, 2]
+        );
+
+        let (decoded, i) = ForkData::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(decoded, fork_data);
+        assert_eq!(i, 24);
+    }
+
+    #[test]
+    pub fn test_fork_data_version_at() {
+        let fork_data = ForkData {
+            pre_fork_version: 1,
+            post_fork_version: 2,
+            fork_slot: 10,
+        };
+
+        assert_eq!(fork_data.version_at(0), 1);
+        assert_eq!(fork_data.version_at(9), 1);
+        assert_eq!(fork_data.version_at(10), 2);
+        assert_eq!(fork_data.version_at(11), 2);
+    }
+
+    #[test]
+    pub fn test_fork_data_domain() {
+        let fork_data = ForkData {
+            pre_fork_version: 1,
+            post_fork_version: 2,
+            fork_slot: 10,
+        };
+
+        assert_eq!(fork_data.domain(9, 3), Some((1 << 32) + 3));
+        assert_eq!(fork_data.domain(10, 3), Some((2 << 32) + 3));
+        assert_ne!(fork_data.domain(9, 3), fork_data.domain(9, 2));
+    }
+
+    #[test]
+    pub fn test_fork_data_domain_out_of_range() {
+        let fork_data = ForkData {
+            pre_fork_version: 0xffff_ffff,
+            post_fork_version: 1 << 32,
+            fork_slot: 10,
+        };
+
+        assert_eq!(fork_data.domain(9, 3), Some(0xffff_ffff_0000_0003));
+        assert_eq!(
+            fork_data.domain(9, 0xffff_ffff),
+            Some(0xffff_ffff_ffff_ffff)
+        );
+        assert_eq!(fork_data.domain(9, 1 << 32), None);
+        assert_eq!(fork_data.domain(10, 3), None);
+    }
+}
@@ -2,18 +2,22 @@ extern crate bls;
 extern crate boolean_bitfield;
 extern crate ethereum_types;
 extern crate ssz;
+#[macro_use]
+extern crate ssz_derive;
 
 pub mod active_state;
-pub mod attestation_data;
 pub mod attestation;
+pub mod attestation_data;
 pub mod beacon_block;
 pub mod beacon_state;
 pub mod candidate_pow_receipt_root_record;
-pub mod chain_config;
+pub mod casper_slashing_special;
 pub mod crosslink_record;
 pub mod crystallized_state;
 pub mod fork_data;
+pub mod logout_special;
 pub mod pending_attestation_record;
+pub mod randao_change_special;
 pub mod shard_and_committee;
 pub mod shard_reassignment_record;
 pub mod special_record;
@@ -24,17 +28,19 @@ use self::ethereum_types::{H160, H256, U256};
 use std::collections::HashMap;
 
 pub use active_state::ActiveState;
-pub use attestation_data::AttestationData;
 pub use attestation::Attestation;
+pub use attestation_data::AttestationData;
 pub use beacon_block::BeaconBlock;
 pub use beacon_state::BeaconState;
-pub use chain_config::ChainConfig;
+pub use casper_slashing_special::{CasperSlashingSpecial, SlashableVote};
 pub use crosslink_record::CrosslinkRecord;
 pub use crystallized_state::CrystallizedState;
 pub use fork_data::ForkData;
+pub use logout_special::LogoutSpecial;
 pub use pending_attestation_record::PendingAttestationRecord;
+pub use randao_change_special::RandaoChangeSpecial;
 pub use shard_and_committee::ShardAndCommittee;
-pub use special_record::{SpecialRecord, SpecialRecordKind};
+pub use special_record::{SpecialRecord, SpecialRecordKind, SpecialRecordPayload};
 pub use validator_record::{ValidatorRecord, ValidatorStatus};
 pub use validator_registration::ValidatorRegistration;
 
@@ -0,0 +1,53 @@
+use super::bls::Signature;
+use super::ssz::ssz_encode;
+
+/// The message which is signed by a validator to log out, prior to the signature domain.
+pub const LOGOUT_MESSAGE: &[u8] = b"LOGOUT";
+
+/// The payload of a `SpecialRecord` of kind `Logout`.
+#[derive(Debug, PartialEq, Clone, Encode, Decode, TreeHash)]
+pub struct LogoutSpecial {
+    pub validator_index: u64,
+    #[ssz(bls)]
+    pub signature: Signature,
+}
+
+impl LogoutSpecial {
+    /// Returns the message which must be signed by the validator for a logout in some signature
+    /// domain.
+    pub fn signing_message(domain: u64) -> Vec<u8> {
+        let mut message = LOGOUT_MESSAGE.to_vec();
+        message.append(&mut ssz_encode(&domain));
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bls::Keypair;
+    use super::super::ssz::Decodable;
+    use super::*;
+
+    #[test]
+    fn test_logout_special_ssz_round_trip() {
+        let keypair = Keypair::random();
+        let original = LogoutSpecial {
+            validator_index: 42,
+            signature: Signature::new(&LogoutSpecial::signing_message(1), &keypair.sk),
+        };
+
+        let bytes = ssz_encode(&original);
+        let (decoded, i) = LogoutSpecial::ssz_decode(&bytes, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, bytes.len());
+    }
+
+    #[test]
+    fn test_logout_special_signing_message() {
+        assert_eq!(
+            LogoutSpecial::signing_message(258),
+            vec![76, 79, 71, 79, 85, 84, 0, 0, 0, 0, 0, 0, 1, 2]
+        );
+    }
+}
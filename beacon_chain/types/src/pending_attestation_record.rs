@@ -1,9 +1,31 @@
 use super::{AttestationData, Bitfield};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct PendingAttestationRecord {
     pub data: AttestationData,
     pub participation_bitfield: Bitfield,
     pub custody_bitfield: Bitfield,
     pub slot_included: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    pub fn test_pending_attestation_record_ssz_round_trip() {
+        let original = PendingAttestationRecord {
+            data: AttestationData::zero(),
+            participation_bitfield: Bitfield::from_bytes(&[17; 4]),
+            custody_bitfield: Bitfield::from_bytes(&[18; 2]),
+            slot_included: 9,
+        };
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = PendingAttestationRecord::ssz_decode(&ssz, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
+    }
+}
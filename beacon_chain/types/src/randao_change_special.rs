@@ -0,0 +1,84 @@
+use super::bls::Signature;
+use super::ssz::ssz_encode;
+use super::Hash256;
+
+/// The message which is signed by a proposer to change their randao commitment, prior to the
+/// proposer index, commitment and signature domain.
+pub const RANDAO_CHANGE_MESSAGE: &[u8] = b"RANDAO_CHANGE";
+
+/// The payload of a `SpecialRecord` of kind `RandaoChange`.
+///
+/// Replaces the `randao_commitment` of the proposer of the block which includes it. The change
+/// must be signed by the proposer.
+#[derive(Debug, PartialEq, Clone, Encode, Decode, TreeHash)]
+pub struct RandaoChangeSpecial {
+    pub proposer_index: u64,
+    pub randao_commitment: Hash256,
+    #[ssz(bls)]
+    pub signature: Signature,
+}
+
+impl RandaoChangeSpecial {
+    /// Returns the message which must be signed by the proposer for this change in some
+    /// signature domain.
+    pub fn signing_message(&self, domain: u64) -> Vec<u8> {
+        let mut message = RANDAO_CHANGE_MESSAGE.to_vec();
+        message.append(&mut ssz_encode(&self.proposer_index));
+        message.extend_from_slice(&self.randao_commitment);
+        message.append(&mut ssz_encode(&domain));
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bls::Keypair;
+    use super::super::ssz::Decodable;
+    use super::*;
+
+    #[test]
+    fn test_randao_change_special_ssz_round_trip() {
+        let keypair = Keypair::random();
+        let mut original = RandaoChangeSpecial {
+            proposer_index: 7,
+            randao_commitment: Hash256::from("commitment".as_bytes()),
+            signature: Signature::empty_signature(),
+        };
+        original.signature = Signature::new(&original.signing_message(3), &keypair.sk);
+
+        let bytes = ssz_encode(&original);
+        assert_eq!(
+            bytes.len(),
+            8 + 32 + 4 + original.signature.as_bytes().len()
+        );
+        let (decoded, i) = RandaoChangeSpecial::ssz_decode(&bytes, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, bytes.len());
+    }
+
+    #[test]
+    fn test_randao_change_special_signing_message() {
+        let change = RandaoChangeSpecial {
+            proposer_index: 1,
+            randao_commitment: Hash256::from("commitment".as_bytes()),
+            signature: Signature::empty_signature(),
+        };
+
+        let message = change.signing_message(258);
+        assert_eq!(&message[..13], RANDAO_CHANGE_MESSAGE);
+        assert_eq!(&message[13..21], &[0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(&message[21..53], &change.randao_commitment[..]);
+        assert_eq!(&message[53..], &[0, 0, 0, 0, 0, 0, 1, 2]);
+
+        /*
+         * The message commits to the proposer and the commitment.
+         */
+        let mut other = change.clone();
+        other.randao_commitment = Hash256::zero();
+        assert_ne!(other.signing_message(258), message);
+        other = change.clone();
+        other.proposer_index = 2;
+        assert_ne!(other.signing_message(258), message);
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Encode, Decode, TreeHash)]
 pub struct ShardAndCommittee {
     pub shard: u16,
     pub committee: Vec<usize>,
@@ -17,6 +17,7 @@ impl ShardAndCommittee {
 
 #[cfg(test)]
 mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
     use super::*;
 
     #[test]
@@ -25,4 +26,17 @@ mod tests {
         assert_eq!(s.shard, 0);
         assert_eq!(s.committee.len(), 0);
     }
+
+    #[test]
+    fn test_shard_and_committee_ssz_round_trip() {
+        let original = ShardAndCommittee {
+            shard: 7,
+            committee: vec![1, 2, 3],
+        };
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = ShardAndCommittee::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
+    }
 }
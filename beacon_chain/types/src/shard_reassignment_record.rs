@@ -1,6 +1,27 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct ShardReassignmentRecord {
     pub validator_index: u64,
     pub shard: u64,
     pub slot: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::ssz::{ssz_encode, Decodable};
+    use super::*;
+
+    #[test]
+    pub fn test_shard_reassignment_record_ssz_round_trip() {
+        let original = ShardReassignmentRecord {
+            validator_index: 7,
+            shard: 3,
+            slot: 42,
+        };
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = ShardReassignmentRecord::ssz_decode(&ssz, 0).unwrap();
+
+        assert_eq!(original, decoded);
+        assert_eq!(i, 24);
+    }
+}
@@ -1,4 +1,5 @@
-use super::ssz::{Decodable, DecodeError, Encodable, SszStream};
+use super::ssz::{decode_ssz_exact, hash_tree_roots, DecodeError, DecodeErrorKind, TreeHash};
+use super::{CasperSlashingSpecial, LogoutSpecial, RandaoChangeSpecial};
 
 /// The value of the "type" field of SpecialRecord.
 ///
@@ -10,8 +11,18 @@ pub enum SpecialRecordKind {
     RandaoChange = 2,
 }
 
-/// The structure used in the `BeaconBlock.specials` field.
+/// The decoded `data` of a `SpecialRecord`.
+///
+/// A `CasperSlashingSpecial` is boxed, as it is much larger than the other payloads.
 #[derive(Debug, PartialEq, Clone)]
+pub enum SpecialRecordPayload {
+    Logout(LogoutSpecial),
+    CasperSlashing(Box<CasperSlashingSpecial>),
+    RandaoChange(RandaoChangeSpecial),
+}
+
+/// The structure used in the `BeaconBlock.specials` field.
+#[derive(Debug, PartialEq, Clone, Encode, Decode)]
 pub struct SpecialRecord {
     pub kind: u8,
     pub data: Vec<u8>,
@@ -54,25 +65,47 @@ impl SpecialRecord {
             _ => None,
         }
     }
-}
 
-impl Encodable for SpecialRecord {
-    fn ssz_append(&self, s: &mut SszStream) {
-        s.append(&self.kind);
-        s.append_vec(&self.data);
+    /// Decode `self.data` as the payload of the kind of `self`.
+    ///
+    /// Returns an error if `self.kind` is an unknown value or if `self.data` is not exactly one
+    /// payload of that kind.
+    pub fn decode_payload(&self) -> Result<SpecialRecordPayload, DecodeError> {
+        match self.resolve_kind() {
+            Some(SpecialRecordKind::Logout) => {
+                decode_ssz_exact(&self.data, "LogoutSpecial").map(SpecialRecordPayload::Logout)
+            }
+            Some(SpecialRecordKind::CasperSlashing) => {
+                decode_ssz_exact(&self.data, "CasperSlashingSpecial")
+                    .map(|slashing| SpecialRecordPayload::CasperSlashing(Box::new(slashing)))
+            }
+            Some(SpecialRecordKind::RandaoChange) => {
+                decode_ssz_exact(&self.data, "RandaoChangeSpecial")
+                    .map(SpecialRecordPayload::RandaoChange)
+            }
+            None => Err(DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                "SpecialRecordKind",
+                0,
+            )),
+        }
     }
 }
 
-impl Decodable for SpecialRecord {
-    fn ssz_decode(bytes: &[u8], i: usize) -> Result<(Self, usize), DecodeError> {
-        let (kind, i) = u8::ssz_decode(bytes, i)?;
-        let (data, i) = Decodable::ssz_decode(bytes, i)?;
-        Ok((SpecialRecord { kind, data }, i))
+/*
+ * Not derived: `data` is hashed as a byte string, rather than as a list of `u8`.
+ */
+impl TreeHash for SpecialRecord {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        hash_tree_roots(&[self.kind.hash_tree_root(), self.data[..].hash_tree_root()])
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::bls::Signature;
+    use super::super::ssz::{ssz_encode, Decodable, SszStream};
+    use super::super::Hash256;
     use super::*;
 
     #[test]
@@ -129,4 +162,58 @@ mod tests {
         };
         assert_eq!(s.resolve_kind(), None);
     }
+
+    #[test]
+    pub fn test_special_record_decode_payload() {
+        let payload = RandaoChangeSpecial {
+            proposer_index: 3,
+            randao_commitment: Hash256::from("commitment".as_bytes()),
+            signature: Signature::empty_signature(),
+        };
+        let s = SpecialRecord::randao_change(&ssz_encode(&payload));
+        assert_eq!(
+            s.decode_payload(),
+            Ok(SpecialRecordPayload::RandaoChange(payload))
+        );
+
+        /*
+         * The payload must match the kind of the record.
+         */
+        let s = SpecialRecord::logout(&s.data);
+        assert!(s.decode_payload().is_err());
+
+        let s = SpecialRecord {
+            kind: 88,
+            data: vec![],
+        };
+        assert_eq!(
+            s.decode_payload(),
+            Err(DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                "SpecialRecordKind",
+                0
+            ))
+        );
+    }
+
+    #[test]
+    pub fn test_special_record_decode_payload_trailing_bytes() {
+        let payload = RandaoChangeSpecial {
+            proposer_index: 3,
+            randao_commitment: Hash256::zero(),
+            signature: Signature::empty_signature(),
+        };
+        let mut data = ssz_encode(&payload);
+        let payload_len = data.len();
+        data.push(0);
+        let s = SpecialRecord::randao_change(&data);
+        assert_eq!(
+            s.decode_payload(),
+            Err(DecodeError::new(
+                DecodeErrorKind::TrailingBytes,
+                "RandaoChangeSpecial",
+                payload_len
+            ))
+        );
+    }
 }
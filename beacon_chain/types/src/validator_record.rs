@@ -1,4 +1,5 @@
 use super::bls::{Keypair, PublicKey};
+use super::ssz::{Decodable, DecodeError, DecodeErrorKind, Encodable, SszStream, TreeHash};
 use super::{Address, Hash256};
 use std::convert;
 
@@ -12,22 +13,59 @@ pub enum ValidatorStatus {
     Penalized,
 }
 
-impl convert::From<u8> for ValidatorStatus {
-    fn from(status: u8) -> Self {
+impl ValidatorStatus {
+    /// Returns the status with the given byte, or `None` if it is not a known status.
+    pub fn from_u8(status: u8) -> Option<Self> {
         match status {
-            0 => ValidatorStatus::PendingActivation,
-            1 => ValidatorStatus::Active,
-            2 => ValidatorStatus::PendingExit,
-            3 => ValidatorStatus::PendingWithdraw,
-            5 => ValidatorStatus::Withdrawn,
-            127 => ValidatorStatus::Penalized,
-            _ => unreachable!(),
+            0 => Some(ValidatorStatus::PendingActivation),
+            1 => Some(ValidatorStatus::Active),
+            2 => Some(ValidatorStatus::PendingExit),
+            3 => Some(ValidatorStatus::PendingWithdraw),
+            5 => Some(ValidatorStatus::Withdrawn),
+            127 => Some(ValidatorStatus::Penalized),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl convert::From<ValidatorStatus> for u8 {
+    fn from(status: ValidatorStatus) -> Self {
+        match status {
+            ValidatorStatus::PendingActivation => 0,
+            ValidatorStatus::Active => 1,
+            ValidatorStatus::PendingExit => 2,
+            ValidatorStatus::PendingWithdraw => 3,
+            ValidatorStatus::Withdrawn => 5,
+            ValidatorStatus::Penalized => 127,
+        }
+    }
+}
+
+impl Encodable for ValidatorStatus {
+    fn ssz_append(&self, s: &mut SszStream) {
+        s.append(&u8::from(self.clone()));
+    }
+}
+
+impl Decodable for ValidatorStatus {
+    fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+        let (status, i) = u8::ssz_decode(bytes, index)?;
+        let status = ValidatorStatus::from_u8(status).ok_or_else(|| {
+            DecodeError::new(DecodeErrorKind::InvalidValue, "ValidatorStatus", index)
+        })?;
+        Ok((status, i))
+    }
+}
+
+impl TreeHash for ValidatorStatus {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        u8::from(self.clone()).hash_tree_root()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TreeHash)]
 pub struct ValidatorRecord {
+    #[ssz(bls)]
     pub pubkey: PublicKey,
     pub withdrawal_shard: u16,
     pub withdrawal_address: Address,
@@ -52,7 +90,7 @@ impl ValidatorRecord {
             randao_commitment: Hash256::zero(),
             randao_last_change: 0,
             balance: 0,
-            status: From::from(0),
+            status: ValidatorStatus::PendingActivation,
             exit_slot: 0,
         };
         (s, keypair)
@@ -65,6 +103,7 @@ impl ValidatorRecord {
 
 #[cfg(test)]
 mod tests {
+    use super::super::ssz::ssz_encode;
     use super::*;
 
     #[test]
@@ -75,7 +114,53 @@ mod tests {
         assert!(v.randao_commitment.is_zero());
         assert_eq!(v.randao_last_change, 0);
         assert_eq!(v.balance, 0);
-        assert_eq!(v.status, From::from(0));
+        assert_eq!(v.status, ValidatorStatus::PendingActivation);
         assert_eq!(v.exit_slot, 0);
     }
+
+    #[test]
+    fn test_validator_status_u8_round_trip() {
+        for status in &[0, 1, 2, 3, 5, 127] {
+            assert_eq!(
+                u8::from(ValidatorStatus::from_u8(*status).unwrap()),
+                *status
+            );
+        }
+        assert_eq!(ValidatorStatus::from_u8(4), None);
+    }
+
+    #[test]
+    fn test_validator_record_ssz_round_trip() {
+        let (mut original, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+        original.withdrawal_shard = 3;
+        original.withdrawal_address = Address::from([7; 20]);
+        original.randao_commitment = Hash256::from("randao".as_bytes());
+        original.balance = 32;
+        original.status = ValidatorStatus::Penalized;
+        original.exit_slot = 100;
+
+        let ssz = ssz_encode(&original);
+        let (decoded, i) = ValidatorRecord::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(original, decoded);
+        assert_eq!(i, ssz.len());
+    }
+
+    #[test]
+    fn test_validator_status_ssz_encode() {
+        assert_eq!(ssz_encode(&ValidatorStatus::PendingActivation), vec![0]);
+        assert_eq!(ssz_encode(&ValidatorStatus::Withdrawn), vec![5]);
+        assert_eq!(ssz_encode(&ValidatorStatus::Penalized), vec![127]);
+    }
+
+    #[test]
+    fn test_validator_status_ssz_decode_unknown() {
+        assert_eq!(
+            ValidatorStatus::ssz_decode(&[0, 4], 1),
+            Err(DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                "ValidatorStatus",
+                1
+            ))
+        );
+    }
 }
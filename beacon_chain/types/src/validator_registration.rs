@@ -2,12 +2,14 @@ use super::{Address, Hash256};
 use bls::{create_proof_of_possession, Keypair, PublicKey, Signature};
 
 /// The information gathered from the PoW chain validator registration function.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, TreeHash)]
 pub struct ValidatorRegistration {
+    #[ssz(bls)]
     pub pubkey: PublicKey,
     pub withdrawal_shard: u16,
     pub withdrawal_address: Address,
     pub randao_commitment: Hash256,
+    #[ssz(bls)]
     pub proof_of_possession: Signature,
 }
 
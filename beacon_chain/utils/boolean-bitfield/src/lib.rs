@@ -1,6 +1,9 @@
 extern crate bit_vec;
 extern crate ssz;
 
+#[cfg(test)]
+extern crate proptest;
+
 use bit_vec::BitVec;
 
 use std::cmp;
@@ -120,11 +123,17 @@ impl ssz::Encodable for BooleanBitfield {
     }
 }
 
+impl ssz::TreeHash for BooleanBitfield {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        ssz::TreeHash::hash_tree_root(&self.to_bytes()[..])
+    }
+}
+
 impl ssz::Decodable for BooleanBitfield {
     fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), ssz::DecodeError> {
         let len = ssz::decode::decode_length(bytes, index, ssz::LENGTH_BYTES)?;
-        if (ssz::LENGTH_BYTES + len) > bytes.len() {
-            return Err(ssz::DecodeError::TooShort);
+        if (index + ssz::LENGTH_BYTES + len) > bytes.len() {
+            return Err(ssz::DecodeError::too_short("BooleanBitfield", index));
         }
 
         if len == 0 {
@@ -152,6 +161,8 @@ impl ssz::Decodable for BooleanBitfield {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
     use ssz::{ssz_encode, Decodable, SszStream};
 
     #[test]
@@ -352,6 +363,24 @@ mod tests {
         assert_eq!(field, expected);
     }
 
+    #[test]
+    fn test_ssz_decode_at_index() {
+        let encoded = vec![42, 0, 0, 0, 2, 225, 192];
+        let (field, i): (BooleanBitfield, usize) = ssz::decode_ssz(&encoded, 1).unwrap();
+        assert_eq!(field, create_test_bitfield());
+        assert_eq!(i, 7);
+
+        /*
+         * The length prefix fits within the input when counted from the start of the input,
+         * but not when counted from the index.
+         */
+        let encoded = vec![42, 0, 0, 0, 2, 225];
+        assert_eq!(
+            BooleanBitfield::ssz_decode(&encoded, 1),
+            Err(ssz::DecodeError::too_short("BooleanBitfield", 1))
+        );
+    }
+
     #[test]
     fn test_ssz_round_trip() {
         let original = BooleanBitfield::from_bytes(&vec![18; 12][..]);
@@ -359,4 +388,45 @@ mod tests {
         let (decoded, _) = BooleanBitfield::ssz_decode(&ssz, 0).unwrap();
         assert_eq!(original, decoded);
     }
+
+    proptest! {
+        #[test]
+        fn prop_ssz_round_trip(bytes in vec(any::<u8>(), 1..64)) {
+            let original = BooleanBitfield::from_bytes(&bytes);
+            let ssz = ssz_encode(&original);
+            prop_assert_eq!(
+                BooleanBitfield::ssz_decode(&ssz, 0),
+                Ok((original, ssz.len()))
+            );
+        }
+
+        #[test]
+        fn prop_ssz_decode_random_bytes(
+            bytes in vec(any::<u8>(), 0..16),
+            index in 0..16_usize
+        ) {
+            if let Ok((_, i)) = BooleanBitfield::ssz_decode(&bytes, index) {
+                prop_assert!(i <= bytes.len());
+            }
+        }
+
+        #[test]
+        fn prop_ssz_decode_short_length_prefix(
+            len in 1..8_u8,
+            data in vec(any::<u8>(), 0..8)
+        ) {
+            /*
+             * Random length prefixes are almost always too long, so also try some which are not.
+             */
+            let mut bytes = vec![0, 0, 0, len];
+            bytes.extend_from_slice(&data);
+            match BooleanBitfield::ssz_decode(&bytes, 0) {
+                Ok((field, i)) => {
+                    prop_assert_eq!(i, 4 + len as usize);
+                    prop_assert_eq!(field.to_bytes(), &bytes[4..i]);
+                }
+                Err(_) => prop_assert!(data.len() < len as usize),
+            }
+        }
+    }
 }
@@ -1,9 +1,46 @@
+use super::reader::SszReader;
 use super::LENGTH_BYTES;
 
-#[derive(Debug, PartialEq)]
-pub enum DecodeError {
+/// The reason some SSZ could not be decoded.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecodeErrorKind {
+    /// The input ended before the value was complete.
     TooShort,
-    TooLong,
+    /// The input continued after the value was complete.
+    TrailingBytes,
+    /// A list length prefix does not end on an element boundary.
+    InvalidLengthPrefix,
+    /// A list holds more elements than permitted.
+    ListTooLong,
+    /// The bytes are not a valid value for the type (e.g., an unknown enum discriminant).
+    InvalidValue,
+    /// The bytes are not a valid BLS public key or signature.
+    InvalidBlsPoint,
+}
+
+/// An error produced while decoding SSZ.
+///
+/// Records the type which could not be decoded and the index of the first byte of that type
+/// within the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DecodeError {
+    pub kind: DecodeErrorKind,
+    pub type_name: &'static str,
+    pub offset: usize,
+}
+
+impl DecodeError {
+    pub fn new(kind: DecodeErrorKind, type_name: &'static str, offset: usize) -> Self {
+        Self {
+            kind,
+            type_name,
+            offset,
+        }
+    }
+
+    pub fn too_short(type_name: &'static str, offset: usize) -> Self {
+        Self::new(DecodeErrorKind::TooShort, type_name, offset)
+    }
 }
 
 pub trait Decodable: Sized {
@@ -18,49 +55,59 @@ pub fn decode_ssz<T>(ssz_bytes: &[u8], index: usize) -> Result<(T, usize), Decod
 where
     T: Decodable,
 {
-    if index >= ssz_bytes.len() {
-        return Err(DecodeError::TooShort);
-    }
     T::ssz_decode(ssz_bytes, index)
 }
 
+/// Decode the given bytes as exactly one value of the given type.
+///
+/// Returns an error if any bytes remain after the value.
+pub fn decode_ssz_exact<T>(ssz_bytes: &[u8], type_name: &'static str) -> Result<T, DecodeError>
+where
+    T: Decodable,
+{
+    let (value, i) = T::ssz_decode(ssz_bytes, 0)?;
+    if i != ssz_bytes.len() {
+        return Err(DecodeError::new(
+            DecodeErrorKind::TrailingBytes,
+            type_name,
+            i,
+        ));
+    }
+    Ok(value)
+}
+
 /// Decode a vector (list) of encoded bytes.
 ///
-/// Each element in the list will be decoded and placed into the vector.
+/// Each element in the list will be decoded and placed into the vector. The number of elements is
+/// limited only by the length of the input (every element is at least one byte long), see
+/// `decode_ssz_list_max`.
 pub fn decode_ssz_list<T>(ssz_bytes: &[u8], index: usize) -> Result<(Vec<T>, usize), DecodeError>
 where
     T: Decodable,
 {
-    if index + LENGTH_BYTES > ssz_bytes.len() {
-        return Err(DecodeError::TooShort);
-    };
-
-    // get the length
-    let serialized_length = match decode_length(ssz_bytes, index, LENGTH_BYTES) {
-        Err(v) => return Err(v),
-        Ok(v) => v,
-    };
-
-    let final_len: usize = index + LENGTH_BYTES + serialized_length;
+    decode_ssz_list_max(ssz_bytes, index, ssz_bytes.len())
+}
 
-    if final_len > ssz_bytes.len() {
-        return Err(DecodeError::TooShort);
+/// Decode a list of at most `max_len` elements.
+///
+/// The elements are decoded one at a time, so no more than `max_len` elements are ever
+/// allocated regardless of the length prefix.
+pub fn decode_ssz_list_max<T>(
+    ssz_bytes: &[u8],
+    index: usize,
+    max_len: usize,
+) -> Result<(Vec<T>, usize), DecodeError>
+where
+    T: Decodable,
+{
+    if index + LENGTH_BYTES > ssz_bytes.len() {
+        return Err(DecodeError::too_short("list", index));
     };
 
-    let mut tmp_index = index + LENGTH_BYTES;
-    let mut res_vec: Vec<T> = Vec::new();
-
-    while tmp_index < final_len {
-        match T::ssz_decode(ssz_bytes, tmp_index) {
-            Err(v) => return Err(v),
-            Ok(v) => {
-                tmp_index = v.1;
-                res_vec.push(v.0);
-            }
-        };
-    }
+    let mut reader = SszReader::at(ssz_bytes, index);
+    let list = reader.read_list(max_len)?.collect::<Result<Vec<T>, _>>()?;
 
-    Ok((res_vec, final_len))
+    Ok((list, reader.index()))
 }
 
 /// Given some number of bytes, interpret the first four
@@ -72,7 +119,7 @@ pub fn decode_length(
     length_bytes: usize,
 ) -> Result<usize, DecodeError> {
     if bytes.len() < index + length_bytes {
-        return Err(DecodeError::TooShort);
+        return Err(DecodeError::too_short("length", index));
     };
     let mut len: usize = 0;
     for (i, byte) in bytes
@@ -148,7 +195,8 @@ mod tests {
                 0, 0, 0, 16, 0, 0, 0, 10, 0, 0, 0, 10, 0, 0, 0, 10, 0, 0, 0, 10,
             ],
             0,
-        ).unwrap();
+        )
+        .unwrap();
         assert_eq!(decoded.0, v);
         assert_eq!(decoded.1, 20);
 
@@ -160,7 +208,8 @@ mod tests {
                 10, 0, 0, 0, 0, 0, 0, 0, 10,
             ],
             0,
-        ).unwrap();
+        )
+        .unwrap();
         assert_eq!(decoded.0, v);
         assert_eq!(decoded.1, 36);
 
@@ -172,18 +221,57 @@ mod tests {
                 0, 0, 15, 0, 0, 0, 0, 0, 0, 0, 15, 0, 0, 0, 0, 0, 0, 0, 15,
             ],
             10,
-        ).unwrap();
+        )
+        .unwrap();
         assert_eq!(decoded.0, v);
         assert_eq!(decoded.1, 46);
 
         // Check that length > bytes throws error
         let decoded: Result<(Vec<usize>, usize), DecodeError> =
             decode_ssz_list(&vec![0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 15], 0);
-        assert_eq!(decoded, Err(DecodeError::TooShort));
+        assert_eq!(decoded, Err(DecodeError::too_short("list", 0)));
 
         // Check that incorrect index throws error
         let decoded: Result<(Vec<usize>, usize), DecodeError> =
             decode_ssz_list(&vec![0, 0, 0, 0, 0, 0, 0, 15], 16);
-        assert_eq!(decoded, Err(DecodeError::TooShort));
+        assert_eq!(decoded, Err(DecodeError::too_short("list", 16)));
+
+        // Check that a length which splits an element throws error
+        let decoded: Result<(Vec<u16>, usize), DecodeError> =
+            decode_ssz_list(&[0, 0, 0, 3, 0, 10, 0, 10], 0);
+        assert_eq!(
+            decoded,
+            Err(DecodeError::new(
+                DecodeErrorKind::InvalidLengthPrefix,
+                "list",
+                0
+            ))
+        );
+    }
+
+    #[test]
+    fn test_decode_ssz_list_max() {
+        let ssz = vec![0, 0, 0, 6, 0, 1, 0, 2, 0, 3];
+
+        let decoded: (Vec<u16>, usize) = decode_ssz_list_max(&ssz, 0, 3).unwrap();
+        assert_eq!(decoded, (vec![1, 2, 3], 10));
+
+        let decoded: Result<(Vec<u16>, usize), DecodeError> = decode_ssz_list_max(&ssz, 0, 2);
+        assert_eq!(
+            decoded,
+            Err(DecodeError::new(DecodeErrorKind::ListTooLong, "list", 0))
+        );
+    }
+
+    #[test]
+    fn test_decode_ssz_exact() {
+        let decoded: u16 = decode_ssz_exact(&[0, 10], "u16").unwrap();
+        assert_eq!(decoded, 10);
+
+        let decoded: Result<u16, DecodeError> = decode_ssz_exact(&[0, 10, 0], "u16");
+        assert_eq!(
+            decoded,
+            Err(DecodeError::new(DecodeErrorKind::TrailingBytes, "u16", 2))
+        );
     }
 }
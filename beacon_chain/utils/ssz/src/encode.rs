@@ -1,7 +1,23 @@
 use super::LENGTH_BYTES;
 
+/// The reason some value could not be SSZ encoded.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EncodeError {
+    /// A list is too long for its length to be represented by the length prefix.
+    ListTooLong,
+}
+
 pub trait Encodable {
     fn ssz_append(&self, s: &mut SszStream);
+
+    /// Append `self` to the stream, or return an error and leave the stream unchanged if `self`
+    /// cannot be encoded.
+    fn try_ssz_append(&self, s: &mut SszStream) -> Result<(), EncodeError>
+    where
+        Self: Sized,
+    {
+        s.try_append(self).map(|_| ())
+    }
 }
 
 /// Provides a buffer for appending ssz-encodable values.
@@ -9,15 +25,22 @@ pub trait Encodable {
 /// Use the `append()` fn to add a value to a list, then use
 /// the `drain()` method to consume the struct and return the
 /// ssz encoded bytes.
+///
+/// If some value cannot be encoded the stream records the error,
+/// which is returned by `try_append()` and `try_drain()`.
 #[derive(Default)]
 pub struct SszStream {
     buffer: Vec<u8>,
+    error: Option<EncodeError>,
 }
 
 impl SszStream {
     /// Create a new, empty stream for writing ssz values.
     pub fn new() -> Self {
-        SszStream { buffer: Vec::new() }
+        SszStream {
+            buffer: Vec::new(),
+            error: None,
+        }
     }
 
     /// Append some ssz encodable value to the stream.
@@ -29,14 +52,31 @@ impl SszStream {
         self
     }
 
+    /// Append some ssz encodable value to the stream, or return an
+    /// error and leave the stream unchanged if it cannot be encoded.
+    pub fn try_append<E>(&mut self, value: &E) -> Result<&mut Self, EncodeError>
+    where
+        E: Encodable,
+    {
+        let mut value_stream = SszStream::new();
+        value.ssz_append(&mut value_stream);
+        let ssz = value_stream.try_drain()?;
+        self.buffer.extend_from_slice(&ssz);
+        Ok(self)
+    }
+
     /// Append some ssz encoded bytes to the stream.
     ///
     /// The length of the supplied bytes will be concatenated
     /// to the stream before the supplied bytes.
     pub fn append_encoded_val(&mut self, vec: &[u8]) {
-        self.buffer
-            .extend_from_slice(&encode_length(vec.len(), LENGTH_BYTES));
-        self.buffer.extend_from_slice(&vec);
+        match try_encode_length(vec.len(), LENGTH_BYTES) {
+            Ok(length) => {
+                self.buffer.extend_from_slice(&length);
+                self.buffer.extend_from_slice(&vec);
+            }
+            Err(e) => self.fail(e),
+        }
     }
 
     /// Append some ssz encoded bytes to the stream without calculating length
@@ -58,12 +98,37 @@ impl SszStream {
         for item in vec {
             item.ssz_append(&mut list_stream);
         }
-        self.append_encoded_val(&list_stream.drain());
+        match list_stream.try_drain() {
+            Ok(ssz) => self.append_encoded_val(&ssz),
+            Err(e) => self.fail(e),
+        }
     }
 
     /// Consume the stream and return the underlying bytes.
+    ///
+    /// Panics if some value could not be encoded, use `try_drain()`
+    /// where the values may not be encodable.
     pub fn drain(self) -> Vec<u8> {
-        self.buffer
+        match self.try_drain() {
+            Ok(ssz) => ssz,
+            Err(e) => panic!("Unable to SSZ encode: {:?}", e),
+        }
+    }
+
+    /// Consume the stream and return the underlying bytes, or the
+    /// first error encountered while encoding.
+    pub fn try_drain(self) -> Result<Vec<u8>, EncodeError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.buffer),
+        }
+    }
+
+    /// Record that some value could not be encoded.
+    fn fail(&mut self, error: EncodeError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
     }
 }
 
@@ -71,15 +136,29 @@ impl SszStream {
 ///
 /// The ssz size prefix is 4 bytes, which is treated as a continuious
 /// 32bit big-endian integer.
+///
+/// Panics if `len` cannot be represented in `length_bytes` bytes.
 pub fn encode_length(len: usize, length_bytes: usize) -> Vec<u8> {
     assert!(length_bytes > 0); // For sanity
-    assert!((len as usize) < 2usize.pow(length_bytes as u32 * 8));
+    match try_encode_length(len, length_bytes) {
+        Ok(header) => header,
+        Err(e) => panic!("Unable to encode length {}: {:?}", len, e),
+    }
+}
+
+/// Encode some length into a ssz size prefix, or return an error if
+/// `len` cannot be represented in `length_bytes` bytes.
+pub fn try_encode_length(len: usize, length_bytes: usize) -> Result<Vec<u8>, EncodeError> {
+    let bits = (length_bytes * 8) as u32;
+    if len.checked_shr(bits).unwrap_or(0) != 0 {
+        return Err(EncodeError::ListTooLong);
+    }
     let mut header: Vec<u8> = vec![0; length_bytes];
     for (i, header_byte) in header.iter_mut().enumerate() {
-        let offset = (length_bytes - i - 1) * 8;
-        *header_byte = ((len >> offset) & 0xff) as u8;
+        let offset = ((length_bytes - i - 1) * 8) as u32;
+        *header_byte = (len.checked_shr(offset).unwrap_or(0) & 0xff) as u8;
     }
-    header
+    Ok(header)
 }
 
 #[cfg(test)]
@@ -110,6 +189,80 @@ mod tests {
         encode_length(4294967296, LENGTH_BYTES); // 2^(3*8)
     }
 
+    #[test]
+    fn test_try_encode_length() {
+        assert_eq!(
+            try_encode_length(4294967295, LENGTH_BYTES),
+            Ok(vec![255, 255, 255, 255])
+        );
+        assert_eq!(
+            try_encode_length(4294967296, LENGTH_BYTES),
+            Err(EncodeError::ListTooLong)
+        );
+        assert_eq!(try_encode_length(255, 1), Ok(vec![255]));
+        assert_eq!(try_encode_length(256, 1), Err(EncodeError::ListTooLong));
+        assert_eq!(try_encode_length(0, 0), Ok(vec![]));
+        assert_eq!(try_encode_length(1, 0), Err(EncodeError::ListTooLong));
+        assert_eq!(
+            try_encode_length(usize::max_value(), 16),
+            Ok([vec![0; 8], vec![255; 8]].concat())
+        );
+    }
+
+    /// A value which cannot be encoded, as if it held a list which is too long.
+    struct Unencodable;
+
+    impl Encodable for Unencodable {
+        fn ssz_append(&self, s: &mut SszStream) {
+            s.append(&1_u8);
+            s.fail(EncodeError::ListTooLong);
+        }
+    }
+
+    #[test]
+    fn test_try_append() {
+        let mut stream = SszStream::new();
+        stream.try_append(&1_u16).unwrap();
+        assert_eq!(
+            stream.try_append(&Unencodable).err(),
+            Some(EncodeError::ListTooLong)
+        );
+        assert_eq!(2_u8.try_ssz_append(&mut stream), Ok(()));
+        assert_eq!(
+            Unencodable.try_ssz_append(&mut stream),
+            Err(EncodeError::ListTooLong)
+        );
+
+        /*
+         * The stream is unchanged by the values which could not be encoded.
+         */
+        assert_eq!(stream.try_drain(), Ok(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_try_append_nested_list() {
+        /*
+         * An error within a list element is propagated to the outer stream.
+         */
+        let mut stream = SszStream::new();
+        assert_eq!(
+            stream.try_append(&vec![vec![Unencodable]]).err(),
+            Some(EncodeError::ListTooLong)
+        );
+
+        let mut stream = SszStream::new();
+        stream.append(&vec![Unencodable]);
+        assert_eq!(stream.try_drain(), Err(EncodeError::ListTooLong));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_panics_on_error() {
+        let mut stream = SszStream::new();
+        stream.append(&Unencodable);
+        stream.drain();
+    }
+
     #[test]
     fn test_encode_list() {
         let test_vec: Vec<u16> = vec![256; 12];
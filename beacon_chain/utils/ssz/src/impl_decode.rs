@@ -1,6 +1,6 @@
 use super::decode::decode_ssz_list;
-use super::ethereum_types::H256;
-use super::{Decodable, DecodeError};
+use super::ethereum_types::{H160, H256, U256};
+use super::{Decodable, DecodeError, DecodeErrorKind};
 
 macro_rules! impl_decodable_for_uint {
     ($type: ident, $bit_size: expr) => {
@@ -17,7 +17,7 @@ macro_rules! impl_decodable_for_uint {
                     }
                     Ok((result, end_bytes))
                 } else {
-                    Err(DecodeError::TooShort)
+                    Err(DecodeError::too_short(stringify!($type), index))
                 }
             }
         }
@@ -27,28 +27,166 @@ macro_rules! impl_decodable_for_uint {
 impl_decodable_for_uint!(u16, 16);
 impl_decodable_for_uint!(u32, 32);
 impl_decodable_for_uint!(u64, 64);
-impl_decodable_for_uint!(usize, 64);
+
+/*
+ * `usize` is always decoded from 64 bits, values which do not fit into the
+ * platform's `usize` are rejected.
+ */
+impl Decodable for usize {
+    fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+        let (value, i) =
+            u64::ssz_decode(bytes, index).map_err(|_| DecodeError::too_short("usize", index))?;
+        /*
+         * Casting back and forth only loses bits if the value does not fit.
+         */
+        if value as usize as u64 != value {
+            return Err(DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                "usize",
+                index,
+            ));
+        }
+        Ok((value as usize, i))
+    }
+}
+
+macro_rules! impl_decodable_for_int {
+    ($type: ident, $unsigned: ident) => {
+        impl Decodable for $type {
+            fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+                let (value, i) = $unsigned::ssz_decode(bytes, index)
+                    .map_err(|_| DecodeError::too_short(stringify!($type), index))?;
+                Ok((value as $type, i))
+            }
+        }
+    };
+}
+
+impl_decodable_for_int!(i8, u8);
+impl_decodable_for_int!(i16, u16);
+impl_decodable_for_int!(i32, u32);
+impl_decodable_for_int!(i64, u64);
 
 impl Decodable for u8 {
     fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
         if index >= bytes.len() {
-            Err(DecodeError::TooShort)
+            Err(DecodeError::too_short("u8", index))
         } else {
             Ok((bytes[index], index + 1))
         }
     }
 }
 
+impl Decodable for bool {
+    fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+        let (value, i) =
+            u8::ssz_decode(bytes, index).map_err(|_| DecodeError::too_short("bool", index))?;
+        match value {
+            0 => Ok((false, i)),
+            1 => Ok((true, i)),
+            _ => Err(DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                "bool",
+                index,
+            )),
+        }
+    }
+}
+
+/// Returns the `len` bytes starting at `index`.
+fn fixed_bytes<'a>(
+    bytes: &'a [u8],
+    index: usize,
+    len: usize,
+    type_name: &'static str,
+) -> Result<&'a [u8], DecodeError> {
+    if bytes.len() < len || bytes.len() - len < index {
+        Err(DecodeError::too_short(type_name, index))
+    } else {
+        Ok(&bytes[index..(index + len)])
+    }
+}
+
+macro_rules! impl_decodable_for_u8_array {
+    ($len: expr) => {
+        impl Decodable for [u8; $len] {
+            fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+                let slice = fixed_bytes(bytes, index, $len, stringify!([u8; $len]))?;
+                let mut array = [0; $len];
+                array.copy_from_slice(slice);
+                Ok((array, index + $len))
+            }
+        }
+    };
+}
+
+impl_decodable_for_u8_array!(4);
+impl_decodable_for_u8_array!(8);
+impl_decodable_for_u8_array!(20);
+impl_decodable_for_u8_array!(32);
+impl_decodable_for_u8_array!(48);
+impl_decodable_for_u8_array!(96);
+
+impl Decodable for H160 {
+    fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+        let slice = fixed_bytes(bytes, index, 20, "H160")?;
+        Ok((H160::from(slice), index + 20))
+    }
+}
+
 impl Decodable for H256 {
     fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
-        if bytes.len() < 32 || bytes.len() - 32 < index {
-            Err(DecodeError::TooShort)
-        } else {
-            Ok((H256::from(&bytes[index..(index + 32)]), index + 32))
+        let slice = fixed_bytes(bytes, index, 32, "H256")?;
+        Ok((H256::from(slice), index + 32))
+    }
+}
+
+impl Decodable for U256 {
+    fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+        let slice = fixed_bytes(bytes, index, 32, "U256")?;
+        Ok((U256::from(slice), index + 32))
+    }
+}
+
+impl<T> Decodable for Option<T>
+where
+    T: Decodable,
+{
+    fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+        let (selector, i) =
+            u8::ssz_decode(bytes, index).map_err(|_| DecodeError::too_short("Option", index))?;
+        match selector {
+            0 => Ok((None, i)),
+            1 => {
+                let (value, i) = T::ssz_decode(bytes, i)?;
+                Ok((Some(value), i))
+            }
+            _ => Err(DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                "Option",
+                index,
+            )),
         }
     }
 }
 
+macro_rules! impl_decodable_for_tuple {
+    ($($name: ident),+) => {
+        impl<$($name: Decodable),+> Decodable for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn ssz_decode(bytes: &[u8], index: usize) -> Result<(Self, usize), DecodeError> {
+                let i = index;
+                $(let ($name, i) = $name::ssz_decode(bytes, i)?;)+
+                Ok((($($name,)+), i))
+            }
+        }
+    };
+}
+
+impl_decodable_for_tuple!(A, B);
+impl_decodable_for_tuple!(A, B, C);
+impl_decodable_for_tuple!(A, B, C, D);
+
 impl<T> Decodable for Vec<T>
 where
     T: Decodable,
@@ -60,8 +198,11 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::super::{decode_ssz, DecodeError};
+    use super::super::{decode_ssz, ssz_encode, try_ssz_encode, DecodeError, Encodable};
     use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use std::fmt::Debug;
 
     #[test]
     fn test_ssz_decode_h256() {
@@ -87,7 +228,7 @@ mod tests {
          */
         let input = vec![42_u8; 31];
         let res = H256::ssz_decode(&input, 0);
-        assert_eq!(res, Err(DecodeError::TooShort));
+        assert_eq!(res, Err(DecodeError::too_short("H256", 0)));
     }
 
     #[test]
@@ -115,7 +256,7 @@ mod tests {
 
         let ssz = vec![1];
         let result: Result<(u16, usize), DecodeError> = decode_ssz(&ssz, 0);
-        assert_eq!(result, Err(DecodeError::TooShort));
+        assert_eq!(result, Err(DecodeError::too_short("u16", 0)));
     }
 
     #[test]
@@ -147,7 +288,7 @@ mod tests {
 
         let ssz = vec![0, 0, 1];
         let result: Result<(u32, usize), DecodeError> = decode_ssz(&ssz, 0);
-        assert_eq!(result, Err(DecodeError::TooShort));
+        assert_eq!(result, Err(DecodeError::too_short("u32", 0)));
     }
 
     #[test]
@@ -169,7 +310,7 @@ mod tests {
 
         let ssz = vec![0, 0, 0, 0, 0, 0, 0];
         let result: Result<(u64, usize), DecodeError> = decode_ssz(&ssz, 0);
-        assert_eq!(result, Err(DecodeError::TooShort));
+        assert_eq!(result, Err(DecodeError::too_short("u64", 0)));
     }
 
     #[test]
@@ -191,18 +332,208 @@ mod tests {
 
         let ssz = vec![0, 0, 0, 0, 0, 0, 1];
         let result: Result<(usize, usize), DecodeError> = decode_ssz(&ssz, 0);
-        assert_eq!(result, Err(DecodeError::TooShort));
+        assert_eq!(result, Err(DecodeError::too_short("usize", 0)));
     }
 
     #[test]
     fn test_decode_ssz_bounds() {
         let err: Result<(u16, usize), DecodeError> = decode_ssz(&vec![1], 2);
-        assert_eq!(err, Err(DecodeError::TooShort));
+        assert_eq!(err, Err(DecodeError::too_short("u16", 2)));
 
         let err: Result<(u16, usize), DecodeError> = decode_ssz(&vec![0, 0, 0, 0], 3);
-        assert_eq!(err, Err(DecodeError::TooShort));
+        assert_eq!(err, Err(DecodeError::too_short("u16", 3)));
 
         let result: u16 = decode_ssz(&vec![0, 0, 0, 0, 1], 3).unwrap().0;
         assert_eq!(result, 1);
     }
+
+    /// Encode `value`, then check it decodes to itself and consumes all bytes.
+    fn round_trip<T: Encodable + Decodable + PartialEq + Debug>(value: T) {
+        let ssz = ssz_encode(&value);
+        let (decoded, i) = T::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(i, ssz.len());
+    }
+
+    #[test]
+    fn test_ssz_decode_signed_ints() {
+        for x in &[i8::min_value(), -1, 0, 1, i8::max_value()] {
+            round_trip(*x);
+        }
+        for x in &[i16::min_value(), -1, 0, 1, i16::max_value()] {
+            round_trip(*x);
+        }
+        for x in &[i32::min_value(), -1, 0, 1, i32::max_value()] {
+            round_trip(*x);
+        }
+        for x in &[i64::min_value(), -1, 0, 1, i64::max_value()] {
+            round_trip(*x);
+        }
+
+        assert_eq!(decode_ssz(&[255, 254], 0), Ok((-2_i16, 2)));
+        assert_eq!(i8::ssz_decode(&[], 0), Err(DecodeError::too_short("i8", 0)));
+        assert_eq!(
+            i64::ssz_decode(&[0; 8], 1),
+            Err(DecodeError::too_short("i64", 1))
+        );
+    }
+
+    #[test]
+    fn test_ssz_decode_bool() {
+        assert_eq!(decode_ssz(&[0], 0), Ok((false, 1)));
+        assert_eq!(decode_ssz(&[1], 0), Ok((true, 1)));
+        assert_eq!(
+            bool::ssz_decode(&[1, 2], 1),
+            Err(DecodeError::new(DecodeErrorKind::InvalidValue, "bool", 1))
+        );
+        assert_eq!(
+            bool::ssz_decode(&[255], 0),
+            Err(DecodeError::new(DecodeErrorKind::InvalidValue, "bool", 0))
+        );
+        assert_eq!(
+            bool::ssz_decode(&[1], 1),
+            Err(DecodeError::too_short("bool", 1))
+        );
+    }
+
+    #[test]
+    fn test_ssz_decode_u8_array() {
+        round_trip([1_u8, 2, 3, 4]);
+        round_trip([255_u8; 8]);
+        round_trip([3_u8; 48]);
+        round_trip([9_u8; 96]);
+
+        assert_eq!(decode_ssz(&[0, 1, 2, 3, 4], 1), Ok(([1_u8, 2, 3, 4], 5)));
+        assert_eq!(
+            <[u8; 4]>::ssz_decode(&[0, 1, 2, 3, 4], 2),
+            Err(DecodeError::too_short("[u8; 4]", 2))
+        );
+        assert_eq!(
+            <[u8; 32]>::ssz_decode(&[0; 31], 0),
+            Err(DecodeError::too_short("[u8; 32]", 0))
+        );
+    }
+
+    #[test]
+    fn test_ssz_decode_h160() {
+        round_trip(H160::zero());
+        round_trip(H160::from([42; 20]));
+
+        let input = vec![7_u8; 21];
+        assert_eq!(H160::ssz_decode(&input, 1), Ok((H160::from([7; 20]), 21)));
+        assert_eq!(
+            H160::ssz_decode(&input, 2),
+            Err(DecodeError::too_short("H160", 2))
+        );
+    }
+
+    #[test]
+    fn test_ssz_decode_u256() {
+        round_trip(U256::zero());
+        round_trip(U256::from(1));
+        round_trip(U256::from(u64::max_value()));
+        round_trip(U256::max_value());
+
+        assert_eq!(
+            U256::ssz_decode(&[0; 31], 0),
+            Err(DecodeError::too_short("U256", 0))
+        );
+    }
+
+    #[test]
+    fn test_ssz_decode_option() {
+        round_trip(None::<u64>);
+        round_trip(Some(0_u64));
+        round_trip(Some(Some(true)));
+        round_trip(Some(vec![1_u16, 2]));
+
+        assert_eq!(
+            Option::<u8>::ssz_decode(&[2, 0], 0),
+            Err(DecodeError::new(DecodeErrorKind::InvalidValue, "Option", 0))
+        );
+        assert_eq!(
+            Option::<u16>::ssz_decode(&[1, 0], 0),
+            Err(DecodeError::too_short("u16", 1))
+        );
+        assert_eq!(
+            Option::<u8>::ssz_decode(&[], 0),
+            Err(DecodeError::too_short("Option", 0))
+        );
+    }
+
+    #[test]
+    fn test_ssz_decode_tuple() {
+        round_trip((1_u8, 2_u16));
+        round_trip((true, -1_i32, vec![3_u8]));
+        round_trip((H256::zero(), None::<u8>, 7_u64, H160::zero()));
+
+        assert_eq!(
+            <(u8, u16)>::ssz_decode(&[1, 0], 0),
+            Err(DecodeError::too_short("u16", 1))
+        );
+    }
+
+    /// Check that `value` survives a checked encode and decode.
+    fn check_round_trip<T>(value: T) -> Result<(), TestCaseError>
+    where
+        T: Encodable + Decodable + PartialEq + Debug,
+    {
+        let ssz = try_ssz_encode(&value).unwrap();
+        prop_assert_eq!(T::ssz_decode(&ssz, 0), Ok((value, ssz.len())));
+        Ok(())
+    }
+
+    /// Decode `T` from `bytes` at `index`, which must return rather than panic.
+    fn check_decode<T: Decodable>(bytes: &[u8], index: usize) -> Result<(), TestCaseError> {
+        if let Ok((_, i)) = T::ssz_decode(bytes, index) {
+            prop_assert!(i <= bytes.len());
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_int_round_trip(
+            a in any::<u8>(),
+            b in any::<u16>(),
+            c in any::<u32>(),
+            d in any::<u64>(),
+            e in any::<usize>(),
+            f in any::<i8>(),
+            g in any::<i16>(),
+            h in any::<i32>(),
+            j in any::<i64>(),
+            k in any::<bool>(),
+            l in any::<(u8, u64)>(),
+            m in any::<Option<u32>>()
+        ) {
+            check_round_trip(a)?;
+            check_round_trip(b)?;
+            check_round_trip(c)?;
+            check_round_trip(d)?;
+            check_round_trip(e)?;
+            check_round_trip(f)?;
+            check_round_trip(g)?;
+            check_round_trip(h)?;
+            check_round_trip(j)?;
+            check_round_trip(k)?;
+            check_round_trip(l)?;
+            check_round_trip(m)?;
+        }
+
+        #[test]
+        fn prop_decode_does_not_panic(bytes in vec(any::<u8>(), 0..64), index in 0..72_usize) {
+            check_decode::<u8>(&bytes, index)?;
+            check_decode::<u64>(&bytes, index)?;
+            check_decode::<usize>(&bytes, index)?;
+            check_decode::<i32>(&bytes, index)?;
+            check_decode::<bool>(&bytes, index)?;
+            check_decode::<H256>(&bytes, index)?;
+            check_decode::<U256>(&bytes, index)?;
+            check_decode::<Option<u16>>(&bytes, index)?;
+            check_decode::<(u8, u16)>(&bytes, index)?;
+            check_decode::<Vec<u8>>(&bytes, index)?;
+            check_decode::<Vec<Vec<u16>>>(&bytes, index)?;
+        }
+    }
 }
@@ -1,7 +1,7 @@
 extern crate bytes;
 
 use self::bytes::{BufMut, BytesMut};
-use super::ethereum_types::H256;
+use super::ethereum_types::{H160, H256, U256};
 use super::{Encodable, SszStream};
 
 /*
@@ -12,15 +12,7 @@ use super::{Encodable, SszStream};
 macro_rules! impl_encodable_for_uint {
     ($type: ident, $bit_size: expr) => {
         impl Encodable for $type {
-            #[allow(cast_lossless)]
             fn ssz_append(&self, s: &mut SszStream) {
-                // Ensure bit size is valid
-                assert!(
-                    (0 < $bit_size)
-                        && ($bit_size % 8 == 0)
-                        && (2_u128.pow($bit_size) > *self as u128)
-                );
-
                 // Serialize to bytes
                 let mut buf = BytesMut::with_capacity($bit_size / 8);
 
@@ -44,7 +36,67 @@ impl_encodable_for_uint!(u8, 8);
 impl_encodable_for_uint!(u16, 16);
 impl_encodable_for_uint!(u32, 32);
 impl_encodable_for_uint!(u64, 64);
-impl_encodable_for_uint!(usize, 64);
+
+/*
+ * `usize` is always encoded as 64 bits, regardless of the platform.
+ */
+impl Encodable for usize {
+    fn ssz_append(&self, s: &mut SszStream) {
+        s.append(&(*self as u64));
+    }
+}
+
+/*
+ * Signed integers are encoded as the big-endian two's complement of the
+ * same width.
+ */
+macro_rules! impl_encodable_for_int {
+    ($type: ident, $unsigned: ident) => {
+        impl Encodable for $type {
+            fn ssz_append(&self, s: &mut SszStream) {
+                s.append(&(*self as $unsigned));
+            }
+        }
+    };
+}
+
+impl_encodable_for_int!(i8, u8);
+impl_encodable_for_int!(i16, u16);
+impl_encodable_for_int!(i32, u32);
+impl_encodable_for_int!(i64, u64);
+
+impl Encodable for bool {
+    fn ssz_append(&self, s: &mut SszStream) {
+        s.append(&(*self as u8));
+    }
+}
+
+/*
+ * Fixed-length byte arrays and hashes are encoded as their raw bytes, without
+ * a length prefix.
+ */
+macro_rules! impl_encodable_for_u8_array {
+    ($len: expr) => {
+        impl Encodable for [u8; $len] {
+            fn ssz_append(&self, s: &mut SszStream) {
+                s.append_encoded_raw(&self[..]);
+            }
+        }
+    };
+}
+
+impl_encodable_for_u8_array!(4);
+impl_encodable_for_u8_array!(8);
+impl_encodable_for_u8_array!(20);
+impl_encodable_for_u8_array!(32);
+impl_encodable_for_u8_array!(48);
+impl_encodable_for_u8_array!(96);
+
+impl Encodable for H160 {
+    fn ssz_append(&self, s: &mut SszStream) {
+        s.append_encoded_raw(&self.to_vec());
+    }
+}
 
 impl Encodable for H256 {
     fn ssz_append(&self, s: &mut SszStream) {
@@ -52,8 +104,67 @@ impl Encodable for H256 {
     }
 }
 
+/*
+ * `U256` is encoded as 32 big-endian bytes.
+ */
+impl Encodable for U256 {
+    fn ssz_append(&self, s: &mut SszStream) {
+        let mut bytes = [0; 32];
+        self.to_big_endian(&mut bytes);
+        s.append_encoded_raw(&bytes);
+    }
+}
+
+/*
+ * An `Option` is encoded as a single byte which is `0` for `None` and `1`
+ * for `Some`, followed by the value if it is `Some`.
+ */
+impl<T> Encodable for Option<T>
+where
+    T: Encodable,
+{
+    fn ssz_append(&self, s: &mut SszStream) {
+        match self {
+            None => {
+                s.append(&0_u8);
+            }
+            Some(value) => {
+                s.append(&1_u8);
+                s.append(value);
+            }
+        }
+    }
+}
+
+/*
+ * Tuples are encoded as the concatenation of their elements.
+ */
+macro_rules! impl_encodable_for_tuple {
+    ($($name: ident: $index: tt),+) => {
+        impl<$($name: Encodable),+> Encodable for ($($name,)+) {
+            fn ssz_append(&self, s: &mut SszStream) {
+                $(s.append(&self.$index);)+
+            }
+        }
+    };
+}
+
+impl_encodable_for_tuple!(A: 0, B: 1);
+impl_encodable_for_tuple!(A: 0, B: 1, C: 2);
+impl_encodable_for_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+impl<T> Encodable for Vec<T>
+where
+    T: Encodable,
+{
+    fn ssz_append(&self, s: &mut SszStream) {
+        s.append_vec(self);
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::ssz_encode;
     use super::*;
 
     #[test]
@@ -64,6 +175,19 @@ mod tests {
         assert_eq!(ssz.drain(), vec![0; 32]);
     }
 
+    #[test]
+    fn test_ssz_encode_vec() {
+        let list: Vec<u16> = vec![1, 2];
+        let mut ssz = SszStream::new();
+        ssz.append(&list);
+        assert_eq!(ssz.drain(), vec![0, 0, 0, 4, 0, 1, 0, 2]);
+
+        let nested: Vec<Vec<u8>> = vec![vec![1], vec![]];
+        let mut ssz = SszStream::new();
+        ssz.append(&nested);
+        assert_eq!(ssz.drain(), vec![0, 0, 0, 9, 0, 0, 0, 1, 1, 0, 0, 0, 0]);
+    }
+
     #[test]
     fn test_ssz_encode_u8() {
         let x: u8 = 0;
@@ -183,4 +307,93 @@ mod tests {
         ssz.append(&x);
         assert_eq!(ssz.drain(), vec![255, 255, 255, 255, 255, 255, 255, 255]);
     }
+
+    #[test]
+    fn test_ssz_encode_signed_ints() {
+        assert_eq!(ssz_encode(&0_i8), vec![0]);
+        assert_eq!(ssz_encode(&-1_i8), vec![255]);
+        assert_eq!(ssz_encode(&i8::min_value()), vec![128]);
+        assert_eq!(ssz_encode(&i8::max_value()), vec![127]);
+
+        assert_eq!(ssz_encode(&-1_i16), vec![255, 255]);
+        assert_eq!(ssz_encode(&i16::min_value()), vec![128, 0]);
+        assert_eq!(ssz_encode(&i16::max_value()), vec![127, 255]);
+
+        assert_eq!(ssz_encode(&-2_i32), vec![255, 255, 255, 254]);
+        assert_eq!(ssz_encode(&i32::min_value()), vec![128, 0, 0, 0]);
+        assert_eq!(ssz_encode(&i32::max_value()), vec![127, 255, 255, 255]);
+
+        assert_eq!(ssz_encode(&1_i64), vec![0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(
+            ssz_encode(&i64::min_value()),
+            vec![128, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            ssz_encode(&i64::max_value()),
+            vec![127, 255, 255, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_ssz_encode_usize_max() {
+        /*
+         * `usize` is 64 bits, regardless of the platform.
+         */
+        assert_eq!(ssz_encode(&0_usize).len(), 8);
+        assert_eq!(
+            ssz_encode(&usize::max_value()),
+            ssz_encode(&(usize::max_value() as u64))
+        );
+    }
+
+    #[test]
+    fn test_ssz_encode_bool() {
+        assert_eq!(ssz_encode(&false), vec![0]);
+        assert_eq!(ssz_encode(&true), vec![1]);
+    }
+
+    #[test]
+    fn test_ssz_encode_u8_array() {
+        assert_eq!(ssz_encode(&[1_u8, 2, 3, 4]), vec![1, 2, 3, 4]);
+        assert_eq!(ssz_encode(&[7_u8; 96]), vec![7; 96]);
+    }
+
+    #[test]
+    fn test_ssz_encode_h160() {
+        assert_eq!(ssz_encode(&H160::zero()), vec![0; 20]);
+        assert_eq!(ssz_encode(&H160::from([42; 20])), vec![42; 20]);
+    }
+
+    #[test]
+    fn test_ssz_encode_u256() {
+        assert_eq!(ssz_encode(&U256::zero()), vec![0; 32]);
+
+        let mut expected = vec![0; 32];
+        expected[31] = 1;
+        assert_eq!(ssz_encode(&U256::from(1)), expected);
+
+        let mut expected = vec![0; 24];
+        expected.append(&mut vec![255; 8]);
+        assert_eq!(ssz_encode(&U256::from(u64::max_value())), expected);
+
+        assert_eq!(ssz_encode(&U256::max_value()), vec![255; 32]);
+    }
+
+    #[test]
+    fn test_ssz_encode_option() {
+        let none: Option<u16> = None;
+        assert_eq!(ssz_encode(&none), vec![0]);
+        assert_eq!(ssz_encode(&Some(258_u16)), vec![1, 1, 2]);
+        assert_eq!(ssz_encode(&Some(Some(false))), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn test_ssz_encode_tuple() {
+        assert_eq!(ssz_encode(&(1_u8, 2_u16)), vec![1, 0, 2]);
+        assert_eq!(
+            ssz_encode(&(true, 2_u8, vec![3_u8])),
+            vec![1, 2, 0, 0, 0, 1, 3]
+        );
+        assert_eq!(ssz_encode(&(1_u8, 2_u8, 3_u8, -1_i8)), vec![1, 2, 3, 255]);
+    }
 }
@@ -9,15 +9,26 @@
  */
 extern crate bytes;
 extern crate ethereum_types;
+extern crate hashing;
+
+#[cfg(test)]
+extern crate proptest;
 
 pub mod decode;
 pub mod encode;
+pub mod reader;
+pub mod tree_hash;
 
 mod impl_decode;
 mod impl_encode;
 
-pub use decode::{decode_ssz, decode_ssz_list, Decodable, DecodeError};
-pub use encode::{Encodable, SszStream};
+pub use decode::{
+    decode_ssz, decode_ssz_exact, decode_ssz_list, decode_ssz_list_max, Decodable, DecodeError,
+    DecodeErrorKind,
+};
+pub use encode::{Encodable, EncodeError, SszStream};
+pub use reader::{ReadElement, SszListIter, SszReader};
+pub use tree_hash::{hash_tree_roots, merkle_hash, TreeHash};
 
 pub const LENGTH_BYTES: usize = 4;
 pub const MAX_LIST_SIZE: usize = 1 << (4 * 8);
@@ -31,3 +42,14 @@ where
     ssz_stream.append(val);
     ssz_stream.drain()
 }
+
+/// Convenience function to SSZ encode an object, returning an error instead of panicking if it
+/// cannot be encoded.
+pub fn try_ssz_encode<T>(val: &T) -> Result<Vec<u8>, EncodeError>
+where
+    T: Encodable,
+{
+    let mut ssz_stream = SszStream::new();
+    ssz_stream.try_append(val)?;
+    ssz_stream.try_drain()
+}
@@ -0,0 +1,290 @@
+use super::decode::decode_length;
+use super::{Decodable, DecodeError, DecodeErrorKind, LENGTH_BYTES};
+use std::marker::PhantomData;
+
+/// Reads values in sequence from some SSZ bytes without copying them.
+///
+/// Lists may be read with `read_list`, which decodes each element only as it is requested and
+/// refuses to yield more than some maximum number of elements. This allows untrusted SSZ to be
+/// inspected without allocating in proportion to its length prefixes.
+///
+/// All offsets in errors are relative to the start of the slice given to the reader.
+#[derive(Debug, Clone)]
+pub struct SszReader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> SszReader<'a> {
+    /// Create a reader starting at the first byte of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self::at(bytes, 0)
+    }
+
+    /// Create a reader starting at `index` in `bytes`.
+    pub fn at(bytes: &'a [u8], index: usize) -> Self {
+        Self { bytes, index }
+    }
+
+    /// Return the index of the next byte to be read.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Return the number of bytes which have not yet been read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.index)
+    }
+
+    /// Decode the next value.
+    pub fn read<T: Decodable>(&mut self) -> Result<T, DecodeError> {
+        let (value, i) = T::ssz_decode(self.bytes, self.index)?;
+        self.index = i;
+        Ok(value)
+    }
+
+    /// Return the next `len` bytes without decoding them.
+    ///
+    /// `type_name` is used to describe the bytes if there are fewer than `len` remaining.
+    pub fn read_bytes(
+        &mut self,
+        len: usize,
+        type_name: &'static str,
+    ) -> Result<&'a [u8], DecodeError> {
+        if len > self.remaining() {
+            return Err(DecodeError::too_short(type_name, self.index));
+        }
+        let start = self.index;
+        self.index += len;
+        Ok(&self.bytes[start..self.index])
+    }
+
+    /// Return the bytes of the next list without decoding them.
+    ///
+    /// The length prefix is read but not included in the returned slice.
+    pub fn read_list_bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let len = decode_length(self.bytes, self.index, LENGTH_BYTES)?;
+        if len > self.remaining() - LENGTH_BYTES {
+            return Err(DecodeError::too_short("list", self.index));
+        }
+        self.index += LENGTH_BYTES;
+        self.read_bytes(len, "list")
+    }
+
+    /// Return an iterator which decodes the elements of the next list as they are requested.
+    ///
+    /// The iterator yields an error and then stops if an element cannot be decoded within the
+    /// bounds of the list, or if the list holds more than `max_len` elements.
+    pub fn read_list<T: Decodable>(
+        &mut self,
+        max_len: usize,
+    ) -> Result<SszListIter<'a, T>, DecodeError> {
+        self.read_list_with(max_len, SszReader::read::<T>)
+    }
+
+    /// As per `read_list`, but each element is read from the list by `read_element` rather than
+    /// being decoded.
+    ///
+    /// This allows the elements to be stepped over or borrowed from the SSZ (e.g., as slices)
+    /// without decoding them. `read_element` must advance the reader past the element it reads.
+    pub fn read_list_with<T, F>(
+        &mut self,
+        max_len: usize,
+        read_element: F,
+    ) -> Result<SszListIter<'a, T, F>, DecodeError>
+    where
+        F: FnMut(&mut SszReader<'a>) -> Result<T, DecodeError>,
+    {
+        let list_index = self.index;
+        let list_bytes = self.read_list_bytes()?;
+        let start = list_index + LENGTH_BYTES;
+
+        Ok(SszListIter {
+            reader: SszReader::at(&self.bytes[..start + list_bytes.len()], start),
+            read_element,
+            list_index,
+            max_len,
+            count: 0,
+            failed: false,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// A function which reads one element of a list, see `SszReader::read_list_with`.
+pub type ReadElement<'a, T> = fn(&mut SszReader<'a>) -> Result<T, DecodeError>;
+
+/// Lazily reads the elements of a list, see `SszReader::read_list`.
+#[derive(Debug, Clone)]
+pub struct SszListIter<'a, T, F = ReadElement<'a, T>> {
+    /// Reads from the SSZ bytes, truncated at the end of the list.
+    reader: SszReader<'a>,
+    read_element: F,
+    list_index: usize,
+    max_len: usize,
+    count: usize,
+    failed: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T, F> Iterator for SszListIter<'a, T, F>
+where
+    F: FnMut(&mut SszReader<'a>) -> Result<T, DecodeError>,
+{
+    type Item = Result<T, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.reader.remaining() == 0 {
+            return None;
+        }
+        if self.count == self.max_len {
+            self.failed = true;
+            return Some(Err(DecodeError::new(
+                DecodeErrorKind::ListTooLong,
+                "list",
+                self.list_index,
+            )));
+        }
+        match (self.read_element)(&mut self.reader) {
+            Ok(value) => {
+                self.count += 1;
+                Some(Ok(value))
+            }
+            /*
+             * An element which is cut short by the end of the list means the length prefix does
+             * not end on an element boundary.
+             */
+            Err(ref e) if e.kind == DecodeErrorKind::TooShort => {
+                self.failed = true;
+                Some(Err(DecodeError::new(
+                    DecodeErrorKind::InvalidLengthPrefix,
+                    "list",
+                    self.list_index,
+                )))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SszStream;
+    use super::*;
+
+    fn list_ssz(values: &[u16]) -> Vec<u8> {
+        let mut ssz_stream = SszStream::new();
+        ssz_stream.append(&42_u8);
+        ssz_stream.append_vec(values);
+        ssz_stream.append(&7_u8);
+        ssz_stream.drain()
+    }
+
+    #[test]
+    fn test_read_values() {
+        let ssz = list_ssz(&[1, 2]);
+        let mut reader = SszReader::new(&ssz);
+
+        assert_eq!(reader.read::<u8>(), Ok(42));
+        assert_eq!(reader.index(), 1);
+        assert_eq!(reader.read_list_bytes(), Ok(&[0, 1, 0, 2][..]));
+        assert_eq!(reader.read::<u8>(), Ok(7));
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(reader.read::<u8>(), Err(DecodeError::too_short("u8", 10)));
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        let ssz = vec![1, 2, 3];
+        let mut reader = SszReader::at(&ssz, 1);
+
+        assert_eq!(
+            reader.read_bytes(3, "thing"),
+            Err(DecodeError::too_short("thing", 1))
+        );
+        assert_eq!(reader.read_bytes(2, "thing"), Ok(&[2, 3][..]));
+        assert_eq!(reader.read_bytes(0, "thing"), Ok(&[][..]));
+    }
+
+    #[test]
+    fn test_read_list() {
+        let ssz = list_ssz(&[1, 2, 3]);
+        let mut reader = SszReader::at(&ssz, 1);
+
+        let list: Result<Vec<u16>, DecodeError> = reader.read_list(3).unwrap().collect();
+        assert_eq!(list, Ok(vec![1, 2, 3]));
+        assert_eq!(reader.read::<u8>(), Ok(7));
+    }
+
+    #[test]
+    fn test_read_list_too_long() {
+        let ssz = list_ssz(&[1, 2, 3]);
+        let mut reader = SszReader::at(&ssz, 1);
+
+        let mut list = reader.read_list::<u16>(2).unwrap();
+        assert_eq!(list.next(), Some(Ok(1)));
+        assert_eq!(list.next(), Some(Ok(2)));
+        assert_eq!(
+            list.next(),
+            Some(Err(DecodeError::new(
+                DecodeErrorKind::ListTooLong,
+                "list",
+                1
+            )))
+        );
+        assert_eq!(list.next(), None);
+
+        /*
+         * The reader has moved past the list regardless.
+         */
+        assert_eq!(reader.read::<u8>(), Ok(7));
+    }
+
+    #[test]
+    fn test_read_list_element_exceeds_list() {
+        /*
+         * A list of three bytes cannot hold two `u16`s, the second must not be read from the
+         * bytes following the list.
+         */
+        let ssz = vec![0, 0, 0, 3, 0, 1, 0, 2];
+        let mut reader = SszReader::new(&ssz);
+
+        let list: Result<Vec<u16>, DecodeError> = reader.read_list(10).unwrap().collect();
+        assert_eq!(
+            list,
+            Err(DecodeError::new(
+                DecodeErrorKind::InvalidLengthPrefix,
+                "list",
+                0
+            ))
+        );
+    }
+
+    #[test]
+    fn test_read_list_huge_length_prefix() {
+        let ssz = vec![255, 255, 255, 255, 0, 1];
+        let mut reader = SszReader::new(&ssz);
+
+        assert_eq!(
+            reader.read_list::<u16>(10).err(),
+            Some(DecodeError::too_short("list", 0))
+        );
+        assert_eq!(reader.index(), 0);
+    }
+
+    #[test]
+    fn test_read_list_with() {
+        let ssz = list_ssz(&[1, 2, 3]);
+        let mut reader = SszReader::at(&ssz, 1);
+
+        let list: Result<Vec<&[u8]>, DecodeError> = reader
+            .read_list_with(3, |reader| reader.read_bytes(2, "element"))
+            .unwrap()
+            .collect();
+        assert_eq!(list, Ok(vec![&[0, 1][..], &[0, 2][..], &[0, 3][..]]));
+        assert_eq!(reader.read::<u8>(), Ok(7));
+    }
+}
@@ -0,0 +1,204 @@
+use super::ethereum_types::{H160, H256};
+use super::hashing::canonical_hash;
+use super::ssz_encode;
+
+/// The size of the chunks which are hashed together at the base of the merkle tree.
+pub const SSZ_CHUNK_SIZE: usize = 128;
+/// The size of the hash returned by `hash_tree_root`.
+pub const HASH_SIZE: usize = 32;
+
+/// Provides the "tree hash" of some object, as defined in the `simpleserialize` spec.
+///
+/// - Unsigned integers, hashes and addresses are represented by their serialized bytes.
+/// - Byte slices (e.g., public keys and signatures) are hashed.
+/// - Lists are merkle hashed (see `merkle_hash`).
+/// - Containers are the hash of the concatenated tree hashes of their fields.
+pub trait TreeHash {
+    fn hash_tree_root(&self) -> Vec<u8>;
+}
+
+/// Returns the 32 byte merkle root of the given list of values, mixed with the length of the
+/// list.
+///
+/// The values are concatenated (each value is zero-padded to a power-of-two length if required)
+/// and split into `SSZ_CHUNK_SIZE` chunks which are then hashed pair-wise until a single root
+/// remains. The length of the list is hashed with the root so that lists with equal data but
+/// different lengths (e.g., trailing zeros) do not share a root.
+pub fn merkle_hash(list: &[Vec<u8>]) -> Vec<u8> {
+    /*
+     * Concatenate the list into a single blob, padded to a multiple of the chunk size.
+     */
+    let mut data: Vec<u8> = vec![];
+    for item in list {
+        data.extend_from_slice(item);
+        let padded_len = item.len().next_power_of_two();
+        data.resize(data.len() + padded_len - item.len(), 0);
+    }
+    let remainder = data.len() % SSZ_CHUNK_SIZE;
+    if remainder != 0 {
+        data.resize(data.len() + SSZ_CHUNK_SIZE - remainder, 0);
+    }
+
+    /*
+     * Merkleise the chunks, padding any odd layer with a zero chunk.
+     */
+    let mut chunks: Vec<Vec<u8>> = data.chunks(SSZ_CHUNK_SIZE).map(|c| c.to_vec()).collect();
+    while chunks.len() > 1 {
+        if chunks.len() % 2 == 1 {
+            chunks.push(vec![0; SSZ_CHUNK_SIZE]);
+        }
+        chunks = chunks
+            .chunks(2)
+            .map(|pair| canonical_hash(&[&pair[0][..], &pair[1][..]].concat()))
+            .collect();
+    }
+    let root = chunks.pop().unwrap_or_else(|| vec![0; HASH_SIZE]);
+
+    /*
+     * Mix in the length of the list, as a 32 byte big-endian integer.
+     */
+    let mut length_bytes = vec![0; HASH_SIZE - 8];
+    length_bytes.append(&mut ssz_encode(&(list.len() as u64)));
+
+    canonical_hash(&[&root[..], &length_bytes[..]].concat())
+}
+
+/// Returns the hash of the concatenation of the given tree hash roots.
+///
+/// This is the tree hash of a container whose fields have the given roots.
+pub fn hash_tree_roots(roots: &[Vec<u8>]) -> Vec<u8> {
+    canonical_hash(&roots.concat())
+}
+
+macro_rules! impl_tree_hash_for_uint {
+    ($type: ident) => {
+        impl TreeHash for $type {
+            fn hash_tree_root(&self) -> Vec<u8> {
+                ssz_encode(self)
+            }
+        }
+    };
+}
+
+impl_tree_hash_for_uint!(u8);
+impl_tree_hash_for_uint!(u16);
+impl_tree_hash_for_uint!(u32);
+impl_tree_hash_for_uint!(u64);
+impl_tree_hash_for_uint!(usize);
+
+impl TreeHash for H256 {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl TreeHash for H160 {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl TreeHash for [u8] {
+    fn hash_tree_root(&self) -> Vec<u8> {
+        canonical_hash(self)
+    }
+}
+
+impl<T> TreeHash for Vec<T>
+where
+    T: TreeHash,
+{
+    fn hash_tree_root(&self) -> Vec<u8> {
+        let roots: Vec<Vec<u8>> = self.iter().map(|item| item.hash_tree_root()).collect();
+        merkle_hash(&roots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_hash_uints() {
+        assert_eq!(1_u8.hash_tree_root(), vec![1]);
+        assert_eq!(1_u16.hash_tree_root(), vec![0, 1]);
+        assert_eq!(1_u32.hash_tree_root(), vec![0, 0, 0, 1]);
+        assert_eq!(1_u64.hash_tree_root(), vec![0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(1_usize.hash_tree_root(), vec![0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_tree_hash_hashes() {
+        let h = H256::from([42; 32]);
+        assert_eq!(h.hash_tree_root(), vec![42; 32]);
+
+        let a = H160::from([42; 20]);
+        assert_eq!(a.hash_tree_root(), vec![42; 20]);
+    }
+
+    #[test]
+    fn test_tree_hash_bytes() {
+        let bytes = vec![1, 2, 3];
+        assert_eq!(bytes[..].hash_tree_root(), canonical_hash(&bytes));
+    }
+
+    #[test]
+    fn test_merkle_hash_empty() {
+        let expected = canonical_hash(&vec![0; 64]);
+        assert_eq!(merkle_hash(&[]), expected);
+        assert_eq!(Vec::<u64>::new().hash_tree_root(), expected);
+    }
+
+    #[test]
+    fn test_merkle_hash_single_chunk() {
+        let list = vec![vec![1; 32], vec![2; 32]];
+
+        let mut chunk = vec![1; 32];
+        chunk.append(&mut vec![2; 32]);
+        chunk.append(&mut vec![0; 64]);
+        let mut length = vec![0; 31];
+        length.push(2);
+        let expected = canonical_hash(&[chunk, length].concat());
+
+        assert_eq!(merkle_hash(&list), expected);
+    }
+
+    #[test]
+    fn test_merkle_hash_multiple_chunks() {
+        let list: Vec<Vec<u8>> = (0..5).map(|i| vec![i; 32]).collect();
+
+        let first: Vec<u8> = (0..4).flat_map(|i| vec![i; 32]).collect();
+        let mut second = vec![4; 32];
+        second.append(&mut vec![0; 96]);
+        let root = canonical_hash(&[first, second].concat());
+        let mut length = vec![0; 31];
+        length.push(5);
+        let expected = canonical_hash(&[root, length].concat());
+
+        assert_eq!(merkle_hash(&list), expected);
+    }
+
+    #[test]
+    fn test_merkle_hash_pads_items() {
+        /*
+         * Items are padded to a power of two, so a three byte item occupies four bytes.
+         */
+        let list = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let padded = vec![vec![1, 2, 3, 0], vec![4, 5, 6, 0]];
+        assert_eq!(merkle_hash(&list), merkle_hash(&padded));
+    }
+
+    #[test]
+    fn test_merkle_hash_mixes_length() {
+        let a = vec![vec![0; 32]];
+        let b = vec![vec![0; 32], vec![0; 32]];
+        assert!(merkle_hash(&a) != merkle_hash(&b));
+    }
+
+    #[test]
+    fn test_tree_hash_vec() {
+        let list: Vec<u64> = vec![1, 2, 3];
+        let roots: Vec<Vec<u8>> = list.iter().map(|x| x.hash_tree_root()).collect();
+        assert_eq!(list.hash_tree_root(), merkle_hash(&roots));
+    }
+}
@@ -0,0 +1,215 @@
+/*
+ * Provides `#[derive(Encode, Decode, TreeHash)]` for the `ssz` crate.
+ *
+ * Fields are encoded in the order they are declared. `Vec<T>` fields are
+ * encoded as length-prefixed lists. A field marked `#[ssz(skip)]` is neither
+ * encoded, decoded nor hashed; it is set to `Default::default()` when decoding.
+ *
+ * A list field marked `#[ssz(max_len = "<expr>")]` is decoded with at most
+ * `<expr>` elements, other lists are limited only by the length of the input.
+ *
+ * A field marked `#[ssz(bls)]` (a BLS key or signature, which are foreign
+ * types) is encoded as a list of the bytes returned by its `as_bytes()` and
+ * decoded with its `from_bytes()`. Bytes which are not a valid point produce
+ * an `InvalidBlsPoint` error.
+ *
+ * The tree hash of a struct is the hash of the concatenated tree hashes of its
+ * fields. A `#[ssz(bls)]` field is hashed as its byte string.
+ *
+ * The generated code refers to `::ssz`, so the crate using the derive must
+ * declare `extern crate ssz;` at its root.
+ */
+extern crate proc_macro;
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Field, Fields, Lit, Meta, NestedMeta, Type};
+
+/// Returns the named fields of a struct, panicking on any other input.
+fn struct_fields(input: &DeriveInput) -> Vec<&Field> {
+    match input.data {
+        Data::Struct(ref s) => match s.fields {
+            Fields::Named(ref fields) => fields.named.iter().collect(),
+            _ => panic!("ssz_derive only supports structs with named fields"),
+        },
+        _ => panic!("ssz_derive only supports structs"),
+    }
+}
+
+/// Returns true if the field is marked with `#[ssz(<word>)]`.
+fn has_ssz_word(field: &Field, word: &str) -> bool {
+    field.attrs.iter().any(|attr| match attr.parse_meta() {
+        Ok(Meta::List(ref list)) if list.ident == "ssz" => {
+            list.nested.iter().any(|nested| match nested {
+                NestedMeta::Meta(Meta::Word(ref ident)) => ident == word,
+                _ => false,
+            })
+        }
+        _ => false,
+    })
+}
+
+/// Returns true if the field is marked with `#[ssz(skip)]`.
+fn should_skip(field: &Field) -> bool {
+    has_ssz_word(field, "skip")
+}
+
+/// Returns true if the field is marked with `#[ssz(bls)]`.
+fn is_bls(field: &Field) -> bool {
+    has_ssz_word(field, "bls")
+}
+
+/// Returns the expression given by `#[ssz(max_len = "<expr>")]` on the field, if any.
+fn max_len(field: &Field) -> Option<Expr> {
+    field
+        .attrs
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => {
+                if list.ident == "ssz" {
+                    Some(list.nested)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .flat_map(|nested| nested.into_iter())
+        .filter_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(ref name_value)) if name_value.ident == "max_len" => {
+                match name_value.lit {
+                    Lit::Str(ref lit) => Some(
+                        lit.parse()
+                            .expect("ssz_derive max_len must be an expression"),
+                    ),
+                    _ => panic!("ssz_derive max_len must be given as a string"),
+                }
+            }
+            _ => None,
+        })
+        .next()
+}
+
+/// Returns true if the type is a `Vec<T>`.
+fn is_vec(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ref type_path) => match type_path.path.segments.last() {
+            Some(segment) => segment.value().ident == "Vec",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[proc_macro_derive(Encode, attributes(ssz))]
+pub fn ssz_encode_derive(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as DeriveInput);
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let appends = struct_fields(&item)
+        .into_iter()
+        .filter(|field| !should_skip(field))
+        .map(|field| {
+            let ident = &field.ident;
+            if is_bls(field) {
+                quote! { s.append_vec(&self.#ident.as_bytes()); }
+            } else if is_vec(&field.ty) {
+                quote! { s.append_vec(&self.#ident); }
+            } else {
+                quote! { s.append(&self.#ident); }
+            }
+        });
+
+    let output = quote! {
+        impl #impl_generics ::ssz::Encodable for #name #ty_generics #where_clause {
+            fn ssz_append(&self, s: &mut ::ssz::SszStream) {
+                #(#appends)*
+            }
+        }
+    };
+    output.into()
+}
+
+#[proc_macro_derive(Decode, attributes(ssz))]
+pub fn ssz_decode_derive(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as DeriveInput);
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let fields = struct_fields(&item);
+    let idents = fields.iter().map(|field| &field.ident);
+    let decodes = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = &field.ty;
+        if should_skip(field) {
+            quote! { let #ident = <#ty as ::std::default::Default>::default(); }
+        } else if is_bls(field) {
+            quote! {
+                let (#ident, i) = {
+                    let (point_bytes, next): (Vec<u8>, usize) = ::ssz::decode_ssz_list(bytes, i)?;
+                    let point = <#ty>::from_bytes(&point_bytes).map_err(|_| {
+                        ::ssz::DecodeError::new(
+                            ::ssz::DecodeErrorKind::InvalidBlsPoint,
+                            stringify!(#ty),
+                            i,
+                        )
+                    })?;
+                    (point, next)
+                };
+            }
+        } else if let Some(max_len) = max_len(field) {
+            quote! { let (#ident, i) = ::ssz::decode_ssz_list_max(bytes, i, #max_len)?; }
+        } else if is_vec(ty) {
+            quote! { let (#ident, i) = ::ssz::decode_ssz_list(bytes, i)?; }
+        } else {
+            quote! { let (#ident, i) = <#ty as ::ssz::Decodable>::ssz_decode(bytes, i)?; }
+        }
+    });
+
+    let output = quote! {
+        impl #impl_generics ::ssz::Decodable for #name #ty_generics #where_clause {
+            fn ssz_decode(
+                bytes: &[u8],
+                i: usize,
+            ) -> Result<(Self, usize), ::ssz::DecodeError> {
+                #(#decodes)*
+                Ok((Self { #(#idents,)* }, i))
+            }
+        }
+    };
+    output.into()
+}
+
+#[proc_macro_derive(TreeHash, attributes(ssz))]
+pub fn ssz_tree_hash_derive(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as DeriveInput);
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let roots = struct_fields(&item)
+        .into_iter()
+        .filter(|field| !should_skip(field))
+        .map(|field| {
+            let ident = &field.ident;
+            if is_bls(field) {
+                quote! { ::ssz::TreeHash::hash_tree_root(&self.#ident.as_bytes()[..]) }
+            } else {
+                quote! { ::ssz::TreeHash::hash_tree_root(&self.#ident) }
+            }
+        });
+
+    let output = quote! {
+        impl #impl_generics ::ssz::TreeHash for #name #ty_generics #where_clause {
+            fn hash_tree_root(&self) -> Vec<u8> {
+                ::ssz::hash_tree_roots(&[#(#roots),*])
+            }
+        }
+    };
+    output.into()
+}
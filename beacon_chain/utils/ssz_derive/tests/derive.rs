@@ -0,0 +1,185 @@
+extern crate ssz;
+#[macro_use]
+extern crate ssz_derive;
+
+use ssz::{hash_tree_roots, ssz_encode, Decodable, DecodeError, DecodeErrorKind, TreeHash};
+
+#[derive(Debug, PartialEq, Encode, Decode, TreeHash)]
+struct Inner {
+    a: u16,
+    b: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TreeHash)]
+struct Outer {
+    x: u64,
+    inners: Vec<Inner>,
+    #[ssz(skip)]
+    cache: Option<u32>,
+    y: u8,
+}
+
+#[test]
+fn test_encode_field_order() {
+    let inner = Inner {
+        a: 258,
+        b: vec![7, 8],
+    };
+    assert_eq!(ssz_encode(&inner), vec![1, 2, 0, 0, 0, 2, 7, 8]);
+}
+
+#[test]
+fn test_skipped_field() {
+    let outer = Outer {
+        x: 1,
+        inners: vec![],
+        cache: Some(42),
+        y: 9,
+    };
+    let ssz = ssz_encode(&outer);
+    assert_eq!(ssz, vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 9]);
+
+    let (decoded, i) = Outer::ssz_decode(&ssz, 0).unwrap();
+    assert_eq!(i, ssz.len());
+    assert_eq!(decoded.cache, None);
+    assert_eq!(decoded.x, 1);
+    assert_eq!(decoded.y, 9);
+}
+
+#[test]
+fn test_round_trip() {
+    let outer = Outer {
+        x: 42,
+        inners: vec![
+            Inner { a: 1, b: vec![] },
+            Inner {
+                a: 2,
+                b: vec![3, 4, 5],
+            },
+        ],
+        cache: None,
+        y: 255,
+    };
+    let ssz = ssz_encode(&outer);
+    let (decoded, i) = Outer::ssz_decode(&ssz, 0).unwrap();
+    assert_eq!(decoded, outer);
+    assert_eq!(i, ssz.len());
+}
+
+#[test]
+fn test_decode_too_short() {
+    let inner = Inner {
+        a: 1,
+        b: vec![1, 2, 3],
+    };
+    let ssz = ssz_encode(&inner);
+    assert_eq!(
+        Inner::ssz_decode(&ssz[..ssz.len() - 1], 0),
+        Err(DecodeError::too_short("list", 2))
+    );
+}
+
+/// Stands in for a BLS type, which only provides its bytes and may be parsed from exactly three
+/// bytes.
+#[derive(Debug, PartialEq)]
+struct Key(Vec<u8>);
+
+impl Key {
+    fn as_bytes(&self) -> Vec<u8> {
+        self.0.clone()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ()> {
+        if bytes.len() == 3 {
+            Ok(Key(bytes.to_vec()))
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TreeHash)]
+struct Keyed {
+    n: u32,
+    #[ssz(bls)]
+    key: Key,
+}
+
+#[test]
+fn test_tree_hash() {
+    let outer = Outer {
+        x: 42,
+        inners: vec![Inner {
+            a: 1,
+            b: vec![2, 3],
+        }],
+        cache: Some(7),
+        y: 255,
+    };
+    /*
+     * The skipped field does not contribute to the root.
+     */
+    assert_eq!(
+        outer.hash_tree_root(),
+        hash_tree_roots(&[
+            outer.x.hash_tree_root(),
+            outer.inners.hash_tree_root(),
+            outer.y.hash_tree_root(),
+        ])
+    );
+
+    let keyed = Keyed {
+        n: 1,
+        key: Key(vec![4, 5, 6]),
+    };
+    assert_eq!(
+        keyed.hash_tree_root(),
+        hash_tree_roots(&[1_u32.hash_tree_root(), [4_u8, 5, 6][..].hash_tree_root()])
+    );
+}
+
+#[test]
+fn test_bls_field() {
+    let keyed = Keyed {
+        n: 1,
+        key: Key(vec![4, 5, 6]),
+    };
+    let ssz = ssz_encode(&keyed);
+    assert_eq!(ssz, vec![0, 0, 0, 1, 0, 0, 0, 3, 4, 5, 6]);
+    assert_eq!(Keyed::ssz_decode(&ssz, 0), Ok((keyed, ssz.len())));
+
+    /*
+     * Bytes which are not a valid key are rejected at the start of the key.
+     */
+    let invalid = ssz_encode(&Keyed {
+        n: 1,
+        key: Key(vec![4, 5]),
+    });
+    assert_eq!(
+        Keyed::ssz_decode(&invalid, 0),
+        Err(DecodeError::new(DecodeErrorKind::InvalidBlsPoint, "Key", 4))
+    );
+}
+
+const MAX_LIMITED: usize = 2;
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct Limited {
+    #[ssz(max_len = "MAX_LIMITED")]
+    values: Vec<u8>,
+}
+
+#[test]
+fn test_max_len() {
+    let limited = Limited { values: vec![1, 2] };
+    let ssz = ssz_encode(&limited);
+    assert_eq!(Limited::ssz_decode(&ssz, 0), Ok((limited, ssz.len())));
+
+    let ssz = ssz_encode(&Limited {
+        values: vec![1, 2, 3],
+    });
+    assert_eq!(
+        Limited::ssz_decode(&ssz, 0),
+        Err(DecodeError::new(DecodeErrorKind::ListTooLong, "list", 0))
+    );
+}
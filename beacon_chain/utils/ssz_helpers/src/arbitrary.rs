@@ -0,0 +1,86 @@
+use super::bls::{AggregateSignature, Signature};
+use super::types::{Attestation, AttestationData, BeaconBlock, Bitfield, Hash256, SpecialRecord};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/*
+ * Strategies for generating arbitrary, valid, blocks and their components for property tests.
+ *
+ * The sizes of lists are kept small so that each case is fast to test.
+ */
+
+pub fn hash256() -> impl Strategy<Value = Hash256> {
+    any::<[u8; 32]>().prop_map(Hash256::from)
+}
+
+/// An empty bitfield does not survive an encode and decode unchanged, so bitfields hold at least
+/// one byte.
+pub fn bitfield() -> impl Strategy<Value = Bitfield> {
+    vec(any::<u8>(), 1..8).prop_map(|bytes| Bitfield::from_bytes(&bytes))
+}
+
+pub fn attestation() -> impl Strategy<Value = Attestation> {
+    (
+        any::<u64>(),
+        any::<u64>(),
+        hash256(),
+        any::<u64>(),
+        bitfield(),
+        bitfield(),
+    )
+        .prop_map(
+            |(slot, shard, hash, justified_slot, participation_bitfield, custody_bitfield)| {
+                Attestation {
+                    data: AttestationData {
+                        slot,
+                        shard,
+                        beacon_block_hash: hash,
+                        justified_slot,
+                        ..AttestationData::zero()
+                    },
+                    participation_bitfield,
+                    custody_bitfield,
+                    aggregate_sig: AggregateSignature::new(),
+                }
+            },
+        )
+}
+
+pub fn special_record() -> impl Strategy<Value = SpecialRecord> {
+    (any::<u8>(), vec(any::<u8>(), 0..16)).prop_map(|(kind, data)| SpecialRecord { kind, data })
+}
+
+pub fn beacon_block() -> impl Strategy<Value = BeaconBlock> {
+    (
+        any::<u64>(),
+        hash256(),
+        hash256(),
+        vec(hash256(), 0..4),
+        hash256(),
+        hash256(),
+        vec(attestation(), 0..3),
+        vec(special_record(), 0..3),
+    )
+        .prop_map(
+            |(
+                slot,
+                randao_reveal,
+                pow_chain_reference,
+                ancestor_hashes,
+                active_state_root,
+                crystallized_state_root,
+                attestations,
+                specials,
+            )| BeaconBlock {
+                slot,
+                randao_reveal,
+                pow_chain_reference,
+                ancestor_hashes,
+                active_state_root,
+                crystallized_state_root,
+                attestations,
+                specials,
+                signature: Signature::empty_signature(),
+            },
+        )
+}
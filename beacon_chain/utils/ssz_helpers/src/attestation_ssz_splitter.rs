@@ -1,6 +1,5 @@
 use super::bls::BLS_AGG_SIG_BYTE_SIZE;
-use super::ssz::decode::decode_length;
-use super::ssz::LENGTH_BYTES;
+use super::ssz::{DecodeError, DecodeErrorKind, SszReader};
 use super::types::attestation::MIN_SSZ_ATTESTION_RECORD_LENGTH;
 use super::types::attestation_data::SSZ_ATTESTION_DATA_LENGTH;
 
@@ -17,7 +16,7 @@ pub fn split_all_attestations<'a>(
 ) -> Result<Vec<&'a [u8]>, AttestationSplitError> {
     let mut v = vec![];
     let mut index = index;
-    while index < full_ssz.len() - 1 {
+    while index + 1 < full_ssz.len() {
         let (slice, i) = split_one_attestation(full_ssz, index)?;
         v.push(slice);
         index = i;
@@ -56,34 +55,46 @@ fn determine_ssz_attestation_len(
         return Err(AttestationSplitError::TooShort);
     }
 
-    let data_struct_end = index + SSZ_ATTESTION_DATA_LENGTH;
-
-    // Determine the end of the first bitfield.
-    let participation_bitfield_len = decode_length(full_ssz, data_struct_end, LENGTH_BYTES)
-        .map_err(|_| AttestationSplitError::TooShort)?;
-    let participation_bitfield_end = data_struct_end + LENGTH_BYTES + participation_bitfield_len;
-
-    // Determine the end of the second bitfield.
-    let custody_bitfield_len = decode_length(full_ssz, participation_bitfield_end, LENGTH_BYTES)
-        .map_err(|_| AttestationSplitError::TooShort)?;
-    let custody_bitfield_end = participation_bitfield_end + LENGTH_BYTES + custody_bitfield_len;
-
-    // Determine the very end of the Attestation.
-    let agg_sig_end = custody_bitfield_end + LENGTH_BYTES + BLS_AGG_SIG_BYTE_SIZE;
+    let mut reader = SszReader::at(full_ssz, index);
+    read_attestation(&mut reader)
+        .map(|attestation_ssz| attestation_ssz.len())
+        .map_err(|_| AttestationSplitError::TooShort)
+}
 
-    if agg_sig_end > full_ssz.len() {
-        Err(AttestationSplitError::TooShort)
-    } else {
-        Ok(agg_sig_end - index)
+/// Step the reader over a serialized `Attestation`, returning the bytes of that `Attestation`.
+///
+/// As with `determine_ssz_attestation_len`, the `Attestation` is not validated other than to
+/// check the length of its aggregate signature.
+pub fn read_attestation<'a>(reader: &mut SszReader<'a>) -> Result<&'a [u8], DecodeError> {
+    let start = reader.index();
+
+    // Step over the data, both bitfields and the aggregate signature.
+    let mut lookahead = reader.clone();
+    lookahead.read_bytes(SSZ_ATTESTION_DATA_LENGTH, "AttestationData")?;
+    lookahead.read_list_bytes()?;
+    lookahead.read_list_bytes()?;
+    let agg_sig_index = lookahead.index();
+    let agg_sig = lookahead.read_list_bytes()?;
+
+    if agg_sig.len() != BLS_AGG_SIG_BYTE_SIZE {
+        return Err(DecodeError::new(
+            DecodeErrorKind::InvalidBlsPoint,
+            "AggregateSignature",
+            agg_sig_index,
+        ));
     }
+    reader.read_bytes(lookahead.index() - start, "Attestation")
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::arbitrary;
     use super::super::bls::AggregateSignature;
     use super::super::ssz::{Decodable, SszStream};
     use super::super::types::{Attestation, AttestationData, Bitfield, Hash256};
     use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
     fn get_two_records() -> Vec<Attestation> {
         let a = Attestation {
@@ -159,4 +170,66 @@ mod tests {
         let ssz = &ssz[0..ssz.len() - 1];
         assert!(split_all_attestations(&ssz, 0).is_err());
     }
+    #[test]
+    fn test_attestation_ssz_split_invalid_signature_length() {
+        let a = get_two_records()[0].clone();
+
+        let mut ssz_stream = SszStream::new();
+        ssz_stream.append(&a.data);
+        ssz_stream.append(&a.participation_bitfield);
+        ssz_stream.append(&a.custody_bitfield);
+        ssz_stream.append_vec(&[0_u8; BLS_AGG_SIG_BYTE_SIZE - 1]);
+        ssz_stream.append(&42_u8);
+        let ssz = ssz_stream.drain();
+
+        assert_eq!(
+            split_one_attestation(&ssz, 0),
+            Err(AttestationSplitError::TooShort)
+        );
+    }
+
+    #[test]
+    fn test_attestation_ssz_split_empty() {
+        assert_eq!(split_all_attestations(&[], 0), Ok(vec![]));
+        assert_eq!(
+            split_one_attestation(&[], 0),
+            Err(AttestationSplitError::TooShort)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_split_agrees_with_decode(
+            attestations in vec(arbitrary::attestation(), 0..4)
+        ) {
+            let mut ssz_stream = SszStream::new();
+            for a in &attestations {
+                ssz_stream.append(a);
+            }
+            let ssz = ssz_stream.drain();
+
+            let slices = split_all_attestations(&ssz, 0).unwrap();
+            prop_assert_eq!(slices.len(), attestations.len());
+            for (slice, a) in slices.iter().zip(&attestations) {
+                prop_assert_eq!(&Attestation::ssz_decode(slice, 0).unwrap().0, a);
+            }
+        }
+
+        #[test]
+        fn prop_split_random_bytes(
+            ssz in vec(any::<u8>(), 0..1024),
+            index in 0..1024_usize
+        ) {
+            /*
+             * Any split must succeed or fail without panicking, and must stay within the bytes.
+             */
+            if let Ok(slices) = split_all_attestations(&ssz, index) {
+                let total: usize = slices.iter().map(|s| s.len()).sum();
+                prop_assert!(total <= ssz.len().saturating_sub(index));
+            }
+            if let Ok((slice, i)) = split_one_attestation(&ssz, index) {
+                prop_assert_eq!(i, index + slice.len());
+            }
+        }
+    }
 }
@@ -3,5 +3,10 @@ extern crate hashing;
 extern crate ssz;
 extern crate types;
 
+#[cfg(test)]
+extern crate proptest;
+
+#[cfg(test)]
+mod arbitrary;
 pub mod attestation_ssz_splitter;
 pub mod ssz_beacon_block;
@@ -1,11 +1,28 @@
+use super::attestation_ssz_splitter::read_attestation;
 use super::hashing::canonical_hash;
-use super::ssz::decode::{decode_length, Decodable};
-use super::types::beacon_block::{MAX_SSZ_BLOCK_LENGTH, MIN_SSZ_BLOCK_LENGTH};
+use super::ssz::decode::Decodable;
+use super::ssz::{DecodeError, DecodeErrorKind, SszReader};
+use super::types::beacon_block::{
+    MAX_ANCESTOR_HASHES, MAX_ATTESTATIONS_PER_BLOCK, MAX_SPECIALS_PER_BLOCK, MAX_SSZ_BLOCK_LENGTH,
+    MIN_SSZ_BLOCK_LENGTH,
+};
+use super::types::Hash256;
 
 #[derive(Debug, PartialEq)]
 pub enum SszBeaconBlockError {
     TooShort,
     TooLong,
+    /// A list of the block holds more elements than a block may.
+    ListTooLong,
+}
+
+impl From<DecodeError> for SszBeaconBlockError {
+    fn from(e: DecodeError) -> Self {
+        match e.kind {
+            DecodeErrorKind::ListTooLong => SszBeaconBlockError::ListTooLong,
+            _ => SszBeaconBlockError::TooShort,
+        }
+    }
 }
 
 /*
@@ -44,6 +61,9 @@ pub struct SszBeaconBlock<'a> {
     // Specials
     specials_position: usize,
     specials_len: usize,
+    // Signature
+    signature_position: usize,
+    signature_len: usize,
 }
 
 impl<'a> SszBeaconBlock<'a> {
@@ -73,37 +93,44 @@ impl<'a> SszBeaconBlock<'a> {
         }
 
         /*
-         * Determine how many bytes are used to store ancestor hashes.
+         * Step over each field to find the position and length of the variable length fields
+         * (ancestors, attestations, specials, signature).
+         *
+         * The reader rejects any list which would extend beyond the slice, therefore the slice
+         * is known to hold the entire block once the final list has been read. The elements of
+         * each list are stepped over (but not decoded) to ensure there are not more than a block
+         * may hold.
          */
-        let ancestors_position = SLOT_BYTES + RANDAO_REVEAL_BYTES + POW_CHAIN_REF_BYTES;
-        let ancestors_len = decode_length(untrimmed_ssz, ancestors_position, LENGTH_PREFIX_BYTES)
-            .map_err(|_| SszBeaconBlockError::TooShort)?;
+        let mut reader = SszReader::new(untrimmed_ssz);
+        reader.read_bytes(
+            SLOT_BYTES + RANDAO_REVEAL_BYTES + POW_CHAIN_REF_BYTES,
+            "BeaconBlock",
+        )?;
+
+        let ancestors_position = reader.index();
+        for ancestor_hash in reader.read_list::<Hash256>(MAX_ANCESTOR_HASHES)? {
+            ancestor_hash?;
+        }
+        let ancestors_len = reader.index() - ancestors_position - LENGTH_PREFIX_BYTES;
 
-        /*
-         * Determine how many bytes are used to store attestation records.
-         */
-        let attestations_position = ancestors_position + LENGTH_PREFIX_BYTES + ancestors_len +     // end of ancestor bytes
-            ACTIVE_STATE_BYTES +
-            CRYSTALLIZED_STATE_BYTES;
-        let attestations_len =
-            decode_length(untrimmed_ssz, attestations_position, LENGTH_PREFIX_BYTES)
-                .map_err(|_| SszBeaconBlockError::TooShort)?;
+        reader.read_bytes(ACTIVE_STATE_BYTES + CRYSTALLIZED_STATE_BYTES, "BeaconBlock")?;
 
-        /*
-         * Determine how many bytes are used to store specials.
-         */
-        let specials_position = attestations_position + LENGTH_PREFIX_BYTES + attestations_len;
-        let specials_len = decode_length(untrimmed_ssz, specials_position, LENGTH_PREFIX_BYTES)
-            .map_err(|_| SszBeaconBlockError::TooShort)?;
+        let attestations_position = reader.index();
+        for attestation in reader.read_list_with(MAX_ATTESTATIONS_PER_BLOCK, read_attestation)? {
+            attestation?;
+        }
+        let attestations_len = reader.index() - attestations_position - LENGTH_PREFIX_BYTES;
 
-        /*
-         * Now that all variable field lengths are known (ancestors, attestations, specials) we can
-         * know the exact length of the block and reject it if the slice is too short.
-         */
-        let block_ssz_len = MIN_SSZ_BLOCK_LENGTH + ancestors_len + attestations_len + specials_len;
-        if vec.len() < block_ssz_len {
-            return Err(SszBeaconBlockError::TooShort);
+        let specials_position = reader.index();
+        for special in reader.read_list_with(MAX_SPECIALS_PER_BLOCK, read_special)? {
+            special?;
         }
+        let specials_len = reader.index() - specials_position - LENGTH_PREFIX_BYTES;
+
+        let signature_position = reader.index();
+        let signature_len = reader.read_list_bytes()?.len();
+
+        let block_ssz_len = reader.index();
 
         Ok(Self {
             ssz: &untrimmed_ssz[0..block_ssz_len],
@@ -114,6 +141,8 @@ impl<'a> SszBeaconBlock<'a> {
             attestations_len,
             specials_position,
             specials_len,
+            signature_position,
+            signature_len,
         })
     }
 
@@ -210,15 +239,32 @@ impl<'a> SszBeaconBlock<'a> {
         let start = self.specials_position;
         &self.ssz[start..(start + self.specials_len + LENGTH_PREFIX_BYTES)]
     }
+
+    /// Return the `signature` bytes _without_ the length prefix.
+    pub fn signature(&self) -> &[u8] {
+        let start = self.signature_position + LENGTH_PREFIX_BYTES;
+        &self.ssz[start..start + self.signature_len]
+    }
+}
+
+/// Step the reader over a serialized `SpecialRecord` (its kind and data), returning its data.
+fn read_special<'a>(reader: &mut SszReader<'a>) -> Result<&'a [u8], DecodeError> {
+    reader.read::<u8>()?;
+    reader.read_list_bytes()
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::arbitrary;
+    use super::super::attestation_ssz_splitter::split_all_attestations;
+    use super::super::bls::{Keypair, Signature};
     use super::super::ssz::encode::encode_length;
-    use super::super::ssz::SszStream;
+    use super::super::ssz::{decode_ssz_list, SszStream};
     use super::super::types::Hash256;
     use super::super::types::{Attestation, BeaconBlock, SpecialRecord};
     use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
     use super::canonical_hash;
 
@@ -274,6 +320,28 @@ mod tests {
         assert_eq!(ssz_block.len(), original_len);
     }
 
+    #[test]
+    fn test_ssz_block_too_many_list_elements() {
+        let mut b = BeaconBlock::zero();
+        b.ancestor_hashes = vec![Hash256::zero(); MAX_ANCESTOR_HASHES];
+        b.specials = vec![SpecialRecord::logout(&[]); MAX_SPECIALS_PER_BLOCK];
+        assert!(SszBeaconBlock::from_slice(&get_block_ssz(&b)).is_ok());
+
+        let mut too_many_ancestors = b.clone();
+        too_many_ancestors.ancestor_hashes.push(Hash256::zero());
+        assert_eq!(
+            SszBeaconBlock::from_slice(&get_block_ssz(&too_many_ancestors)),
+            Err(SszBeaconBlockError::ListTooLong)
+        );
+
+        let mut too_many_specials = b.clone();
+        too_many_specials.specials.push(SpecialRecord::logout(&[]));
+        assert_eq!(
+            SszBeaconBlock::from_slice(&get_block_ssz(&too_many_specials)),
+            Err(SszBeaconBlockError::ListTooLong)
+        );
+    }
+
     #[test]
     fn test_ssz_block_single_attestation_record() {
         let mut b = BeaconBlock::zero();
@@ -296,8 +364,8 @@ mod tests {
         // canonical reference.
         // TODO: make sure this test conforms to canonical test vectors; it is not clear that it currently does so
         let expected_hash = [
-            132, 43, 230, 49, 234, 240, 253, 146, 85, 121, 104, 79, 35, 0, 126, 162, 132, 99, 145,
-            13, 30, 57, 118, 5, 175, 136, 174, 7, 52, 161, 87, 196,
+            173, 80, 238, 9, 240, 41, 162, 140, 234, 76, 85, 185, 175, 141, 229, 151, 196, 102,
+            116, 42, 56, 85, 61, 42, 130, 132, 49, 245, 122, 164, 243, 251,
         ];
         assert_eq!(hash, expected_hash);
 
@@ -480,4 +548,127 @@ mod tests {
 
         assert_eq!(ssz_block.cry_state_root(), &reference_hash.to_vec()[..]);
     }
+
+    #[test]
+    fn test_ssz_block_signature() {
+        let mut block = BeaconBlock::zero();
+        block.specials.push(SpecialRecord::logout(&[]));
+        block.signature = Signature::new(&[42], &Keypair::random().sk);
+
+        let serialized = get_block_ssz(&block);
+        let ssz_block = SszBeaconBlock::from_slice(&serialized).unwrap();
+
+        assert_eq!(ssz_block.signature(), &block.signature.as_bytes()[..]);
+
+        /*
+         * The signature is the final field, so the block is too short without its last byte.
+         */
+        assert_eq!(
+            SszBeaconBlock::from_slice(&serialized[..serialized.len() - 1]),
+            Err(SszBeaconBlockError::TooShort)
+        );
+    }
+
+    /// Decode some list which must fill the entirety of `ssz`.
+    fn decode_entire_list<T: Decodable>(ssz: &[u8]) -> Vec<T> {
+        let (list, i) = decode_ssz_list(ssz, 0).unwrap();
+        assert_eq!(i, ssz.len());
+        list
+    }
+
+    /// Check `SszBeaconBlock` against a full `BeaconBlock` decoding of `ssz`, returning the
+    /// `BeaconBlock` if it could be decoded.
+    ///
+    /// Neither may panic. If the full decoding succeeds, `SszBeaconBlock` must accept the same
+    /// bytes and each of its fields must hold the same value as the `BeaconBlock`.
+    fn check_against_beacon_block(ssz: &[u8]) -> Option<BeaconBlock> {
+        let ssz_block = SszBeaconBlock::from_slice(ssz);
+        if let Ok(ref ssz_block) = ssz_block {
+            /*
+             * Every field must be readable from any accepted bytes.
+             */
+            ssz_block.block_hash();
+            ssz_block.parent_hash();
+            ssz_block.slot();
+            ssz_block.randao_reveal();
+            ssz_block.pow_chain_reference();
+            ssz_block.act_state_root();
+            ssz_block.cry_state_root();
+            ssz_block.attestations_without_length();
+            ssz_block.specials();
+            ssz_block.signature();
+        }
+
+        let (block, i) = BeaconBlock::ssz_decode(ssz, 0).ok()?;
+        let ssz_block = ssz_block.expect("SszBeaconBlock must accept a valid block");
+
+        assert_eq!(ssz_block.block_ssz(), &ssz[..i]);
+        assert_eq!(ssz_block.len(), i);
+        assert_eq!(ssz_block.slot(), block.slot);
+        assert_eq!(ssz_block.randao_reveal(), &block.randao_reveal[..]);
+        assert_eq!(
+            ssz_block.pow_chain_reference(),
+            &block.pow_chain_reference[..]
+        );
+        assert_eq!(ssz_block.act_state_root(), &block.active_state_root[..]);
+        assert_eq!(
+            ssz_block.cry_state_root(),
+            &block.crystallized_state_root[..]
+        );
+        assert_eq!(ssz_block.parent_hash(), block.parent_hash().map(|h| &h[..]));
+        assert_eq!(
+            decode_entire_list::<Hash256>(ssz_block.ancestor_hashes()),
+            block.ancestor_hashes
+        );
+        assert_eq!(
+            decode_entire_list::<Attestation>(ssz_block.attestations()),
+            block.attestations
+        );
+        assert_eq!(
+            decode_entire_list::<SpecialRecord>(ssz_block.specials()),
+            block.specials
+        );
+        assert_eq!(ssz_block.signature(), &block.signature.as_bytes()[..]);
+
+        let attestations = split_all_attestations(ssz_block.attestations_without_length(), 0)
+            .expect("the attestations of a valid block must split");
+        assert_eq!(attestations.len(), block.attestations.len());
+        for (a_ssz, a) in attestations.iter().zip(&block.attestations) {
+            assert_eq!(&Attestation::ssz_decode(a_ssz, 0).unwrap().0, a);
+        }
+
+        Some(block)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ssz_block_agrees_with_beacon_block(
+            block in arbitrary::beacon_block(),
+            excess in vec(any::<u8>(), 0..8)
+        ) {
+            let mut ssz = get_block_ssz(&block);
+            ssz.extend_from_slice(&excess);
+
+            prop_assert_eq!(check_against_beacon_block(&ssz), Some(block));
+        }
+
+        #[test]
+        fn prop_ssz_block_mutated(
+            block in arbitrary::beacon_block(),
+            index in any::<usize>(),
+            byte in any::<u8>()
+        ) {
+            let mut ssz = get_block_ssz(&block);
+            let index = index % ssz.len();
+            ssz[index] = byte;
+
+            check_against_beacon_block(&ssz);
+            check_against_beacon_block(&ssz[..index]);
+        }
+
+        #[test]
+        fn prop_ssz_block_random_bytes(ssz in vec(any::<u8>(), 0..1024)) {
+            check_against_beacon_block(&ssz);
+        }
+    }
 }
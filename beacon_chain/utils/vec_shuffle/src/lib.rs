@@ -21,7 +21,7 @@ pub enum ShuffleErr {
 pub fn shuffle<T>(seed: &[u8], mut list: Vec<T>) -> Result<Vec<T>, ShuffleErr> {
     let mut rng = ShuffleRng::new(seed);
 
-    if list.len() > rng.rand_max as usize {
+    if list.len() >= rng.rand_max as usize {
         return Err(ShuffleErr::ExceedsListLength);
     }
 
@@ -31,7 +31,10 @@ pub fn shuffle<T>(seed: &[u8], mut list: Vec<T>) -> Result<Vec<T>, ShuffleErr> {
 
     for i in 0..(list.len() - 1) {
         let n = list.len() - i;
-        let j = rng.rand_range(n as u32) as usize + i;
+        let j = match rng.rand_range(n as u32) {
+            Some(r) => r as usize + i,
+            None => return Err(ShuffleErr::ExceedsListLength),
+        };
         list.swap(i, j);
     }
     Ok(list)
@@ -78,4 +81,14 @@ mod tests {
             assert_eq!(s, output);
         }
     }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        for len in 0..100 {
+            let list: Vec<usize> = (0..len).collect();
+            let mut shuffled = shuffle(&[len as u8], list.clone()).unwrap();
+            shuffled.sort();
+            assert_eq!(shuffled, list);
+        }
+    }
 }
@@ -44,13 +44,17 @@ impl ShuffleRng {
     /// Provides a filtered result from a higher-level rng, by discarding
     /// results which may bias the output. Because of this, execution time is
     /// not linear and may potentially be infinite.
-    pub fn rand_range(&mut self, n: u32) -> u32 {
-        assert!(n < RAND_MAX, "RAND_MAX exceed");
+    ///
+    /// Returns `None` if `n` is zero or not less than `rand_max`.
+    pub fn rand_range(&mut self, n: u32) -> Option<u32> {
+        if n == 0 || n >= self.rand_max {
+            return None;
+        }
         let mut x = self.rand();
         while x >= self.rand_max - (self.rand_max % n) {
             x = self.rand();
         }
-        x % n
+        Some(x % n)
     }
 }
 
@@ -87,4 +91,24 @@ mod tests {
         x = int_from_byte_slice(&[0x8f, 0xbb, 0xc7], 0);
         assert_eq!(x, 9419719);
     }
+
+    #[test]
+    fn test_rand_range_bounds() {
+        let mut rng = ShuffleRng::new(&[42]);
+
+        assert_eq!(rng.rand_range(0), None);
+        assert_eq!(rng.rand_range(RAND_MAX), None);
+        assert_eq!(rng.rand_range(u32::max_value()), None);
+        assert_eq!(rng.rand_range(1), Some(0));
+        assert!(rng.rand_range(RAND_MAX - 1).unwrap() < RAND_MAX - 1);
+    }
+
+    #[test]
+    fn test_rand_range_is_below_n() {
+        let mut rng = ShuffleRng::new(&[1, 2, 3]);
+
+        for n in 1..1_000 {
+            assert!(rng.rand_range(n).unwrap() < n);
+        }
+    }
 }
@@ -1,12 +1,15 @@
 extern crate bytes;
 extern crate hashing;
+extern crate spec;
 extern crate types;
 
 use bytes::{BufMut, BytesMut};
 use hashing::canonical_hash;
+use spec::ChainSpec;
 use std::cmp::max;
 use types::{Hash256, ValidatorRecord, ValidatorStatus};
 
+#[derive(Debug, PartialEq)]
 pub enum UpdateValidatorSetError {
     ArithmeticOverflow,
 }
@@ -14,13 +17,20 @@ pub enum UpdateValidatorSetError {
 const VALIDATOR_FLAG_ENTRY: u8 = 0;
 const VALIDATOR_FLAG_EXIT: u8 = 1;
 
+/// Activate and exit validators, subject to a limit on the total balance that may change.
+///
+/// Returns the new validator set delta hash chain, which is `hash_chain` extended with each
+/// change made to the validator set.
 pub fn update_validator_set(
     validators: &mut Vec<ValidatorRecord>,
     hash_chain: Hash256,
     present_slot: u64,
-    deposit_size_gwei: u64,
-    max_validator_churn_quotient: u64,
-) -> Result<(), UpdateValidatorSetError> {
+    spec: &ChainSpec,
+) -> Result<Hash256, UpdateValidatorSetError> {
+    let deposit_size_gwei = spec
+        .max_deposit_gwei()
+        .ok_or(UpdateValidatorSetError::ArithmeticOverflow)?;
+
     /*
      * Total balance of all active validators.
      *
@@ -47,7 +57,7 @@ pub fn update_validator_set(
             .ok_or(UpdateValidatorSetError::ArithmeticOverflow)?;
         max(
             double_deposit_size,
-            total_balance / max_validator_churn_quotient,
+            total_balance / spec.max_balance_churn_quotient,
         )
     };
 
@@ -104,7 +114,7 @@ pub fn update_validator_set(
             break;
         }
     }
-    Ok(())
+    Ok(Hash256::from(&hasher.bytes[..]))
 }
 
 pub struct ValidatorChangeHashChain {
@@ -134,8 +144,31 @@ fn serialize_validator_change_record(index: usize, pubkey: &Vec<u8>, flag: u8) -
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn test_update_validator_set_hash_chain() {
+        let spec = ChainSpec::foundation();
+        let (mut validator, _) = ValidatorRecord::zero_with_thread_rand_keypair();
+        validator.status = ValidatorStatus::PendingActivation;
+        let mut validators = vec![validator];
+
+        let hash_chain = Hash256::zero();
+        let new_hash_chain = update_validator_set(&mut validators, hash_chain, 10, &spec).unwrap();
+
+        assert_eq!(validators[0].status, ValidatorStatus::Active);
+        assert!(new_hash_chain != hash_chain);
+
+        /*
+         * No further changes are possible, so the hash chain should not change.
+         */
+        let next_hash_chain =
+            update_validator_set(&mut validators, new_hash_chain, 20, &spec).unwrap();
+        assert_eq!(next_hash_chain, new_hash_chain);
+    }
 }
@@ -1,13 +1,13 @@
 use bls::verify_proof_of_possession;
+use spec::ChainSpec;
 use types::{ValidatorRecord, ValidatorRegistration, ValidatorStatus};
 
-/// The size of a validators deposit in GWei.
-pub const DEPOSIT_GWEI: u64 = 32_000_000_000;
-
 /// Inducts validators into a `CrystallizedState`.
 pub struct ValidatorInductor {
     pub current_slot: u64,
-    pub shard_count: u16,
+    pub shard_count: u64,
+    /// The balance of each inducted validator, in GWei.
+    pub deposit_gwei: u64,
     validators: Vec<ValidatorRecord>,
     empty_validator_start: usize,
 }
@@ -16,16 +16,26 @@ pub struct ValidatorInductor {
 pub enum ValidatorInductionError {
     InvalidShard,
     InvaidProofOfPossession,
+    /// The maximum deposit of the spec, in Gwei, does not fit in a `u64`.
+    InvalidDepositSize,
 }
 
 impl ValidatorInductor {
-    pub fn new(current_slot: u64, shard_count: u16, validators: Vec<ValidatorRecord>) -> Self {
-        Self {
+    pub fn new(
+        current_slot: u64,
+        spec: &ChainSpec,
+        validators: Vec<ValidatorRecord>,
+    ) -> Result<Self, ValidatorInductionError> {
+        let deposit_gwei = spec
+            .max_deposit_gwei()
+            .ok_or(ValidatorInductionError::InvalidDepositSize)?;
+        Ok(Self {
             current_slot,
-            shard_count,
+            shard_count: spec.shard_count,
+            deposit_gwei,
             validators,
             empty_validator_start: 0,
-        }
+        })
     }
 
     /// Attempt to induct a validator into the CrystallizedState.
@@ -50,7 +60,7 @@ impl ValidatorInductor {
         /*
          * Ensure withdrawal shard is not too high.
          */
-        if r.withdrawal_shard > self.shard_count {
+        if u64::from(r.withdrawal_shard) > self.shard_count {
             return Err(ValidatorInductionError::InvalidShard);
         }
 
@@ -67,7 +77,7 @@ impl ValidatorInductor {
             withdrawal_address: r.withdrawal_address,
             randao_commitment: r.randao_commitment,
             randao_last_change: self.current_slot,
-            balance: DEPOSIT_GWEI,
+            balance: self.deposit_gwei,
             status: status,
             exit_slot: 0,
         })
@@ -140,12 +150,13 @@ mod tests {
 
         let r = get_registration();
 
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
 
         assert_eq!(result.unwrap(), 0);
         assert!(registration_equals_record(&r, &validators[0]));
+        assert_eq!(validators[0].balance, 32_000_000_000);
         assert_eq!(validators.len(), 1);
     }
 
@@ -155,7 +166,7 @@ mod tests {
 
         let r = get_registration();
 
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let _ = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let _ = inductor.induct(&r, ValidatorStatus::Active);
         let validators = inductor.to_vec();
@@ -176,7 +187,7 @@ mod tests {
 
         let r = get_registration();
 
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
 
@@ -199,7 +210,7 @@ mod tests {
 
         let r = get_registration();
 
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
 
@@ -221,7 +232,7 @@ mod tests {
          * Ensure the first validator gets the 0'th slot
          */
         let r = get_registration();
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
         assert_eq!(result.unwrap(), 0);
@@ -231,7 +242,7 @@ mod tests {
          * Ensure the second validator gets the 1'st slot
          */
         let r_two = get_registration();
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r_two, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
         assert_eq!(result.unwrap(), 1);
@@ -246,7 +257,7 @@ mod tests {
         let mut r = get_registration();
         r.withdrawal_shard = 1025;
 
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
 
@@ -262,7 +273,7 @@ mod tests {
         let kp = Keypair::random();
         r.proof_of_possession = create_proof_of_possession(&kp);
 
-        let mut inductor = ValidatorInductor::new(0, 1024, validators);
+        let mut inductor = ValidatorInductor::new(0, &ChainSpec::foundation(), validators).unwrap();
         let result = inductor.induct(&r, ValidatorStatus::PendingActivation);
         let validators = inductor.to_vec();
 
@@ -1,5 +1,6 @@
 extern crate bls;
 extern crate hashing;
+extern crate spec;
 extern crate types;
 
 mod inductor;
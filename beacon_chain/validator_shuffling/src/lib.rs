@@ -1,4 +1,5 @@
 extern crate honey_badger_split;
+extern crate spec;
 extern crate types;
 extern crate vec_shuffle;
 
@@ -1,7 +1,8 @@
 use std::cmp::min;
 
 use honey_badger_split::SplitExt;
-use types::{ChainConfig, ShardAndCommittee, ValidatorRecord, ValidatorStatus};
+use spec::ChainSpec;
+use types::{ShardAndCommittee, ValidatorRecord, ValidatorStatus};
 use vec_shuffle::{shuffle, ShuffleErr};
 
 type DelegatedCycle = Vec<Vec<ShardAndCommittee>>;
@@ -20,7 +21,7 @@ pub fn shard_and_committees_for_cycle(
     seed: &[u8],
     validators: &[ValidatorRecord],
     crosslinking_shard_start: u16,
-    config: &ChainConfig,
+    spec: &ChainSpec,
 ) -> Result<DelegatedCycle, ValidatorAssignmentError> {
     let shuffled_validator_indices = {
         let mut validator_indices = validators
@@ -36,10 +37,10 @@ pub fn shard_and_committees_for_cycle(
             .collect();
         shuffle(seed, validator_indices)?
     };
-    let shard_indices: Vec<usize> = (0_usize..config.shard_count as usize).into_iter().collect();
+    let shard_indices: Vec<usize> = (0_usize..spec.shard_count as usize).into_iter().collect();
     let crosslinking_shard_start = crosslinking_shard_start as usize;
-    let cycle_length = config.cycle_length as usize;
-    let min_committee_size = config.min_committee_size as usize;
+    let cycle_length = spec.epoch_length as usize;
+    let min_committee_size = spec.target_committee_size as usize;
     generate_cycle(
         &shuffled_validator_indices,
         &shard_indices,
@@ -0,0 +1,22 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate ssz;
+extern crate types;
+
+use ssz::{ssz_encode, Decodable};
+use types::BeaconBlock;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((block, i)) = BeaconBlock::ssz_decode(data, 0) {
+        assert!(i <= data.len());
+
+        /*
+         * Any block which was decoded must survive an encode and decode.
+         */
+        let ssz = ssz_encode(&block);
+        let (decoded, i) = BeaconBlock::ssz_decode(&ssz, 0).unwrap();
+        assert_eq!(decoded, block);
+        assert_eq!(i, ssz.len());
+    }
+});
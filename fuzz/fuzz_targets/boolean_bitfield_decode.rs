@@ -0,0 +1,17 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate boolean_bitfield;
+extern crate ssz;
+
+use boolean_bitfield::BooleanBitfield;
+use ssz::{ssz_encode, Decodable};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((field, i)) = BooleanBitfield::ssz_decode(data, 0) {
+        assert!(i <= data.len());
+
+        let ssz = ssz_encode(&field);
+        assert_eq!(BooleanBitfield::ssz_decode(&ssz, 0), Ok((field, ssz.len())));
+    }
+});
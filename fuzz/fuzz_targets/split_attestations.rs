@@ -0,0 +1,27 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate ssz;
+extern crate ssz_helpers;
+extern crate types;
+
+use ssz::Decodable;
+use ssz_helpers::attestation_ssz_splitter::split_all_attestations;
+use types::Attestation;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(slices) = split_all_attestations(data, 0) {
+        let total: usize = slices.iter().map(|s| s.len()).sum();
+        assert!(total <= data.len());
+
+        /*
+         * The splitter does not validate the attestations, but a decoded attestation must
+         * consume exactly the bytes that were split for it.
+         */
+        for slice in slices {
+            if let Ok((_, i)) = Attestation::ssz_decode(slice, 0) {
+                assert_eq!(i, slice.len());
+            }
+        }
+    }
+});
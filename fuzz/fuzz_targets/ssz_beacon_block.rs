@@ -0,0 +1,70 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate ssz;
+extern crate ssz_helpers;
+extern crate types;
+
+use ssz::{decode_ssz_list, Decodable};
+use ssz_helpers::ssz_beacon_block::SszBeaconBlock;
+use types::{Attestation, BeaconBlock, Hash256, SpecialRecord};
+
+/// Decode some list which must fill the entirety of `ssz`.
+fn decode_entire_list<T: Decodable>(ssz: &[u8]) -> Vec<T> {
+    let (list, i) = decode_ssz_list(ssz, 0).unwrap();
+    assert_eq!(i, ssz.len());
+    list
+}
+
+fuzz_target!(|data: &[u8]| {
+    let ssz_block = SszBeaconBlock::from_slice(data);
+    if let Ok(ref ssz_block) = ssz_block {
+        /*
+         * Every field must be readable from any accepted bytes.
+         */
+        ssz_block.block_hash();
+        ssz_block.parent_hash();
+        ssz_block.slot();
+        ssz_block.randao_reveal();
+        ssz_block.pow_chain_reference();
+        ssz_block.act_state_root();
+        ssz_block.cry_state_root();
+        ssz_block.attestations_without_length();
+        ssz_block.specials();
+        ssz_block.signature();
+    }
+
+    /*
+     * If the bytes hold a valid block, the `SszBeaconBlock` must agree with it.
+     */
+    if let Ok((block, i)) = BeaconBlock::ssz_decode(data, 0) {
+        let ssz_block = ssz_block.expect("SszBeaconBlock must accept a valid block");
+
+        assert_eq!(ssz_block.block_ssz(), &data[..i]);
+        assert_eq!(ssz_block.slot(), block.slot);
+        assert_eq!(ssz_block.randao_reveal(), &block.randao_reveal[..]);
+        assert_eq!(
+            ssz_block.pow_chain_reference(),
+            &block.pow_chain_reference[..]
+        );
+        assert_eq!(ssz_block.act_state_root(), &block.active_state_root[..]);
+        assert_eq!(
+            ssz_block.cry_state_root(),
+            &block.crystallized_state_root[..]
+        );
+        assert_eq!(ssz_block.parent_hash(), block.parent_hash().map(|h| &h[..]));
+        assert_eq!(
+            decode_entire_list::<Hash256>(ssz_block.ancestor_hashes()),
+            block.ancestor_hashes
+        );
+        assert_eq!(
+            decode_entire_list::<Attestation>(ssz_block.attestations()),
+            block.attestations
+        );
+        assert_eq!(
+            decode_entire_list::<SpecialRecord>(ssz_block.specials()),
+            block.specials
+        );
+        assert_eq!(ssz_block.signature(), &block.signature.as_bytes()[..]);
+    }
+});
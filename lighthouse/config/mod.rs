@@ -1,5 +1,6 @@
 extern crate dirs;
 
+use db::DiskDBConfig;
 use std::fs;
 use std::path::PathBuf;
 
@@ -10,6 +11,7 @@ use std::path::PathBuf;
 pub struct LighthouseConfig {
     pub data_dir: PathBuf,
     pub p2p_listen_port: u16,
+    pub db_config: DiskDBConfig,
 }
 
 const DEFAULT_LIGHTHOUSE_DIR: &str = ".lighthouse";
@@ -27,6 +29,7 @@ impl LighthouseConfig {
         Self {
             data_dir,
             p2p_listen_port,
+            db_config: DiskDBConfig::default(),
         }
     }
 }
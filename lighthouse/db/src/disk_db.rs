@@ -1,62 +1,111 @@
 extern crate rocksdb;
 
 use super::rocksdb::Error as RocksError;
-use super::rocksdb::{Options, DB};
-use super::{ClientDB, DBError, DBValue};
+use super::rocksdb::{
+    BlockBasedOptions, DBCompressionType, Direction, IteratorMode, Options,
+    WriteBatch as RocksWriteBatch, DB,
+};
+use super::COLUMNS;
+use super::{ClientDB, DBError, DBIterator, DBValue, WriteBatch, WriteOp};
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
+
+/// The compression algorithm used by RocksDB for stored data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiskDBCompression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl FromStr for DiskDBCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(DiskDBCompression::None),
+            "snappy" => Ok(DiskDBCompression::Snappy),
+            "lz4" => Ok(DiskDBCompression::Lz4),
+            "zstd" => Ok(DiskDBCompression::Zstd),
+            _ => Err(format!("Unknown compression type: {}", s)),
+        }
+    }
+}
+
+impl From<DiskDBCompression> for DBCompressionType {
+    fn from(compression: DiskDBCompression) -> Self {
+        match compression {
+            DiskDBCompression::None => DBCompressionType::None,
+            DiskDBCompression::Snappy => DBCompressionType::Snappy,
+            DiskDBCompression::Lz4 => DBCompressionType::Lz4,
+            DiskDBCompression::Zstd => DBCompressionType::Zstd,
+        }
+    }
+}
+
+/// Tuning parameters for the RocksDB database used by a `DiskDB`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskDBConfig {
+    /// The size of the block cache, in bytes.
+    pub cache_size: usize,
+    /// The compression algorithm applied to stored data.
+    pub compression: DiskDBCompression,
+    /// The maximum number of files RocksDB may keep open, -1 means unlimited.
+    pub max_open_files: i32,
+}
+
+impl Default for DiskDBConfig {
+    fn default() -> Self {
+        Self {
+            cache_size: 64 * 1024 * 1024,
+            compression: DiskDBCompression::Snappy,
+            max_open_files: -1,
+        }
+    }
+}
 
 /// A on-disk database which implements the ClientDB trait.
 ///
-/// This implementation uses RocksDB with default options.
+/// This implementation uses RocksDB, tuned with a `DiskDBConfig`.
 pub struct DiskDB {
     db: DB,
 }
 
 impl DiskDB {
-    /// Open the RocksDB database, optionally supplying columns if required.
+    /// Open the RocksDB database, creating it if it does not exist.
     ///
     /// The RocksDB database will be contained in a directory titled
-    /// "database" in the supplied path.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the database is unable to be created.
-    pub fn open(path: &Path, columns: Option<&[&str]>) -> Self {
+    /// "database" in the supplied path. A column family is created for each of the
+    /// `stores::COLUMNS` which does not already exist.
+    pub fn open(path: &Path, config: &DiskDBConfig) -> Result<Self, DBError> {
         /*
          * Initialise the options
          */
         let mut options = Options::default();
         options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        options.set_max_open_files(config.max_open_files);
+        options.set_compression_type(config.compression.into());
 
-        // TODO: ensure that columns are created (and remove
-        // the dead_code allow)
+        let mut block_options = BlockBasedOptions::default();
+        block_options.set_lru_cache(config.cache_size);
+        options.set_block_based_table_factory(&block_options);
 
         /*
          * Initialise the path
          */
-        fs::create_dir_all(&path).unwrap_or_else(|_| panic!("Unable to create {:?}", &path));
+        fs::create_dir_all(&path)
+            .map_err(|e| DBError::new(format!("Unable to create {:?}: {}", &path, e)))?;
         let db_path = path.join("database");
 
         /*
          * Open the database
          */
-        let db = match columns {
-            None => DB::open(&options, db_path),
-            Some(columns) => DB::open_cf(&options, db_path, columns),
-        }.expect("Unable to open local database");;
+        let db = DB::open_cf(&options, db_path, &COLUMNS)?;
 
-        Self { db }
-    }
-
-    /// Create a RocksDB column family. Corresponds to the
-    /// `create_cf()` function on the RocksDB API.
-    #[allow(dead_code)]
-    fn create_col(&mut self, col: &str) -> Result<(), DBError> {
-        match self.db.create_cf(col, &Options::default()) {
-            Err(e) => Err(e.into()),
-            Ok(_) => Ok(()),
-        }
+        Ok(Self { db })
     }
 }
 
@@ -131,31 +180,116 @@ impl ClientDB for DiskDB {
             }
         }
     }
+
+    /// Apply all operations in the batch atomically.
+    ///
+    /// Corresponds to the `write()` method on the RocksDB API. Will return an Err without writing
+    /// anything if any `ColumnFamily` in the batch is unknown.
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), DBError> {
+        let mut rocks_batch = RocksWriteBatch::default();
+        for op in batch.ops() {
+            match op {
+                WriteOp::Put { col, key, val } => {
+                    let handle = self.db.cf_handle(col).ok_or_else(|| DBError {
+                        message: "Unknown column".to_string(),
+                    })?;
+                    rocks_batch.put_cf(handle, key, val)?;
+                }
+                WriteOp::Delete { col, key } => {
+                    let handle = self.db.cf_handle(col).ok_or_else(|| DBError {
+                        message: "Unknown column".to_string(),
+                    })?;
+                    rocks_batch.delete_cf(handle, key)?;
+                }
+            }
+        }
+        self.db.write(rocks_batch).map_err(|e| e.into())
+    }
+
+    /// Iterate over all entries in some column.
+    ///
+    /// Corresponds to the `iterator_cf()` method on the RocksDB API.
+    fn iter_column<'a>(&'a self, col: &str) -> Result<DBIterator<'a>, DBError> {
+        match self.db.cf_handle(col) {
+            None => Err(DBError {
+                message: "Unknown column".to_string(),
+            }),
+            Some(handle) => {
+                let iter = self.db.iterator_cf(handle, IteratorMode::Start)?;
+                Ok(Box::new(
+                    iter.map(|(key, val)| (key.to_vec(), val.to_vec())),
+                ))
+            }
+        }
+    }
+
+    /// Iterate over all entries in some column with a key starting with `prefix`.
+    ///
+    /// The iterator seeks to `prefix` and stops at the first key without the prefix.
+    fn iter_prefix<'a>(&'a self, col: &str, prefix: &[u8]) -> Result<DBIterator<'a>, DBError> {
+        match self.db.cf_handle(col) {
+            None => Err(DBError {
+                message: "Unknown column".to_string(),
+            }),
+            Some(handle) => {
+                let prefix = prefix.to_vec();
+                let iter = self
+                    .db
+                    .iterator_cf(handle, IteratorMode::From(&prefix, Direction::Forward))?;
+                Ok(Box::new(
+                    iter.map(|(key, val)| (key.to_vec(), val.to_vec()))
+                        .take_while(move |(key, _)| key.starts_with(&prefix)),
+                ))
+            }
+        }
+    }
+
+    /// Iterate over all entries in some column with a key in the range `[start, end)`.
+    ///
+    /// The iterator seeks to `start` and stops at the first key which is not less than `end`.
+    fn iter_range<'a>(
+        &'a self,
+        col: &str,
+        start: &[u8],
+        end: &[u8],
+    ) -> Result<DBIterator<'a>, DBError> {
+        match self.db.cf_handle(col) {
+            None => Err(DBError {
+                message: "Unknown column".to_string(),
+            }),
+            Some(handle) => {
+                let end = end.to_vec();
+                let iter = self
+                    .db
+                    .iterator_cf(handle, IteratorMode::From(start, Direction::Forward))?;
+                Ok(Box::new(
+                    iter.map(|(key, val)| (key.to_vec(), val.to_vec()))
+                        .take_while(move |(key, _)| *key < end),
+                ))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::stores::{BLOCKS_DB_COLUMN, VALIDATOR_DB_COLUMN};
     use super::super::ClientDB;
     use super::*;
     use std::sync::Arc;
-    use std::{env, fs, thread};
+    use std::{env, fs, process, thread};
 
     #[test]
-    #[ignore]
     fn test_rocksdb_can_use_db() {
-        let pwd = env::current_dir().unwrap();
-        let path = pwd.join("testdb_please_remove");
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_rocksdb_can_use_db_{}",
+            process::id()
+        ));
         let _ = fs::remove_dir_all(&path);
-        fs::create_dir_all(&path).unwrap();
 
-        let col_name: &str = "TestColumn";
-        let column_families = vec![col_name];
+        let col_name: &str = BLOCKS_DB_COLUMN;
 
-        let mut db = DiskDB::open(&path, None);
-
-        for cf in column_families {
-            db.create_col(&cf).unwrap();
-        }
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
 
         let db = Arc::new(db);
 
@@ -193,4 +327,206 @@ mod tests {
         }
         fs::remove_dir_all(&path).unwrap();
     }
+
+    #[test]
+    fn test_rocksdb_reopen() {
+        let path =
+            env::temp_dir().join(format!("lighthouse_test_rocksdb_reopen_{}", process::id()));
+        let _ = fs::remove_dir_all(&path);
+
+        /*
+         * The columns are created on the first open and the data persists once the database is
+         * closed.
+         */
+        {
+            let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+            db.put(VALIDATOR_DB_COLUMN, "cats".as_bytes(), "lol".as_bytes())
+                .unwrap();
+        }
+
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+        assert_eq!(
+            db.get(VALIDATOR_DB_COLUMN, "cats".as_bytes()).unwrap(),
+            Some("lol".as_bytes().to_vec())
+        );
+        assert!(db.get("ColumnX", "cats".as_bytes()).is_err());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rocksdb_write_batch() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_rocksdb_write_batch_{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_b: &str = VALIDATOR_DB_COLUMN;
+
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+        db.put(col_a, "dogs".as_bytes(), "lol".as_bytes()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.put(col_a, "cats".as_bytes(), "lol".as_bytes());
+        batch.put(col_b, "cats".as_bytes(), "meow".as_bytes());
+        batch.delete(col_a, "dogs".as_bytes());
+        db.write_batch(batch).unwrap();
+
+        assert_eq!(
+            db.get(col_a, "cats".as_bytes()).unwrap().unwrap(),
+            "lol".as_bytes()
+        );
+        assert_eq!(
+            db.get(col_b, "cats".as_bytes()).unwrap().unwrap(),
+            "meow".as_bytes()
+        );
+        assert!(!db.exists(col_a, "dogs".as_bytes()).unwrap());
+
+        drop(db);
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rocksdb_write_batch_is_atomic() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_rocksdb_write_batch_is_atomic_{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_x: &str = "ColumnX";
+
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+
+        /*
+         * A batch containing an unknown column must not apply any of its operations.
+         */
+        let mut batch = WriteBatch::new();
+        batch.put(col_a, "cats".as_bytes(), "lol".as_bytes());
+        batch.put(col_x, "cats".as_bytes(), "lol".as_bytes());
+        assert!(db.write_batch(batch).is_err());
+
+        assert!(!db.exists(col_a, "cats".as_bytes()).unwrap());
+
+        drop(db);
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rocksdb_iter_column() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_rocksdb_iter_column_{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_b: &str = VALIDATOR_DB_COLUMN;
+        let col_x: &str = "ColumnX";
+
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+
+        for key in &[3_u8, 1, 2] {
+            db.put(col_a, &[*key], &[*key * 10]).unwrap();
+        }
+        db.put(col_b, &[0], &[0]).unwrap();
+
+        /*
+         * Keys are returned in ascending order and only from the given column.
+         */
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = db.iter_column(col_a).unwrap().collect();
+        assert_eq!(
+            entries,
+            vec![
+                (vec![1], vec![10]),
+                (vec![2], vec![20]),
+                (vec![3], vec![30])
+            ]
+        );
+
+        assert!(db.iter_column(col_x).is_err());
+
+        drop(db);
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rocksdb_iter_prefix() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_rocksdb_iter_prefix_{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+
+        let col_a: &str = BLOCKS_DB_COLUMN;
+
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+
+        db.put(col_a, "cat".as_bytes(), "a".as_bytes()).unwrap();
+        db.put(col_a, "cats".as_bytes(), "b".as_bytes()).unwrap();
+        db.put(col_a, "catz".as_bytes(), "c".as_bytes()).unwrap();
+        db.put(col_a, "ca".as_bytes(), "d".as_bytes()).unwrap();
+        db.put(col_a, "dog".as_bytes(), "e".as_bytes()).unwrap();
+
+        let keys: Vec<Vec<u8>> = db
+            .iter_prefix(col_a, "cat".as_bytes())
+            .unwrap()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "cat".as_bytes().to_vec(),
+                "cats".as_bytes().to_vec(),
+                "catz".as_bytes().to_vec()
+            ]
+        );
+
+        assert_eq!(db.iter_prefix(col_a, "bird".as_bytes()).unwrap().count(), 0);
+
+        drop(db);
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rocksdb_iter_range() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_rocksdb_iter_range_{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+
+        let col_a: &str = BLOCKS_DB_COLUMN;
+
+        let db = DiskDB::open(&path, &DiskDBConfig::default()).unwrap();
+
+        for key in 0..10_u8 {
+            db.put(col_a, &[key], &[key]).unwrap();
+        }
+
+        let keys: Vec<Vec<u8>> = db
+            .iter_range(col_a, &[3], &[6])
+            .unwrap()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec![vec![3], vec![4], vec![5]]);
+
+        assert_eq!(db.iter_range(col_a, &[6], &[3]).unwrap().count(), 0);
+        assert_eq!(db.iter_range(col_a, &[0], &[255]).unwrap().count(), 10);
+
+        drop(db);
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compression_from_str() {
+        assert_eq!("none".parse(), Ok(DiskDBCompression::None));
+        assert_eq!("snappy".parse(), Ok(DiskDBCompression::Snappy));
+        assert_eq!("lz4".parse(), Ok(DiskDBCompression::Lz4));
+        assert_eq!("zstd".parse(), Ok(DiskDBCompression::Zstd));
+        assert!("gzip".parse::<DiskDBCompression>().is_err());
+    }
 }
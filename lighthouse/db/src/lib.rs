@@ -1,6 +1,8 @@
-extern crate blake2_rfc as blake2;
 extern crate bls;
 extern crate rocksdb;
+extern crate ssz;
+#[macro_use]
+extern crate ssz_derive;
 
 mod disk_db;
 mod memory_db;
@@ -9,6 +11,6 @@ mod traits;
 
 use self::stores::COLUMNS;
 
-pub use self::disk_db::DiskDB;
+pub use self::disk_db::{DiskDB, DiskDBCompression, DiskDBConfig};
 pub use self::memory_db::MemoryDB;
-pub use self::traits::{ClientDB, DBError, DBValue};
+pub use self::traits::{ClientDB, DBError, DBIterator, DBValue, WriteBatch, WriteOp};
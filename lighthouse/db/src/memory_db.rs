@@ -1,19 +1,19 @@
-use super::blake2::blake2b::blake2b;
 use super::COLUMNS;
-use super::{ClientDB, DBError, DBValue};
-use std::collections::{HashMap, HashSet};
+use super::{ClientDB, DBError, DBIterator, DBValue, WriteBatch, WriteOp};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::RwLock;
 
-type DBHashMap = HashMap<Vec<u8>, Vec<u8>>;
-type ColumnHashSet = HashSet<String>;
+type ColumnMap = BTreeMap<Vec<u8>, Vec<u8>>;
+type DBHashMap = HashMap<String, ColumnMap>;
 
 /// An in-memory database implementing the ClientDB trait.
 ///
 /// It is not particularily optimized, it exists for ease and speed of testing. It's not expected
 /// this DB would be used outside of tests.
+///
+/// Each column is an ordered map so that keys may be iterated in the same order as RocksDB.
 pub struct MemoryDB {
     db: RwLock<DBHashMap>,
-    known_columns: RwLock<ColumnHashSet>,
 }
 
 impl MemoryDB {
@@ -22,20 +22,29 @@ impl MemoryDB {
     /// All columns must be supplied initially, you will get an error if you try to access a column
     /// that was not declared here. This condition is enforced artificially to simulate RocksDB.
     pub fn open() -> Self {
-        let db: DBHashMap = HashMap::new();
-        let mut known_columns: ColumnHashSet = HashSet::new();
+        let mut db: DBHashMap = HashMap::new();
         for col in &COLUMNS {
-            known_columns.insert(col.to_string());
+            db.insert(col.to_string(), BTreeMap::new());
         }
         Self {
             db: RwLock::new(db),
-            known_columns: RwLock::new(known_columns),
         }
     }
 
-    /// Hashes a key and a column name in order to get a unique key for the supplied column.
-    fn get_key_for_col(col: &str, key: &[u8]) -> Vec<u8> {
-        blake2b(32, col.as_bytes(), key).as_bytes().to_vec()
+    /// Collect the entries yielded by `iter` into an iterator which does not borrow the database.
+    fn collect_iter<'a, 'b, I>(iter: I) -> DBIterator<'a>
+    where
+        I: Iterator<Item = (&'b Vec<u8>, &'b Vec<u8>)>,
+    {
+        let entries: Vec<(Vec<u8>, DBValue)> =
+            iter.map(|(key, val)| (key.clone(), val.clone())).collect();
+        Box::new(entries.into_iter())
+    }
+}
+
+fn unknown_column() -> DBError {
+    DBError {
+        message: "Unknown column".to_string(),
     }
 }
 
@@ -44,15 +53,10 @@ impl ClientDB for MemoryDB {
     fn get(&self, col: &str, key: &[u8]) -> Result<Option<DBValue>, DBError> {
         // Panic if the DB locks are poisoned.
         let db = self.db.read().unwrap();
-        let known_columns = self.known_columns.read().unwrap();
-
-        if known_columns.contains(&col.to_string()) {
-            let column_key = MemoryDB::get_key_for_col(col, key);
-            Ok(db.get(&column_key).and_then(|val| Some(val.clone())))
-        } else {
-            Err(DBError {
-                message: "Unknown column".to_string(),
-            })
+
+        match db.get(col) {
+            Some(column) => Ok(column.get(key).cloned()),
+            None => Err(unknown_column()),
         }
     }
 
@@ -60,16 +64,13 @@ impl ClientDB for MemoryDB {
     fn put(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), DBError> {
         // Panic if the DB locks are poisoned.
         let mut db = self.db.write().unwrap();
-        let known_columns = self.known_columns.read().unwrap();
-
-        if known_columns.contains(&col.to_string()) {
-            let column_key = MemoryDB::get_key_for_col(col, key);
-            db.insert(column_key, val.to_vec());
-            Ok(())
-        } else {
-            Err(DBError {
-                message: "Unknown column".to_string(),
-            })
+
+        match db.get_mut(col) {
+            Some(column) => {
+                column.insert(key.to_vec(), val.to_vec());
+                Ok(())
+            }
+            None => Err(unknown_column()),
         }
     }
 
@@ -77,15 +78,10 @@ impl ClientDB for MemoryDB {
     fn exists(&self, col: &str, key: &[u8]) -> Result<bool, DBError> {
         // Panic if the DB locks are poisoned.
         let db = self.db.read().unwrap();
-        let known_columns = self.known_columns.read().unwrap();
-
-        if known_columns.contains(&col.to_string()) {
-            let column_key = MemoryDB::get_key_for_col(col, key);
-            Ok(db.contains_key(&column_key))
-        } else {
-            Err(DBError {
-                message: "Unknown column".to_string(),
-            })
+
+        match db.get(col) {
+            Some(column) => Ok(column.contains_key(key)),
+            None => Err(unknown_column()),
         }
     }
 
@@ -93,24 +89,91 @@ impl ClientDB for MemoryDB {
     fn delete(&self, col: &str, key: &[u8]) -> Result<(), DBError> {
         // Panic if the DB locks are poisoned.
         let mut db = self.db.write().unwrap();
-        let known_columns = self.known_columns.read().unwrap();
-
-        if known_columns.contains(&col.to_string()) {
-            let column_key = MemoryDB::get_key_for_col(col, key);
-            db.remove(&column_key);
-            Ok(())
-        } else {
-            Err(DBError {
-                message: "Unknown column".to_string(),
-            })
+
+        match db.get_mut(col) {
+            Some(column) => {
+                column.remove(key);
+                Ok(())
+            }
+            None => Err(unknown_column()),
+        }
+    }
+
+    /// Apply all operations in the batch while holding the write lock.
+    ///
+    /// All columns are checked before any operation is applied, so a batch referencing an
+    /// unknown column has no effect.
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), DBError> {
+        // Panic if the DB locks are poisoned.
+        let mut db = self.db.write().unwrap();
+
+        let all_known = batch.ops().iter().all(|op| match op {
+            WriteOp::Put { col, .. } | WriteOp::Delete { col, .. } => db.contains_key(col),
+        });
+        if !all_known {
+            return Err(unknown_column());
+        }
+
+        for op in batch.ops() {
+            match op {
+                WriteOp::Put { col, key, val } => {
+                    db.get_mut(col)
+                        .ok_or_else(unknown_column)?
+                        .insert(key.clone(), val.clone());
+                }
+                WriteOp::Delete { col, key } => {
+                    db.get_mut(col).ok_or_else(unknown_column)?.remove(key);
+                }
+            }
         }
+        Ok(())
+    }
+
+    /// Iterate over all entries in the column.
+    ///
+    /// The entries are copied from the database, later writes are not reflected in the iterator.
+    fn iter_column<'a>(&'a self, col: &str) -> Result<DBIterator<'a>, DBError> {
+        // Panic if the DB locks are poisoned.
+        let db = self.db.read().unwrap();
+        let column = db.get(col).ok_or_else(unknown_column)?;
+        Ok(MemoryDB::collect_iter(column.iter()))
+    }
+
+    /// Iterate over all entries in the column with a key starting with `prefix`.
+    fn iter_prefix<'a>(&'a self, col: &str, prefix: &[u8]) -> Result<DBIterator<'a>, DBError> {
+        // Panic if the DB locks are poisoned.
+        let db = self.db.read().unwrap();
+        let column = db.get(col).ok_or_else(unknown_column)?;
+        Ok(MemoryDB::collect_iter(
+            column
+                .range(prefix.to_vec()..)
+                .take_while(|(key, _)| key.starts_with(prefix)),
+        ))
+    }
+
+    /// Iterate over all entries in the column with a key in the range `[start, end)`.
+    fn iter_range<'a>(
+        &'a self,
+        col: &str,
+        start: &[u8],
+        end: &[u8],
+    ) -> Result<DBIterator<'a>, DBError> {
+        // Panic if the DB locks are poisoned.
+        let db = self.db.read().unwrap();
+        let column = db.get(col).ok_or_else(unknown_column)?;
+        if start >= end {
+            return Ok(Box::new(vec![].into_iter()));
+        }
+        Ok(MemoryDB::collect_iter(
+            column.range(start.to_vec()..end.to_vec()),
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::stores::{BLOCKS_DB_COLUMN, VALIDATOR_DB_COLUMN};
-    use super::super::ClientDB;
+    use super::super::{ClientDB, WriteBatch};
     use super::*;
     use std::sync::Arc;
     use std::thread;
@@ -194,6 +257,151 @@ mod tests {
         assert_eq!(false, db.exists(col_b, "dogs".as_bytes()).unwrap());
     }
 
+    #[test]
+    fn test_memorydb_write_batch() {
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_b: &str = VALIDATOR_DB_COLUMN;
+
+        let db = MemoryDB::open();
+        db.put(col_a, "dogs".as_bytes(), "lol".as_bytes()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.put(col_a, "cats".as_bytes(), "lol".as_bytes());
+        batch.put(col_b, "cats".as_bytes(), "meow".as_bytes());
+        batch.delete(col_a, "dogs".as_bytes());
+        db.write_batch(batch).unwrap();
+
+        assert_eq!(
+            db.get(col_a, "cats".as_bytes()).unwrap().unwrap(),
+            "lol".as_bytes()
+        );
+        assert_eq!(
+            db.get(col_b, "cats".as_bytes()).unwrap().unwrap(),
+            "meow".as_bytes()
+        );
+        assert!(!db.exists(col_a, "dogs".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_write_batch_get() {
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_b: &str = VALIDATOR_DB_COLUMN;
+
+        let mut batch = WriteBatch::new();
+        batch.put(col_a, "cats".as_bytes(), "lol".as_bytes());
+        batch.put(col_a, "dogs".as_bytes(), "lol".as_bytes());
+        batch.delete(col_a, "dogs".as_bytes());
+        batch.put(col_a, "cats".as_bytes(), "meow".as_bytes());
+
+        /*
+         * The last operation on a key determines its value.
+         */
+        assert_eq!(
+            batch.get(col_a, "cats".as_bytes()),
+            Some(Some("meow".as_bytes()))
+        );
+        assert_eq!(batch.get(col_a, "dogs".as_bytes()), Some(None));
+        assert_eq!(batch.get(col_a, "birds".as_bytes()), None);
+        assert_eq!(batch.get(col_b, "cats".as_bytes()), None);
+    }
+
+    #[test]
+    fn test_memorydb_write_batch_is_atomic() {
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_x: &str = "ColumnX";
+
+        let db = MemoryDB::open();
+
+        /*
+         * A batch containing an unknown column must not apply any of its operations.
+         */
+        let mut batch = WriteBatch::new();
+        batch.put(col_a, "cats".as_bytes(), "lol".as_bytes());
+        batch.put(col_x, "cats".as_bytes(), "lol".as_bytes());
+        assert!(db.write_batch(batch).is_err());
+
+        assert!(!db.exists(col_a, "cats".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_memorydb_iter_column() {
+        let col_a: &str = BLOCKS_DB_COLUMN;
+        let col_b: &str = VALIDATOR_DB_COLUMN;
+        let col_x: &str = "ColumnX";
+
+        let db = MemoryDB::open();
+
+        for key in &[3_u8, 1, 2] {
+            db.put(col_a, &[*key], &[*key * 10]).unwrap();
+        }
+        db.put(col_b, &[0], &[0]).unwrap();
+
+        /*
+         * Keys are returned in ascending order and only from the given column.
+         */
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = db.iter_column(col_a).unwrap().collect();
+        assert_eq!(
+            entries,
+            vec![
+                (vec![1], vec![10]),
+                (vec![2], vec![20]),
+                (vec![3], vec![30])
+            ]
+        );
+
+        assert!(db.iter_column(col_x).is_err());
+    }
+
+    #[test]
+    fn test_memorydb_iter_prefix() {
+        let col_a: &str = BLOCKS_DB_COLUMN;
+
+        let db = MemoryDB::open();
+
+        db.put(col_a, "cat".as_bytes(), "a".as_bytes()).unwrap();
+        db.put(col_a, "cats".as_bytes(), "b".as_bytes()).unwrap();
+        db.put(col_a, "catz".as_bytes(), "c".as_bytes()).unwrap();
+        db.put(col_a, "ca".as_bytes(), "d".as_bytes()).unwrap();
+        db.put(col_a, "dog".as_bytes(), "e".as_bytes()).unwrap();
+
+        let keys: Vec<Vec<u8>> = db
+            .iter_prefix(col_a, "cat".as_bytes())
+            .unwrap()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "cat".as_bytes().to_vec(),
+                "cats".as_bytes().to_vec(),
+                "catz".as_bytes().to_vec()
+            ]
+        );
+
+        assert_eq!(db.iter_prefix(col_a, "bird".as_bytes()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_memorydb_iter_range() {
+        let col_a: &str = BLOCKS_DB_COLUMN;
+
+        let db = MemoryDB::open();
+
+        for key in 0..10_u8 {
+            db.put(col_a, &[key], &[key]).unwrap();
+        }
+
+        let keys: Vec<Vec<u8>> = db
+            .iter_range(col_a, &[3], &[6])
+            .unwrap()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec![vec![3], vec![4], vec![5]]);
+
+        assert_eq!(db.iter_range(col_a, &[6], &[3]).unwrap().count(), 0);
+        assert_eq!(db.iter_range(col_a, &[0], &[255]).unwrap().count(), 10);
+    }
+
     #[test]
     fn test_memorydb_threading() {
         let col_name: &str = BLOCKS_DB_COLUMN;
@@ -1,20 +1,47 @@
+extern crate bytes;
+extern crate ssz;
 extern crate ssz_helpers;
 
+use self::bytes::{BufMut, BytesMut};
+use self::ssz::{ssz_encode, Decodable};
 use self::ssz_helpers::ssz_beacon_block::SszBeaconBlock;
 use super::BLOCKS_DB_COLUMN as DB_COLUMN;
-use super::{ClientDB, DBError};
+use super::{ClientDB, DBError, WriteBatch};
 use std::sync::Arc;
 
 type BeaconBlockHash = Vec<u8>;
 type BeaconBlockSsz = Vec<u8>;
 
+/// The length of a block hash, in bytes.
+const HASH_SIZE: usize = 32;
+
 #[derive(Clone, Debug, PartialEq)]
-pub enum BeaconBlockAtSlotError {
+pub enum BeaconBlockStoreError {
     UnknownBeaconBlock,
     InvalidBeaconBlock,
+    InvalidBlockHash,
+    InvalidIndex,
     DBError(String),
 }
 
+/// Prefixes for the secondary indexes stored alongside blocks in the blocks column.
+///
+/// Blocks are keyed by their 32 byte hash. No index key is 32 bytes long (hash-keyed entries are
+/// longer, slot-keyed entries and the canonical head key are shorter) so there can be no
+/// collision between a block and an index entry.
+#[derive(Debug, PartialEq)]
+enum KeyPrefixes {
+    /// Block hash to the slot of that block.
+    Slot,
+    /// Block hash to the concatenated hashes of the children of that block.
+    Children,
+    /// Slot to the hash of the block at that slot in the canonical chain.
+    CanonicalSlot,
+}
+
+/// The key storing the hash of the head of the canonical chain.
+const CANONICAL_HEAD_KEY: &[u8] = b"canonical_head_hash";
+
 pub struct BeaconBlockStore<T>
 where
     T: ClientDB,
@@ -27,10 +54,88 @@ impl<T: ClientDB> BeaconBlockStore<T> {
         Self { db }
     }
 
+    fn prefix_bytes(&self, key_prefix: &KeyPrefixes) -> Vec<u8> {
+        match key_prefix {
+            KeyPrefixes::Slot => b"block_slot".to_vec(),
+            KeyPrefixes::Children => b"block_children".to_vec(),
+            KeyPrefixes::CanonicalSlot => b"canonical_slot".to_vec(),
+        }
+    }
+
+    fn get_db_key_for_hash(&self, key_prefix: &KeyPrefixes, hash: &[u8]) -> Vec<u8> {
+        let prefix = self.prefix_bytes(key_prefix);
+        let mut buf = BytesMut::with_capacity(prefix.len() + hash.len());
+        buf.put(prefix);
+        buf.put(hash);
+        buf.take().to_vec()
+    }
+
+    fn get_db_key_for_slot(&self, key_prefix: &KeyPrefixes, slot: u64) -> Vec<u8> {
+        let prefix = self.prefix_bytes(key_prefix);
+        let mut buf = BytesMut::with_capacity(prefix.len() + 8);
+        buf.put(prefix);
+        buf.put_u64_be(slot);
+        buf.take().to_vec()
+    }
+
+    /// Store some serialized block without updating any of the block indexes.
+    ///
+    /// Prefer `put_block`, blocks stored with this function are not found by index lookups.
     pub fn put_serialized_block(&self, hash: &[u8], ssz: &[u8]) -> Result<(), DBError> {
         self.db.put(DB_COLUMN, hash, ssz)
     }
 
+    /// Store some serialized block and add it to the slot and children indexes.
+    ///
+    /// The block and its index entries are written atomically. The canonical chain index is not
+    /// modified, see `set_canonical_head`.
+    pub fn put_block(&self, hash: &[u8], ssz: &[u8]) -> Result<(), BeaconBlockStoreError> {
+        let mut batch = WriteBatch::new();
+        self.put_block_in_batch(&mut batch, hash, ssz)?;
+        self.db.write_batch(batch)?;
+        Ok(())
+    }
+
+    /// As per `put_block`, but the writes are added to `batch` rather than applied.
+    ///
+    /// Any earlier writes in `batch` are taken into account.
+    pub fn put_block_in_batch(
+        &self,
+        batch: &mut WriteBatch,
+        hash: &[u8],
+        ssz: &[u8],
+    ) -> Result<(), BeaconBlockStoreError> {
+        if hash.len() != HASH_SIZE {
+            return Err(BeaconBlockStoreError::InvalidBlockHash);
+        }
+        let block = SszBeaconBlock::from_slice(ssz)
+            .map_err(|_| BeaconBlockStoreError::InvalidBeaconBlock)?;
+
+        batch.put(DB_COLUMN, hash, ssz);
+
+        let slot_key = self.get_db_key_for_hash(&KeyPrefixes::Slot, hash);
+        batch.put(DB_COLUMN, &slot_key, &ssz_encode(&block.slot()));
+
+        if let Some(parent_hash) = block.parent_hash() {
+            let mut children = self.block_children_in_batch(batch, parent_hash)?;
+            if !children.iter().any(|child| child[..] == hash[..]) {
+                children.push(hash.to_vec());
+                let children_key = self.get_db_key_for_hash(&KeyPrefixes::Children, parent_hash);
+                batch.put(DB_COLUMN, &children_key, &children.concat());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read `key` from the blocks column as it will be once `batch` is applied.
+    fn get_in_batch(&self, batch: &WriteBatch, key: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
+        match batch.get(DB_COLUMN, key) {
+            Some(val) => Ok(val.map(|val| val.to_vec())),
+            None => self.db.get(DB_COLUMN, key),
+        }
+    }
+
     pub fn get_serialized_block(&self, hash: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
         self.db.get(DB_COLUMN, hash)
     }
@@ -39,8 +144,258 @@ impl<T: ClientDB> BeaconBlockStore<T> {
         self.db.exists(DB_COLUMN, hash)
     }
 
+    /// Delete a block and remove it from the block indexes.
+    ///
+    /// The block and its index entries are removed atomically. The children of the block (if any)
+    /// are not deleted, but the index of its children is.
     pub fn delete_block(&self, hash: &[u8]) -> Result<(), DBError> {
-        self.db.delete(DB_COLUMN, hash)
+        let mut batch = WriteBatch::new();
+        self.delete_block_in_batch(&mut batch, hash)?;
+        self.db.write_batch(batch)
+    }
+
+    /// As per `delete_block`, but the deletions are added to `batch` rather than applied.
+    ///
+    /// Any earlier writes in `batch` are taken into account, so many blocks (e.g., siblings) may
+    /// be deleted in the one batch.
+    pub fn delete_block_in_batch(
+        &self,
+        batch: &mut WriteBatch,
+        hash: &[u8],
+    ) -> Result<(), DBError> {
+        if let Some(ssz) = self.get_in_batch(batch, hash)? {
+            if let Ok(block) = SszBeaconBlock::from_slice(&ssz) {
+                if let Some(parent_hash) = block.parent_hash() {
+                    let children_key =
+                        self.get_db_key_for_hash(&KeyPrefixes::Children, parent_hash);
+                    if let Some(bytes) = self.get_in_batch(batch, &children_key)? {
+                        let children: Vec<u8> = bytes
+                            .chunks(HASH_SIZE)
+                            .filter(|child| child[..] != hash[..])
+                            .flat_map(|child| child.to_vec())
+                            .collect();
+                        batch.put(DB_COLUMN, &children_key, &children);
+                    }
+                }
+
+                let canonical_key =
+                    self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, block.slot());
+                if let Some(canonical_hash) = self.get_in_batch(batch, &canonical_key)? {
+                    if canonical_hash[..] == hash[..] {
+                        batch.delete(DB_COLUMN, &canonical_key);
+                    }
+                }
+            }
+        }
+
+        let slot_key = self.get_db_key_for_hash(&KeyPrefixes::Slot, hash);
+        batch.delete(DB_COLUMN, &slot_key);
+        let children_key = self.get_db_key_for_hash(&KeyPrefixes::Children, hash);
+        batch.delete(DB_COLUMN, &children_key);
+        batch.delete(DB_COLUMN, hash);
+
+        Ok(())
+    }
+
+    /// Returns the slot of the block with the given hash, or `None` if the block is unknown.
+    ///
+    /// Blocks which are not indexed (see `put_serialized_block`) are read from the database.
+    pub fn block_slot(&self, hash: &[u8]) -> Result<Option<u64>, BeaconBlockStoreError> {
+        self.block_slot_in_batch(&WriteBatch::new(), hash)
+    }
+
+    fn block_slot_in_batch(
+        &self,
+        batch: &WriteBatch,
+        hash: &[u8],
+    ) -> Result<Option<u64>, BeaconBlockStoreError> {
+        let slot_key = self.get_db_key_for_hash(&KeyPrefixes::Slot, hash);
+        match self.get_in_batch(batch, &slot_key)? {
+            Some(bytes) => {
+                let (slot, _) =
+                    u64::ssz_decode(&bytes, 0).map_err(|_| BeaconBlockStoreError::InvalidIndex)?;
+                Ok(Some(slot))
+            }
+            None => match self.get_in_batch(batch, hash)? {
+                Some(ssz) => {
+                    let block = SszBeaconBlock::from_slice(&ssz)
+                        .map_err(|_| BeaconBlockStoreError::InvalidBeaconBlock)?;
+                    Ok(Some(block.slot()))
+                }
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Returns the hashes of all known children of the block with the given hash.
+    pub fn block_children(
+        &self,
+        hash: &[u8],
+    ) -> Result<Vec<BeaconBlockHash>, BeaconBlockStoreError> {
+        self.block_children_in_batch(&WriteBatch::new(), hash)
+    }
+
+    fn block_children_in_batch(
+        &self,
+        batch: &WriteBatch,
+        hash: &[u8],
+    ) -> Result<Vec<BeaconBlockHash>, BeaconBlockStoreError> {
+        let children_key = self.get_db_key_for_hash(&KeyPrefixes::Children, hash);
+        match self.get_in_batch(batch, &children_key)? {
+            Some(bytes) => {
+                if bytes.len() % HASH_SIZE != 0 {
+                    return Err(BeaconBlockStoreError::InvalidIndex);
+                }
+                Ok(bytes
+                    .chunks(HASH_SIZE)
+                    .map(|child| child.to_vec())
+                    .collect())
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the hashes of all stored blocks, in ascending order.
+    ///
+    /// Index entries in the blocks column are skipped, they are distinguished from blocks by the
+    /// length of their key.
+    pub fn block_hashes(&self) -> Result<Vec<BeaconBlockHash>, DBError> {
+        Ok(self
+            .db
+            .iter_column(DB_COLUMN)?
+            .map(|(key, _)| key)
+            .filter(|key| key.len() == HASH_SIZE)
+            .collect())
+    }
+
+    /// Returns the `(slot, block_hash)` of each block in the canonical chain with a slot greater
+    /// than or equal to `start_slot` and less than `end_slot`, in ascending slot order.
+    pub fn canonical_block_hashes(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+    ) -> Result<Vec<(u64, BeaconBlockHash)>, BeaconBlockStoreError> {
+        let start = self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, start_slot);
+        let end = self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, end_slot);
+        let prefix_len = self.prefix_bytes(&KeyPrefixes::CanonicalSlot).len();
+
+        let mut hashes = vec![];
+        for (key, hash) in self.db.iter_range(DB_COLUMN, &start, &end)? {
+            let (slot, _) = u64::ssz_decode(&key, prefix_len)
+                .map_err(|_| BeaconBlockStoreError::InvalidIndex)?;
+            hashes.push((slot, hash));
+        }
+        Ok(hashes)
+    }
+
+    /// Returns the hash of the head of the canonical chain, if one has been set.
+    pub fn canonical_head(&self) -> Result<Option<BeaconBlockHash>, DBError> {
+        self.db.get(DB_COLUMN, CANONICAL_HEAD_KEY)
+    }
+
+    /// Returns the hash of the block at `slot` in the canonical chain.
+    ///
+    /// Returns `None` if the slot was skipped or is later than the canonical head.
+    pub fn canonical_block_hash_at_slot(
+        &self,
+        slot: u64,
+    ) -> Result<Option<BeaconBlockHash>, DBError> {
+        let key = self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, slot);
+        self.db.get(DB_COLUMN, &key)
+    }
+
+    /// Returns true if the block with the given hash (at the given slot) is in the canonical
+    /// chain once `batch` is applied.
+    fn is_canonical_at_slot(
+        &self,
+        batch: &WriteBatch,
+        hash: &[u8],
+        slot: u64,
+    ) -> Result<bool, DBError> {
+        let key = self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, slot);
+        match self.get_in_batch(batch, &key)? {
+            Some(canonical_hash) => Ok(canonical_hash[..] == hash[..]),
+            None => Ok(false),
+        }
+    }
+
+    /// Set the head of the canonical chain, updating the canonical slot index.
+    ///
+    /// The index is updated by walking back from the new head until a block which is already in
+    /// the canonical chain is found, so the cost is proportional to the depth of any re-org rather
+    /// than the length of the chain. All changes to the index are written atomically.
+    pub fn set_canonical_head(&self, head_hash: &[u8]) -> Result<(), BeaconBlockStoreError> {
+        let mut batch = WriteBatch::new();
+        self.set_canonical_head_in_batch(&mut batch, head_hash)?;
+        self.db.write_batch(batch)?;
+        Ok(())
+    }
+
+    /// As per `set_canonical_head`, but the writes are added to `batch` rather than applied.
+    ///
+    /// Any earlier writes in `batch` are taken into account, so the head may be a block which is
+    /// stored by the same batch.
+    pub fn set_canonical_head_in_batch(
+        &self,
+        batch: &mut WriteBatch,
+        head_hash: &[u8],
+    ) -> Result<(), BeaconBlockStoreError> {
+        let head_slot = self
+            .block_slot_in_batch(batch, head_hash)?
+            .ok_or(BeaconBlockStoreError::UnknownBeaconBlock)?;
+
+        /*
+         * Remove any entries from the previous canonical chain which are beyond the new head.
+         */
+        if let Some(previous_head) = self.get_in_batch(batch, CANONICAL_HEAD_KEY)? {
+            if let Some(previous_slot) = self.block_slot_in_batch(batch, &previous_head)? {
+                for slot in (head_slot + 1)..=previous_slot {
+                    self.delete_canonical_slot(batch, slot);
+                }
+            }
+        }
+
+        /*
+         * Walk back from the new head, replacing the entries of the previous canonical chain.
+         */
+        let mut hash = head_hash.to_vec();
+        let mut slot = head_slot;
+        while !self.is_canonical_at_slot(batch, &hash, slot)? {
+            let key = self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, slot);
+            batch.put(DB_COLUMN, &key, &hash);
+
+            let ssz = self
+                .get_in_batch(batch, &hash)?
+                .ok_or(BeaconBlockStoreError::UnknownBeaconBlock)?;
+            let block = SszBeaconBlock::from_slice(&ssz)
+                .map_err(|_| BeaconBlockStoreError::InvalidBeaconBlock)?;
+            let parent_hash = match block.parent_hash() {
+                Some(parent_hash) => parent_hash.to_vec(),
+                None => break,
+            };
+            let parent_slot = match self.block_slot_in_batch(batch, &parent_hash)? {
+                Some(parent_slot) => parent_slot,
+                None => break,
+            };
+
+            /*
+             * Slots skipped by the new chain must not refer to blocks of the previous chain.
+             */
+            for skipped_slot in (parent_slot + 1)..slot {
+                self.delete_canonical_slot(batch, skipped_slot);
+            }
+
+            hash = parent_hash;
+            slot = parent_slot;
+        }
+
+        batch.put(DB_COLUMN, CANONICAL_HEAD_KEY, head_hash);
+        Ok(())
+    }
+
+    fn delete_canonical_slot(&self, batch: &mut WriteBatch, slot: u64) {
+        let key = self.get_db_key_for_slot(&KeyPrefixes::CanonicalSlot, slot);
+        batch.delete(DB_COLUMN, &key);
     }
 
     /// Retrieve the block at a slot given a "head_hash" and a slot.
@@ -49,35 +404,51 @@ impl<T: ClientDB> BeaconBlockStore<T> {
     /// slot.
     ///
     /// This function will read each block down the chain until it finds a block with the given
-    /// slot number. If the slot is skipped, the function will return None.
+    /// slot number, or until it reaches a block in the canonical chain at which point the
+    /// canonical slot index is used. If the slot is skipped, the function will return None.
     ///
     /// If a block is found, a tuple of (block_hash, serialized_block) is returned.
     pub fn block_at_slot(
         &self,
         head_hash: &[u8],
         slot: u64,
-    ) -> Result<Option<(BeaconBlockHash, BeaconBlockSsz)>, BeaconBlockAtSlotError> {
-        match self.get_serialized_block(head_hash)? {
-            None => Err(BeaconBlockAtSlotError::UnknownBeaconBlock),
-            Some(ssz) => {
-                let block = SszBeaconBlock::from_slice(&ssz)
-                    .map_err(|_| BeaconBlockAtSlotError::InvalidBeaconBlock)?;
-                match block.slot() {
-                    s if s == slot => Ok(Some((head_hash.to_vec(), ssz.to_vec()))),
-                    s if s < slot => Ok(None),
-                    _ => match block.parent_hash() {
-                        Some(parent_hash) => self.block_at_slot(parent_hash, slot),
-                        None => Err(BeaconBlockAtSlotError::UnknownBeaconBlock),
-                    },
-                }
+    ) -> Result<Option<(BeaconBlockHash, BeaconBlockSsz)>, BeaconBlockStoreError> {
+        let mut hash = head_hash.to_vec();
+        loop {
+            let ssz = self
+                .get_serialized_block(&hash)?
+                .ok_or(BeaconBlockStoreError::UnknownBeaconBlock)?;
+            let block = SszBeaconBlock::from_slice(&ssz)
+                .map_err(|_| BeaconBlockStoreError::InvalidBeaconBlock)?;
+
+            let block_slot = block.slot();
+            if block_slot == slot {
+                return Ok(Some((hash, ssz.to_vec())));
+            } else if block_slot < slot {
+                return Ok(None);
+            } else if self.is_canonical_at_slot(&WriteBatch::new(), &hash, block_slot)? {
+                return match self.canonical_block_hash_at_slot(slot)? {
+                    None => Ok(None),
+                    Some(canonical_hash) => {
+                        let ssz = self
+                            .get_serialized_block(&canonical_hash)?
+                            .ok_or(BeaconBlockStoreError::UnknownBeaconBlock)?;
+                        Ok(Some((canonical_hash, ssz)))
+                    }
+                };
             }
+
+            hash = block
+                .parent_hash()
+                .ok_or(BeaconBlockStoreError::UnknownBeaconBlock)?
+                .to_vec();
         }
     }
 }
 
-impl From<DBError> for BeaconBlockAtSlotError {
+impl From<DBError> for BeaconBlockStoreError {
     fn from(e: DBError) -> Self {
-        BeaconBlockAtSlotError::DBError(e.message)
+        BeaconBlockStoreError::DBError(e.message)
     }
 }
 
@@ -184,7 +555,7 @@ mod tests {
         db.put(DB_COLUMN, hash, ssz).unwrap();
         assert_eq!(
             store.block_at_slot(hash, 42),
-            Err(BeaconBlockAtSlotError::InvalidBeaconBlock)
+            Err(BeaconBlockStoreError::InvalidBeaconBlock)
         );
     }
 
@@ -200,7 +571,7 @@ mod tests {
         db.put(DB_COLUMN, hash, ssz).unwrap();
         assert_eq!(
             store.block_at_slot(other_hash, 42),
-            Err(BeaconBlockAtSlotError::UnknownBeaconBlock)
+            Err(BeaconBlockStoreError::UnknownBeaconBlock)
         );
     }
 
@@ -309,6 +680,295 @@ mod tests {
         assert_eq!(ssz, None);
 
         let ssz = bs.block_at_slot(&Hash256::from("unknown".as_bytes()), 2);
-        assert_eq!(ssz, Err(BeaconBlockAtSlotError::UnknownBeaconBlock));
+        assert_eq!(ssz, Err(BeaconBlockStoreError::UnknownBeaconBlock));
+    }
+
+    /// Store a block at `slot` with the given parent using `put_block`, returning its hash.
+    fn put_test_block(
+        bs: &BeaconBlockStore<MemoryDB>,
+        name: &str,
+        slot: u64,
+        parent_hash: &Hash256,
+    ) -> Hash256 {
+        let hash = Hash256::from(name.as_bytes());
+        let mut block = BeaconBlock::zero();
+        block.slot = slot;
+        block.ancestor_hashes.push(*parent_hash);
+        let mut s = SszStream::new();
+        s.append(&block);
+        bs.put_block(&hash, &s.drain()).unwrap();
+        hash
+    }
+
+    #[test]
+    fn test_put_block_indexes() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        let genesis = Hash256::from("genesis".as_bytes());
+        let a = put_test_block(&bs, "a", 1, &genesis);
+        let b = put_test_block(&bs, "b", 2, &genesis);
+        let c = put_test_block(&bs, "c", 3, &a);
+
+        assert_eq!(bs.block_slot(&a).unwrap(), Some(1));
+        assert_eq!(bs.block_slot(&c).unwrap(), Some(3));
+        assert_eq!(bs.block_slot(&genesis).unwrap(), None);
+
+        assert_eq!(
+            bs.block_children(&genesis).unwrap(),
+            vec![a.to_vec(), b.to_vec()]
+        );
+        assert_eq!(bs.block_children(&a).unwrap(), vec![c.to_vec()]);
+        assert!(bs.block_children(&c).unwrap().is_empty());
+
+        /*
+         * Storing a block twice does not duplicate it in the children index.
+         */
+        put_test_block(&bs, "c", 3, &a);
+        assert_eq!(bs.block_children(&a).unwrap(), vec![c.to_vec()]);
+
+        assert_eq!(
+            bs.put_block(&[42; 8], "not a block".as_bytes()),
+            Err(BeaconBlockStoreError::InvalidBlockHash)
+        );
+        assert_eq!(
+            bs.put_block(&Hash256::zero(), "not a block".as_bytes()),
+            Err(BeaconBlockStoreError::InvalidBeaconBlock)
+        );
+    }
+
+    #[test]
+    fn test_delete_block_indexes() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        let genesis = put_test_block(&bs, "genesis", 0, &Hash256::zero());
+        let a = put_test_block(&bs, "a", 1, &genesis);
+        let b = put_test_block(&bs, "b", 1, &genesis);
+        bs.set_canonical_head(&a).unwrap();
+
+        bs.delete_block(&a).unwrap();
+        assert!(!bs.block_exists(&a).unwrap());
+        assert_eq!(bs.block_slot(&a).unwrap(), None);
+        assert_eq!(bs.block_children(&genesis).unwrap(), vec![b.to_vec()]);
+        assert_eq!(bs.canonical_block_hash_at_slot(1).unwrap(), None);
+
+        /*
+         * The children index of a deleted block is removed along with it.
+         */
+        let children_key = bs.get_db_key_for_hash(&KeyPrefixes::Children, &genesis);
+        assert!(db.exists(DB_COLUMN, &children_key).unwrap());
+        bs.delete_block(&genesis).unwrap();
+        assert!(!db.exists(DB_COLUMN, &children_key).unwrap());
+        assert_eq!(bs.block_slot(&b).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_delete_blocks_in_batch() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        let genesis = put_test_block(&bs, "genesis", 0, &Hash256::zero());
+        let a = put_test_block(&bs, "a", 1, &genesis);
+        let b = put_test_block(&bs, "b", 1, &genesis);
+        let c = put_test_block(&bs, "c", 2, &a);
+
+        /*
+         * Sibling blocks are both removed from the children index of their parent.
+         */
+        let mut batch = WriteBatch::new();
+        for hash in &[a, b, c] {
+            bs.delete_block_in_batch(&mut batch, hash).unwrap();
+        }
+        assert!(bs.block_exists(&a).unwrap());
+
+        db.write_batch(batch).unwrap();
+        for hash in &[a, b, c] {
+            assert!(!bs.block_exists(hash).unwrap());
+        }
+        assert!(bs.block_children(&genesis).unwrap().is_empty());
+        assert!(bs.block_children(&a).unwrap().is_empty());
+        assert_eq!(bs.block_hashes().unwrap(), vec![genesis.to_vec()]);
+    }
+
+    #[test]
+    fn test_set_canonical_head() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        /*
+         * genesis(0) -> a(1) -> b(2) -> c(4)
+         *            \
+         *             -> x(2) -> y(3)
+         */
+        let genesis = put_test_block(&bs, "genesis", 0, &Hash256::zero());
+        let a = put_test_block(&bs, "a", 1, &genesis);
+        let b = put_test_block(&bs, "b", 2, &a);
+        let c = put_test_block(&bs, "c", 4, &b);
+        let x = put_test_block(&bs, "x", 2, &genesis);
+        let y = put_test_block(&bs, "y", 3, &x);
+
+        assert_eq!(bs.canonical_head().unwrap(), None);
+
+        bs.set_canonical_head(&c).unwrap();
+        assert_eq!(bs.canonical_head().unwrap(), Some(c.to_vec()));
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(0).unwrap(),
+            Some(genesis.to_vec())
+        );
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(1).unwrap(),
+            Some(a.to_vec())
+        );
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(2).unwrap(),
+            Some(b.to_vec())
+        );
+        assert_eq!(bs.canonical_block_hash_at_slot(3).unwrap(), None);
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(4).unwrap(),
+            Some(c.to_vec())
+        );
+
+        /*
+         * Re-org to the shorter `y` chain.
+         */
+        bs.set_canonical_head(&y).unwrap();
+        assert_eq!(bs.canonical_head().unwrap(), Some(y.to_vec()));
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(0).unwrap(),
+            Some(genesis.to_vec())
+        );
+        assert_eq!(bs.canonical_block_hash_at_slot(1).unwrap(), None);
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(2).unwrap(),
+            Some(x.to_vec())
+        );
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(3).unwrap(),
+            Some(y.to_vec())
+        );
+        assert_eq!(bs.canonical_block_hash_at_slot(4).unwrap(), None);
+
+        /*
+         * Lookups from both chains return the correct ancestors.
+         */
+        let (hash, _) = bs.block_at_slot(&y, 0).unwrap().unwrap();
+        assert_eq!(hash, genesis.to_vec());
+        assert_eq!(bs.block_at_slot(&y, 1).unwrap(), None);
+        let (hash, _) = bs.block_at_slot(&c, 1).unwrap().unwrap();
+        assert_eq!(hash, a.to_vec());
+        let (hash, _) = bs.block_at_slot(&c, 2).unwrap().unwrap();
+        assert_eq!(hash, b.to_vec());
+
+        assert_eq!(
+            bs.set_canonical_head(&Hash256::from("unknown".as_bytes())),
+            Err(BeaconBlockStoreError::UnknownBeaconBlock)
+        );
+    }
+
+    #[test]
+    fn test_put_block_and_set_canonical_head_in_batch() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        let genesis = put_test_block(&bs, "genesis", 0, &Hash256::zero());
+        bs.set_canonical_head(&genesis).unwrap();
+
+        /*
+         * A block stored by a batch may be made the canonical head by the same batch, nothing is
+         * written until the batch is applied.
+         */
+        let a = Hash256::from("a".as_bytes());
+        let mut block = BeaconBlock::zero();
+        block.slot = 1;
+        block.ancestor_hashes.push(genesis);
+        let mut s = SszStream::new();
+        s.append(&block);
+        let ssz = s.drain();
+
+        let mut batch = WriteBatch::new();
+        bs.put_block_in_batch(&mut batch, &a, &ssz).unwrap();
+        bs.set_canonical_head_in_batch(&mut batch, &a).unwrap();
+        assert!(!bs.block_exists(&a).unwrap());
+        assert_eq!(bs.canonical_head().unwrap(), Some(genesis.to_vec()));
+
+        db.write_batch(batch).unwrap();
+        assert_eq!(bs.get_serialized_block(&a).unwrap(), Some(ssz));
+        assert_eq!(bs.block_children(&genesis).unwrap(), vec![a.to_vec()]);
+        assert_eq!(bs.canonical_head().unwrap(), Some(a.to_vec()));
+        assert_eq!(
+            bs.canonical_block_hash_at_slot(1).unwrap(),
+            Some(a.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_block_hashes() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        assert!(bs.block_hashes().unwrap().is_empty());
+
+        let genesis = put_test_block(&bs, "genesis", 0, &Hash256::zero());
+        let a = put_test_block(&bs, "a", 1, &genesis);
+        bs.set_canonical_head(&a).unwrap();
+
+        let mut expected = vec![genesis.to_vec(), a.to_vec()];
+        expected.sort();
+        assert_eq!(bs.block_hashes().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_canonical_block_hashes() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        let genesis = put_test_block(&bs, "genesis", 0, &Hash256::zero());
+        let a = put_test_block(&bs, "a", 1, &genesis);
+        let b = put_test_block(&bs, "b", 3, &a);
+        let c = put_test_block(&bs, "c", 256, &b);
+        bs.set_canonical_head(&c).unwrap();
+
+        assert_eq!(
+            bs.canonical_block_hashes(0, 1000).unwrap(),
+            vec![
+                (0, genesis.to_vec()),
+                (1, a.to_vec()),
+                (3, b.to_vec()),
+                (256, c.to_vec())
+            ]
+        );
+        assert_eq!(
+            bs.canonical_block_hashes(1, 3).unwrap(),
+            vec![(1, a.to_vec())]
+        );
+        assert!(bs.canonical_block_hashes(4, 256).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_block_at_slot_long_chain() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+
+        let chain_length = 10_000;
+
+        let mut parent_hash = Hash256::zero();
+        let mut hashes = vec![];
+        for slot in 0..chain_length {
+            let hash = put_test_block(&bs, &format!("block_{}", slot), slot, &parent_hash);
+            hashes.push(hash);
+            parent_hash = hash;
+        }
+
+        /*
+         * A long non-canonical chain is walked iteratively.
+         */
+        let (hash, _) = bs.block_at_slot(&parent_hash, 0).unwrap().unwrap();
+        assert_eq!(hash, hashes[0].to_vec());
+
+        bs.set_canonical_head(&parent_hash).unwrap();
+        let (hash, _) = bs.block_at_slot(&parent_hash, 42).unwrap().unwrap();
+        assert_eq!(hash, hashes[42].to_vec());
     }
 }
@@ -0,0 +1,147 @@
+extern crate ssz;
+extern crate types;
+
+use self::ssz::{ssz_encode, Decodable};
+use self::types::Hash256;
+use super::CHAIN_METADATA_DB_COLUMN as DB_COLUMN;
+use super::{ClientDB, DBError, WriteBatch};
+use std::sync::Arc;
+
+/// The key storing the chain metadata.
+const CHAIN_METADATA_KEY: &[u8] = b"chain_metadata";
+
+/// The most head blocks which may be stored in the chain metadata.
+pub const MAX_HEAD_BLOCK_HASHES: usize = 1_024;
+
+#[derive(Debug, PartialEq)]
+pub enum ChainMetadataStoreError {
+    DBError(String),
+    DecodeError,
+}
+
+impl From<DBError> for ChainMetadataStoreError {
+    fn from(error: DBError) -> Self {
+        ChainMetadataStoreError::DBError(error.message)
+    }
+}
+
+/// The parts of the beacon chain which are not stored as blocks or states, but are required to
+/// resume the chain after a restart.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ChainMetadata {
+    /// The hash of the genesis block.
+    pub genesis_block_hash: Hash256,
+    /// The last slot which has been finalized.
+    pub last_finalized_slot: u64,
+    /// The hash of the finalized block, the root of the fork choice.
+    pub finalized_block_hash: Hash256,
+    /// All block heads (tips of chains).
+    #[ssz(max_len = "MAX_HEAD_BLOCK_HASHES")]
+    pub head_block_hashes: Vec<Hash256>,
+    /// The index of the canonical block in `head_block_hashes`.
+    pub canonical_head_block_hash: usize,
+}
+
+pub struct ChainMetadataStore<T>
+where
+    T: ClientDB,
+{
+    db: Arc<T>,
+}
+
+impl<T: ClientDB> ChainMetadataStore<T> {
+    pub fn new(db: Arc<T>) -> Self {
+        Self { db }
+    }
+
+    pub fn put_metadata(&self, metadata: &ChainMetadata) -> Result<(), ChainMetadataStoreError> {
+        self.db
+            .put(DB_COLUMN, CHAIN_METADATA_KEY, &ssz_encode(metadata))
+            .map_err(ChainMetadataStoreError::from)
+    }
+
+    /// As per `put_metadata`, but the write is added to `batch` rather than applied.
+    pub fn put_metadata_in_batch(&self, batch: &mut WriteBatch, metadata: &ChainMetadata) {
+        batch.put(DB_COLUMN, CHAIN_METADATA_KEY, &ssz_encode(metadata));
+    }
+
+    /// Returns the stored chain metadata, or `None` if no chain has been stored.
+    pub fn get_metadata(&self) -> Result<Option<ChainMetadata>, ChainMetadataStoreError> {
+        match self.db.get(DB_COLUMN, CHAIN_METADATA_KEY)? {
+            None => Ok(None),
+            Some(ssz) => {
+                let (metadata, _) = ChainMetadata::ssz_decode(&ssz, 0)
+                    .map_err(|_| ChainMetadataStoreError::DecodeError)?;
+                Ok(Some(metadata))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::MemoryDB;
+    use super::*;
+
+    #[test]
+    fn test_put_get_metadata() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ChainMetadataStore::new(db.clone());
+
+        assert_eq!(store.get_metadata(), Ok(None));
+
+        let metadata = ChainMetadata {
+            genesis_block_hash: Hash256::from("genesis".as_bytes()),
+            last_finalized_slot: 42,
+            finalized_block_hash: Hash256::from("finalized".as_bytes()),
+            head_block_hashes: vec![Hash256::from("a".as_bytes()), Hash256::from("b".as_bytes())],
+            canonical_head_block_hash: 1,
+        };
+        store.put_metadata(&metadata).unwrap();
+        assert_eq!(store.get_metadata(), Ok(Some(metadata.clone())));
+
+        /*
+         * Storing new metadata replaces the existing metadata.
+         */
+        let mut metadata = metadata;
+        metadata.head_block_hashes.pop();
+        metadata.canonical_head_block_hash = 0;
+        store.put_metadata(&metadata).unwrap();
+        assert_eq!(store.get_metadata(), Ok(Some(metadata)));
+    }
+
+    #[test]
+    fn test_too_many_head_block_hashes() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ChainMetadataStore::new(db.clone());
+
+        let mut metadata = ChainMetadata {
+            genesis_block_hash: Hash256::zero(),
+            last_finalized_slot: 0,
+            finalized_block_hash: Hash256::zero(),
+            head_block_hashes: vec![Hash256::zero(); MAX_HEAD_BLOCK_HASHES],
+            canonical_head_block_hash: 0,
+        };
+        store.put_metadata(&metadata).unwrap();
+        assert_eq!(store.get_metadata(), Ok(Some(metadata.clone())));
+
+        metadata.head_block_hashes.push(Hash256::zero());
+        store.put_metadata(&metadata).unwrap();
+        assert_eq!(
+            store.get_metadata(),
+            Err(ChainMetadataStoreError::DecodeError)
+        );
+    }
+
+    #[test]
+    fn test_invalid_metadata_ssz() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ChainMetadataStore::new(db.clone());
+
+        db.put(DB_COLUMN, CHAIN_METADATA_KEY, &[0, 1, 2]).unwrap();
+        assert_eq!(
+            store.get_metadata(),
+            Err(ChainMetadataStoreError::DecodeError)
+        );
+    }
+}
@@ -1,11 +1,17 @@
-use super::{ClientDB, DBError};
+use super::{ClientDB, DBError, WriteBatch};
 
 mod beacon_block_store;
+mod chain_metadata_store;
 mod pow_chain_store;
+mod state_store;
 mod validator_store;
 
-pub use self::beacon_block_store::{BeaconBlockAtSlotError, BeaconBlockStore};
+pub use self::beacon_block_store::{BeaconBlockStore, BeaconBlockStoreError};
+pub use self::chain_metadata_store::{
+    ChainMetadata, ChainMetadataStore, ChainMetadataStoreError, MAX_HEAD_BLOCK_HASHES,
+};
 pub use self::pow_chain_store::PoWChainStore;
+pub use self::state_store::{StateStore, StateStoreError};
 pub use self::validator_store::{ValidatorStore, ValidatorStoreError};
 
 use super::bls;
@@ -13,5 +19,15 @@ use super::bls;
 pub const BLOCKS_DB_COLUMN: &str = "blocks";
 pub const POW_CHAIN_DB_COLUMN: &str = "powchain";
 pub const VALIDATOR_DB_COLUMN: &str = "validator";
+pub const ACTIVE_STATE_DB_COLUMN: &str = "active_state";
+pub const CRYSTALLIZED_STATE_DB_COLUMN: &str = "crystallized_state";
+pub const CHAIN_METADATA_DB_COLUMN: &str = "chain_metadata";
 
-pub const COLUMNS: [&str; 3] = [BLOCKS_DB_COLUMN, POW_CHAIN_DB_COLUMN, VALIDATOR_DB_COLUMN];
+pub const COLUMNS: [&str; 6] = [
+    BLOCKS_DB_COLUMN,
+    POW_CHAIN_DB_COLUMN,
+    VALIDATOR_DB_COLUMN,
+    ACTIVE_STATE_DB_COLUMN,
+    CRYSTALLIZED_STATE_DB_COLUMN,
+    CHAIN_METADATA_DB_COLUMN,
+];
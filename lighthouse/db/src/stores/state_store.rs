@@ -0,0 +1,275 @@
+extern crate ssz;
+extern crate types;
+
+use self::ssz::{ssz_encode, Decodable};
+use self::types::{ActiveState, CrystallizedState, Hash256};
+use super::{ClientDB, DBError, WriteBatch};
+use super::{ACTIVE_STATE_DB_COLUMN, CRYSTALLIZED_STATE_DB_COLUMN};
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq)]
+pub enum StateStoreError {
+    DBError(String),
+    DecodeError,
+}
+
+impl From<DBError> for StateStoreError {
+    fn from(error: DBError) -> Self {
+        StateStoreError::DBError(error.message)
+    }
+}
+
+/// Stores active and crystallized states, keyed by their canonical root.
+pub struct StateStore<T>
+where
+    T: ClientDB,
+{
+    db: Arc<T>,
+}
+
+impl<T: ClientDB> StateStore<T> {
+    pub fn new(db: Arc<T>) -> Self {
+        Self { db }
+    }
+
+    pub fn put_active_state(
+        &self,
+        root: &Hash256,
+        state: &ActiveState,
+    ) -> Result<(), StateStoreError> {
+        self.db
+            .put(ACTIVE_STATE_DB_COLUMN, root, &ssz_encode(state))
+            .map_err(StateStoreError::from)
+    }
+
+    /// As per `put_active_state`, but the write is added to `batch` rather than applied.
+    pub fn put_active_state_in_batch(
+        &self,
+        batch: &mut WriteBatch,
+        root: &Hash256,
+        state: &ActiveState,
+    ) {
+        batch.put(ACTIVE_STATE_DB_COLUMN, root, &ssz_encode(state));
+    }
+
+    pub fn get_active_state(&self, root: &Hash256) -> Result<Option<ActiveState>, StateStoreError> {
+        self.get(ACTIVE_STATE_DB_COLUMN, root)
+    }
+
+    pub fn delete_active_state(&self, root: &Hash256) -> Result<(), StateStoreError> {
+        self.db
+            .delete(ACTIVE_STATE_DB_COLUMN, root)
+            .map_err(StateStoreError::from)
+    }
+
+    /// As per `delete_active_state`, but the deletion is added to `batch` rather than applied.
+    pub fn delete_active_state_in_batch(&self, batch: &mut WriteBatch, root: &Hash256) {
+        batch.delete(ACTIVE_STATE_DB_COLUMN, root);
+    }
+
+    /// Returns all stored active states with their root.
+    pub fn active_states(&self) -> Result<Vec<(Hash256, ActiveState)>, StateStoreError> {
+        self.get_all(ACTIVE_STATE_DB_COLUMN)
+    }
+
+    pub fn put_crystallized_state(
+        &self,
+        root: &Hash256,
+        state: &CrystallizedState,
+    ) -> Result<(), StateStoreError> {
+        self.db
+            .put(CRYSTALLIZED_STATE_DB_COLUMN, root, &ssz_encode(state))
+            .map_err(StateStoreError::from)
+    }
+
+    /// As per `put_crystallized_state`, but the write is added to `batch` rather than applied.
+    pub fn put_crystallized_state_in_batch(
+        &self,
+        batch: &mut WriteBatch,
+        root: &Hash256,
+        state: &CrystallizedState,
+    ) {
+        batch.put(CRYSTALLIZED_STATE_DB_COLUMN, root, &ssz_encode(state));
+    }
+
+    pub fn get_crystallized_state(
+        &self,
+        root: &Hash256,
+    ) -> Result<Option<CrystallizedState>, StateStoreError> {
+        self.get(CRYSTALLIZED_STATE_DB_COLUMN, root)
+    }
+
+    pub fn delete_crystallized_state(&self, root: &Hash256) -> Result<(), StateStoreError> {
+        self.db
+            .delete(CRYSTALLIZED_STATE_DB_COLUMN, root)
+            .map_err(StateStoreError::from)
+    }
+
+    /// As per `delete_crystallized_state`, but the deletion is added to `batch` rather than
+    /// applied.
+    pub fn delete_crystallized_state_in_batch(&self, batch: &mut WriteBatch, root: &Hash256) {
+        batch.delete(CRYSTALLIZED_STATE_DB_COLUMN, root);
+    }
+
+    /// Returns all stored crystallized states with their root.
+    pub fn crystallized_states(
+        &self,
+    ) -> Result<Vec<(Hash256, CrystallizedState)>, StateStoreError> {
+        self.get_all(CRYSTALLIZED_STATE_DB_COLUMN)
+    }
+
+    fn get<S: Decodable>(&self, col: &str, root: &Hash256) -> Result<Option<S>, StateStoreError> {
+        match self.db.get(col, root)? {
+            None => Ok(None),
+            Some(ssz) => {
+                let (state, _) =
+                    S::ssz_decode(&ssz, 0).map_err(|_| StateStoreError::DecodeError)?;
+                Ok(Some(state))
+            }
+        }
+    }
+
+    fn get_all<S: Decodable>(&self, col: &str) -> Result<Vec<(Hash256, S)>, StateStoreError> {
+        let mut states = vec![];
+        for (key, ssz) in self.db.iter_column(col)? {
+            if key.len() != 32 {
+                return Err(StateStoreError::DecodeError);
+            }
+            let (state, _) = S::ssz_decode(&ssz, 0).map_err(|_| StateStoreError::DecodeError)?;
+            states.push((Hash256::from(&key[..]), state));
+        }
+        Ok(states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::MemoryDB;
+    use super::*;
+
+    fn active_state(randao_mix: &str) -> ActiveState {
+        ActiveState {
+            pending_attestations: vec![],
+            pending_specials: vec![],
+            recent_block_hashes: vec![Hash256::zero()],
+            randao_mix: Hash256::from(randao_mix.as_bytes()),
+        }
+    }
+
+    fn crystallized_state(last_finalized_slot: u64) -> CrystallizedState {
+        CrystallizedState {
+            validator_set_change_slot: 0,
+            validators: vec![],
+            crosslinks: vec![],
+            last_state_recalculation_slot: 0,
+            last_finalized_slot,
+            last_justified_slot: 0,
+            previous_justified_slot: 0,
+            justified_streak: 0,
+            shard_and_committee_for_slots: vec![],
+            deposits_penalized_in_period: vec![],
+            validator_set_delta_hash_chain: Hash256::zero(),
+            pre_fork_version: 0,
+            post_fork_version: 0,
+            fork_slot_number: 0,
+        }
+    }
+
+    #[test]
+    fn test_put_get_active_state() {
+        let db = Arc::new(MemoryDB::open());
+        let store = StateStore::new(db.clone());
+
+        let state = active_state("a");
+        let root = state.canonical_root();
+        assert_eq!(store.get_active_state(&root), Ok(None));
+
+        store.put_active_state(&root, &state).unwrap();
+        assert_eq!(store.get_active_state(&root), Ok(Some(state)));
+        assert_eq!(store.get_crystallized_state(&root), Ok(None));
+    }
+
+    #[test]
+    fn test_put_get_crystallized_state() {
+        let db = Arc::new(MemoryDB::open());
+        let store = StateStore::new(db.clone());
+
+        let state = crystallized_state(7);
+        let root = state.canonical_root();
+        assert_eq!(store.get_crystallized_state(&root), Ok(None));
+
+        store.put_crystallized_state(&root, &state).unwrap();
+        assert_eq!(store.get_crystallized_state(&root), Ok(Some(state)));
+        assert_eq!(store.get_active_state(&root), Ok(None));
+    }
+
+    #[test]
+    fn test_delete_states() {
+        let db = Arc::new(MemoryDB::open());
+        let store = StateStore::new(db.clone());
+
+        let act_state = active_state("a");
+        let act_root = act_state.canonical_root();
+        let cry_state = crystallized_state(1);
+        let cry_root = cry_state.canonical_root();
+        store.put_active_state(&act_root, &act_state).unwrap();
+        store.put_crystallized_state(&cry_root, &cry_state).unwrap();
+
+        store.delete_active_state(&act_root).unwrap();
+        assert_eq!(store.get_active_state(&act_root), Ok(None));
+        assert_eq!(store.get_crystallized_state(&cry_root), Ok(Some(cry_state)));
+
+        store.delete_crystallized_state(&cry_root).unwrap();
+        assert_eq!(store.get_crystallized_state(&cry_root), Ok(None));
+    }
+
+    #[test]
+    fn test_all_states() {
+        let db = Arc::new(MemoryDB::open());
+        let store = StateStore::new(db.clone());
+
+        let active: Vec<ActiveState> = vec![active_state("a"), active_state("b")];
+        for state in &active {
+            store
+                .put_active_state(&state.canonical_root(), state)
+                .unwrap();
+        }
+        let crystallized: Vec<CrystallizedState> =
+            vec![crystallized_state(1), crystallized_state(2)];
+        for state in &crystallized {
+            store
+                .put_crystallized_state(&state.canonical_root(), state)
+                .unwrap();
+        }
+
+        let mut stored_active = store.active_states().unwrap();
+        stored_active.sort_by_key(|(_, state)| state.randao_mix);
+        assert_eq!(stored_active.len(), 2);
+        for ((root, stored), state) in stored_active.iter().zip(&active) {
+            assert_eq!(*root, state.canonical_root());
+            assert_eq!(stored, state);
+        }
+
+        let mut stored_crystallized = store.crystallized_states().unwrap();
+        stored_crystallized.sort_by_key(|(_, state)| state.last_finalized_slot);
+        assert_eq!(stored_crystallized.len(), 2);
+        for ((root, stored), state) in stored_crystallized.iter().zip(&crystallized) {
+            assert_eq!(*root, state.canonical_root());
+            assert_eq!(stored, state);
+        }
+    }
+
+    #[test]
+    fn test_invalid_state_ssz() {
+        let db = Arc::new(MemoryDB::open());
+        let store = StateStore::new(db.clone());
+
+        let root = Hash256::from("root".as_bytes());
+        db.put(ACTIVE_STATE_DB_COLUMN, &root, &[0, 1, 2]).unwrap();
+        assert_eq!(
+            store.get_active_state(&root),
+            Err(StateStoreError::DecodeError)
+        );
+        assert_eq!(store.active_states(), Err(StateStoreError::DecodeError));
+    }
+}
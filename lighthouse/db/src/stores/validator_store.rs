@@ -1,9 +1,10 @@
 extern crate bytes;
 
-use self::bytes::{BufMut, BytesMut};
+use self::bytes::{Buf, BufMut, BytesMut};
 use super::bls::PublicKey;
 use super::VALIDATOR_DB_COLUMN as DB_COLUMN;
-use super::{ClientDB, DBError};
+use super::{ClientDB, DBError, WriteBatch};
+use std::io::Cursor;
 use std::sync::Arc;
 
 #[derive(Debug, PartialEq)]
@@ -60,6 +61,17 @@ impl<T: ClientDB> ValidatorStore<T> {
             .map_err(ValidatorStoreError::from)
     }
 
+    /// As per `put_public_key_by_index`, but the write is added to `batch` rather than applied.
+    pub fn put_public_key_by_index_in_batch(
+        &self,
+        batch: &mut WriteBatch,
+        index: usize,
+        public_key: &PublicKey,
+    ) {
+        let key = self.get_db_key_for_index(&KeyPrefixes::PublicKey, index);
+        batch.put(DB_COLUMN, &key[..], &public_key.as_bytes()[..]);
+    }
+
     pub fn get_public_key_by_index(
         &self,
         index: usize,
@@ -74,6 +86,22 @@ impl<T: ClientDB> ValidatorStore<T> {
             },
         }
     }
+
+    /// Returns all stored public keys with their validator index, in ascending index order.
+    pub fn get_public_keys(&self) -> Result<Vec<(usize, PublicKey)>, ValidatorStoreError> {
+        let prefix = self.prefix_bytes(&KeyPrefixes::PublicKey);
+        let mut public_keys = vec![];
+        for (key, val) in self.db.iter_prefix(DB_COLUMN, &prefix)? {
+            if key.len() != prefix.len() + 8 {
+                return Err(ValidatorStoreError::DecodeError);
+            }
+            let index = Cursor::new(&key[prefix.len()..]).get_u64_be() as usize;
+            let public_key =
+                PublicKey::from_bytes(&val).map_err(|_| ValidatorStoreError::DecodeError)?;
+            public_keys.push((index, public_key));
+        }
+        Ok(public_keys)
+    }
 }
 
 #[cfg(test)]
@@ -118,6 +146,21 @@ mod tests {
         assert_eq!(public_key_at_index, public_key.as_bytes());
     }
 
+    #[test]
+    fn test_put_public_key_by_index_in_batch() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ValidatorStore::new(db.clone());
+
+        let public_key = Keypair::random().pk;
+
+        let mut batch = WriteBatch::new();
+        store.put_public_key_by_index_in_batch(&mut batch, 5, &public_key);
+        assert_eq!(store.get_public_key_by_index(5).unwrap(), None);
+
+        db.write_batch(batch).unwrap();
+        assert_eq!(store.get_public_key_by_index(5).unwrap(), Some(public_key));
+    }
+
     #[test]
     fn test_get_public_key_by_index() {
         let db = Arc::new(MemoryDB::open());
@@ -167,6 +210,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_public_keys() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ValidatorStore::new(db.clone());
+
+        assert!(store.get_public_keys().unwrap().is_empty());
+
+        /*
+         * Indices are stored big-endian, so they are returned in numerical order regardless of
+         * the order of insertion.
+         */
+        let keys: Vec<Keypair> = (0..3).map(|_| Keypair::random()).collect();
+        store.put_public_key_by_index(256, &keys[0].pk).unwrap();
+        store.put_public_key_by_index(1, &keys[1].pk).unwrap();
+        store.put_public_key_by_index(2, &keys[2].pk).unwrap();
+
+        let public_keys = store.get_public_keys().unwrap();
+        assert_eq!(
+            public_keys,
+            vec![
+                (1, keys[1].pk.clone()),
+                (2, keys[2].pk.clone()),
+                (256, keys[0].pk.clone())
+            ]
+        );
+    }
+
     #[test]
     fn test_validator_store_put_get() {
         let db = Arc::new(MemoryDB::open());
@@ -1,5 +1,8 @@
 pub type DBValue = Vec<u8>;
 
+/// An iterator over the `(key, value)` pairs of a column, in ascending key order.
+pub type DBIterator<'a> = Box<dyn Iterator<Item = (Vec<u8>, DBValue)> + 'a>;
+
 #[derive(Debug)]
 pub struct DBError {
     pub message: String,
@@ -11,6 +14,85 @@ impl DBError {
     }
 }
 
+/// A single operation within a `WriteBatch`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteOp {
+    Put {
+        col: String,
+        key: Vec<u8>,
+        val: Vec<u8>,
+    },
+    Delete {
+        col: String,
+        key: Vec<u8>,
+    },
+}
+
+/// A collection of writes which are to be applied to a `ClientDB` atomically.
+///
+/// Operations are applied in the order they were added, so a later operation on a key supersedes
+/// an earlier one.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WriteBatch {
+    ops: Vec<WriteOp>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self { ops: vec![] }
+    }
+
+    /// Add a `put` of `val` at `key` in `col` to the batch.
+    pub fn put(&mut self, col: &str, key: &[u8], val: &[u8]) {
+        self.ops.push(WriteOp::Put {
+            col: col.to_string(),
+            key: key.to_vec(),
+            val: val.to_vec(),
+        });
+    }
+
+    /// Add a `delete` of `key` in `col` to the batch.
+    pub fn delete(&mut self, col: &str, key: &[u8]) {
+        self.ops.push(WriteOp::Delete {
+            col: col.to_string(),
+            key: key.to_vec(),
+        });
+    }
+
+    /// Returns the value `key` in `col` will hold once the batch is applied, if the batch writes
+    /// to that key.
+    ///
+    /// `Some(None)` is returned if the last operation on the key is a `delete`, `None` is returned
+    /// if the batch does not modify the key.
+    pub fn get(&self, col: &str, key: &[u8]) -> Option<Option<&[u8]>> {
+        self.ops.iter().rev().find_map(|op| match op {
+            WriteOp::Put {
+                col: op_col,
+                key: op_key,
+                val,
+            } if op_col == col && &op_key[..] == key => Some(Some(&val[..])),
+            WriteOp::Delete {
+                col: op_col,
+                key: op_key,
+            } if op_col == col && &op_key[..] == key => Some(None),
+            _ => None,
+        })
+    }
+
+    /// Returns the operations in the batch, in the order in which they were added.
+    pub fn ops(&self) -> &[WriteOp] {
+        &self.ops
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
 /// A generic database to be used by the "client' (i.e.,
 /// the lighthouse blockchain client).
 ///
@@ -25,4 +107,24 @@ pub trait ClientDB: Sync + Send {
     fn exists(&self, col: &str, key: &[u8]) -> Result<bool, DBError>;
 
     fn delete(&self, col: &str, key: &[u8]) -> Result<(), DBError>;
+
+    /// Apply all of the operations in the batch atomically; either every operation is applied or
+    /// none are.
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), DBError>;
+
+    /// Iterate over all entries in some column, in ascending key order.
+    fn iter_column<'a>(&'a self, col: &str) -> Result<DBIterator<'a>, DBError>;
+
+    /// Iterate over all entries in some column with a key starting with `prefix`, in ascending
+    /// key order.
+    fn iter_prefix<'a>(&'a self, col: &str, prefix: &[u8]) -> Result<DBIterator<'a>, DBError>;
+
+    /// Iterate over all entries in some column with a key greater than or equal to `start` and
+    /// less than `end`, in ascending key order.
+    fn iter_range<'a>(
+        &'a self,
+        col: &str,
+        start: &[u8],
+        end: &[u8],
+    ) -> Result<DBIterator<'a>, DBError>;
 }
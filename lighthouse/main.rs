@@ -14,6 +14,7 @@ use std::path::PathBuf;
 
 use clap::{App, Arg};
 use config::LighthouseConfig;
+use db::{DiskDB, DiskDBCompression};
 use slog::Drain;
 
 fn main() {
@@ -38,6 +39,25 @@ fn main() {
                 .value_name("PORT")
                 .help("Network listen port for p2p connections.")
                 .takes_value(true),
+        ).arg(
+            Arg::with_name("db-cache-size")
+                .long("db-cache-size")
+                .value_name("MB")
+                .help("Size of the database block cache, in megabytes.")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("db-compression")
+                .long("db-compression")
+                .value_name("TYPE")
+                .help("Compression applied to the database.")
+                .possible_values(&["none", "snappy", "lz4", "zstd"])
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("db-max-open-files")
+                .long("db-max-open-files")
+                .value_name("COUNT")
+                .help("Maximum number of files the database may keep open, -1 for unlimited.")
+                .takes_value(true),
         ).get_matches();
 
     let mut config = LighthouseConfig::default();
@@ -57,11 +77,57 @@ fn main() {
         }
     }
 
+    // Custom database cache size
+    if let Some(size_str) = matches.value_of("db-cache-size") {
+        let size = size_str
+            .parse::<usize>()
+            .ok()
+            .and_then(|size| size.checked_mul(1024 * 1024));
+        if let Some(size) = size {
+            config.db_config.cache_size = size;
+        } else {
+            error!(log, "Invalid database cache size"; "db-cache-size" => size_str);
+            return;
+        }
+    }
+
+    // Custom database compression
+    if let Some(compression_str) = matches.value_of("db-compression") {
+        if let Ok(compression) = compression_str.parse::<DiskDBCompression>() {
+            config.db_config.compression = compression;
+        } else {
+            error!(log, "Invalid database compression"; "db-compression" => compression_str);
+            return;
+        }
+    }
+
+    // Custom database max open files
+    if let Some(files_str) = matches.value_of("db-max-open-files") {
+        if let Ok(files) = files_str.parse::<i32>() {
+            config.db_config.max_open_files = files;
+        } else {
+            error!(log, "Invalid database max open files"; "db-max-open-files" => files_str);
+            return;
+        }
+    }
+
     // Log configuration
     info!(log, "";
           "data_dir" => &config.data_dir.to_str(),
           "port" => &config.p2p_listen_port);
 
+    // Open the database
+    let _db = match DiskDB::open(&config.data_dir, &config.db_config) {
+        Ok(db) => db,
+        Err(e) => {
+            error!(log, "Unable to open database"; "error" => e.message);
+            return;
+        }
+    };
+    info!(log, "Opened database";
+          "cache_size" => config.db_config.cache_size,
+          "max_open_files" => config.db_config.max_open_files);
+
     error!(
         log,
         "Lighthouse under development and does not provide a user demo."
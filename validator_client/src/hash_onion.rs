@@ -0,0 +1,239 @@
+use hashing::canonical_hash;
+use rand::random;
+use ssz::{decode_ssz_exact, ssz_encode, DecodeError};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::process;
+use types::Hash256;
+
+/// The greatest number of layers of an onion.
+///
+/// Every layer is held in memory and regenerated on load, so neither a caller nor a corrupt file
+/// may request an arbitrary amount of hashing and memory.
+pub const MAX_LAYER_COUNT: u64 = 1 << 20;
+
+#[derive(Debug, PartialEq)]
+pub enum HashOnionError {
+    IoError(String),
+    DecodeError(DecodeError),
+    /// The onion has more than `MAX_LAYER_COUNT` layers.
+    TooManyLayers(u64),
+}
+
+impl From<io::Error> for HashOnionError {
+    fn from(e: io::Error) -> HashOnionError {
+        HashOnionError::IoError(e.to_string())
+    }
+}
+
+impl From<DecodeError> for HashOnionError {
+    fn from(e: DecodeError) -> HashOnionError {
+        HashOnionError::DecodeError(e)
+    }
+}
+
+/// A chain of hashes from which a validator makes its RANDAO reveals.
+///
+/// Each layer is the hash of the layer beneath it, with the secret seed at the centre. The
+/// outermost layer is the initial `randao_commitment` of the validator. Each block proposed by
+/// the validator reveals a layer beneath its current commitment, which then becomes the new
+/// commitment.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HashOnion {
+    layers: Vec<Hash256>,
+}
+
+impl HashOnion {
+    /// Generate an onion with `layer_count` layers around `seed`.
+    ///
+    /// Onions with more than `MAX_LAYER_COUNT` layers are rejected.
+    pub fn generate(seed: Hash256, layer_count: u64) -> Result<Self, HashOnionError> {
+        if layer_count > MAX_LAYER_COUNT {
+            return Err(HashOnionError::TooManyLayers(layer_count));
+        }
+        let mut layers = Vec::with_capacity(layer_count as usize + 1);
+        let mut layer = seed;
+        layers.push(layer);
+        for _ in 0..layer_count {
+            layer = Hash256::from(&canonical_hash(&layer)[..]);
+            layers.push(layer);
+        }
+        Ok(Self { layers })
+    }
+
+    /// Generate an onion with `layer_count` layers around a random seed.
+    pub fn random(layer_count: u64) -> Result<Self, HashOnionError> {
+        let seed: [u8; 32] = random();
+        Self::generate(Hash256::from(seed), layer_count)
+    }
+
+    pub fn seed(&self) -> Hash256 {
+        self.layers[0]
+    }
+
+    pub fn layer_count(&self) -> u64 {
+        self.layers.len() as u64 - 1
+    }
+
+    /// Returns the outermost layer of the onion.
+    pub fn commitment(&self) -> Hash256 {
+        self.layers[self.layers.len() - 1]
+    }
+
+    /// Returns the layer which is `layers` beneath `commitment`, i.e., the reveal which hashes to
+    /// `commitment` when hashed `layers` times.
+    ///
+    /// Returns `None` if `commitment` is not a layer of the onion, or if there are fewer than
+    /// `layers` layers beneath it.
+    pub fn reveal(&self, commitment: &Hash256, layers: u64) -> Option<Hash256> {
+        let position = self.layers.iter().position(|layer| layer == commitment)?;
+        let index = (position as u64).checked_sub(layers)?;
+        Some(self.layers[index as usize])
+    }
+
+    /// Write the onion to a file at `path`, replacing any existing file.
+    ///
+    /// Only the seed and the number of layers are written, the layers are regenerated by `load`.
+    /// The file holds the secret seed, so on unix it is readable and writable by its owner only.
+    ///
+    /// The onion is first written to a temporary file in the same directory, which is synced and
+    /// then renamed over `path`. A failed save therefore leaves any existing file untouched.
+    pub fn save(&self, path: &Path) -> Result<(), HashOnionError> {
+        let mut temp_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+            .to_os_string();
+        temp_name.push(format!(".tmp{}", process::id()));
+        let temp_path = path.with_file_name(temp_name);
+
+        /*
+         * A temporary file left by an earlier failure may have wider permissions, so it is
+         * replaced rather than reused.
+         */
+        let _ = fs::remove_file(&temp_path);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let result = options.open(&temp_path).and_then(|mut file| {
+            file.write_all(&ssz_encode(&(self.seed(), self.layer_count())))?;
+            file.sync_all()?;
+            fs::rename(&temp_path, path)
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result.map_err(HashOnionError::from)
+    }
+
+    /// Read an onion which was written to a file at `path` by `save`.
+    ///
+    /// Onions with more than `MAX_LAYER_COUNT` layers are rejected.
+    pub fn load(path: &Path) -> Result<Self, HashOnionError> {
+        let bytes = fs::read(path)?;
+        let (seed, layer_count) = decode_ssz_exact::<(Hash256, u64)>(&bytes, "HashOnion")?;
+        Self::generate(seed, layer_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    fn repeat_hash(mut hash: Hash256, n: u64) -> Hash256 {
+        for _ in 0..n {
+            hash = Hash256::from(&canonical_hash(&hash)[..]);
+        }
+        hash
+    }
+
+    #[test]
+    fn test_hash_onion_generate() {
+        let seed = Hash256::from("seed".as_bytes());
+        let onion = HashOnion::generate(seed, 10).unwrap();
+
+        assert_eq!(onion.seed(), seed);
+        assert_eq!(onion.layer_count(), 10);
+        assert_eq!(onion.commitment(), repeat_hash(seed, 10));
+
+        let onion = HashOnion::generate(seed, 0).unwrap();
+        assert_eq!(onion.commitment(), seed);
+
+        for &layer_count in &[MAX_LAYER_COUNT + 1, u64::max_value()] {
+            assert_eq!(
+                HashOnion::generate(seed, layer_count),
+                Err(HashOnionError::TooManyLayers(layer_count))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_onion_reveal() {
+        let onion = HashOnion::random(10).unwrap();
+        let commitment = onion.commitment();
+
+        let reveal = onion.reveal(&commitment, 3).unwrap();
+        assert_eq!(repeat_hash(reveal, 3), commitment);
+
+        let next_reveal = onion.reveal(&reveal, 7).unwrap();
+        assert_eq!(next_reveal, onion.seed());
+        assert_eq!(onion.reveal(&next_reveal, 1), None);
+
+        assert_eq!(onion.reveal(&commitment, 11), None);
+        assert_eq!(onion.reveal(&Hash256::from("unknown".as_bytes()), 1), None);
+    }
+
+    #[test]
+    fn test_hash_onion_save_and_load() {
+        let path = env::temp_dir().join(format!(
+            "lighthouse_test_hash_onion_save_and_load_{}",
+            process::id()
+        ));
+        let onion = HashOnion::random(16).unwrap();
+
+        onion.save(&path).unwrap();
+        assert_eq!(HashOnion::load(&path), Ok(onion.clone()));
+
+        /*
+         * The temporary file is renamed over the target, it does not remain.
+         */
+        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+        assert!(!fs::read_dir(env::temp_dir()).unwrap().any(|entry| {
+            let entry_name = entry.unwrap().file_name();
+            let entry_name = entry_name.to_str().unwrap_or("");
+            entry_name.starts_with(&file_name) && entry_name != file_name
+        }));
+
+        /*
+         * The file is private, even if it existed with wider permissions.
+         */
+        #[cfg(unix)]
+        {
+            let mode = || fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(), 0o600);
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+            onion.save(&path).unwrap();
+            assert_eq!(mode(), 0o600);
+        }
+
+        let seed = Hash256::from("seed".as_bytes());
+        fs::write(&path, ssz_encode(&(seed, MAX_LAYER_COUNT + 1))).unwrap();
+        assert_eq!(
+            HashOnion::load(&path),
+            Err(HashOnionError::TooManyLayers(MAX_LAYER_COUNT + 1))
+        );
+
+        fs::write(&path, vec![0; 3]).unwrap();
+        assert!(HashOnion::load(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+        assert!(HashOnion::load(&path).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+extern crate hashing;
+extern crate rand;
+extern crate ssz;
+extern crate types;
+
+mod hash_onion;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+pub use hash_onion::{HashOnion, HashOnionError, MAX_LAYER_COUNT};
@@ -0,0 +1,10 @@
+use hash_onion::HashOnion;
+use types::Hash256;
+
+/// Returns the hash onion of the validator at `validator_index` in tests.
+///
+/// The seed is derived from the index, so test states and chains may commit to the onion and
+/// tests may later reveal its layers without storing it.
+pub fn test_hash_onion(validator_index: usize) -> HashOnion {
+    HashOnion::generate(Hash256::from(validator_index as u64 + 1), 64).unwrap()
+}